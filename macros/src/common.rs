@@ -0,0 +1,103 @@
+use graphql_parser::query::{Directive, Type, Value};
+use graphql_parser::Pos;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+pub fn pos(pos: &Pos) -> TokenStream {
+    let line = pos.line;
+    let column = pos.column;
+    quote!(::graphql_parser::Pos { line: #line, column: #column })
+}
+
+pub fn opt_str(name: Option<&str>) -> TokenStream {
+    match name {
+        Some(name) => quote!(::std::option::Option::Some(#name)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+pub fn description(descr: &Option<String>) -> TokenStream {
+    match *descr {
+        Some(ref descr) => {
+            quote!(::std::option::Option::Some(::std::string::String::from(#descr)))
+        }
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+pub fn value(val: &Value) -> TokenStream {
+    let path = quote!(::graphql_parser::query::Value);
+    match *val {
+        Value::Variable(name) => quote!(#path::Variable(#name)),
+        Value::Int(ref num) => {
+            let num = num.as_i64().expect("integers are stored as i64");
+            quote!(#path::Int(::graphql_parser::query::Number::from(#num)))
+        }
+        Value::Float(num) => {
+            let bits = num.to_bits();
+            quote!(#path::Float(f64::from_bits(#bits)))
+        }
+        Value::String(ref val) => quote!(#path::String(::std::string::String::from(#val))),
+        Value::Boolean(val) => quote!(#path::Boolean(#val)),
+        Value::Null => quote!(#path::Null),
+        Value::Enum(name) => quote!(#path::Enum(#name)),
+        Value::List(ref items) => {
+            let items = items.iter().map(value);
+            quote!(#path::List(::std::vec![#(#items),*]))
+        }
+        Value::Object(ref fields) => {
+            let names = fields.keys();
+            let values = fields.values().map(value);
+            quote!(#path::Object({
+                let mut fields = ::std::collections::BTreeMap::new();
+                #(fields.insert(#names, #values);)*
+                fields
+            }))
+        }
+    }
+}
+
+pub fn opt_value(val: &Option<Value>) -> TokenStream {
+    match *val {
+        Some(ref val) => {
+            let val = value(val);
+            quote!(::std::option::Option::Some(#val))
+        }
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+pub fn var_type(typ: &Type) -> TokenStream {
+    let path = quote!(::graphql_parser::query::Type);
+    match *typ {
+        Type::NamedType(name) => quote!(#path::NamedType(#name)),
+        Type::ListType(ref inner) => {
+            let inner = var_type(inner);
+            quote!(#path::ListType(::std::boxed::Box::new(#inner)))
+        }
+        Type::NonNullType(ref inner) => {
+            let inner = var_type(inner);
+            quote!(#path::NonNullType(::std::boxed::Box::new(#inner)))
+        }
+    }
+}
+
+pub fn arguments(args: &[(&str, Value)]) -> TokenStream {
+    let names = args.iter().map(|&(name, _)| name);
+    let values = args.iter().map(|(_, val)| value(val));
+    quote!(::std::vec![#((#names, #values)),*])
+}
+
+pub fn directives(dirs: &[Directive]) -> TokenStream {
+    let dirs = dirs.iter().map(|dir| {
+        let position = pos(&dir.position);
+        let name = dir.name;
+        let arguments = arguments(&dir.arguments);
+        quote!(::graphql_parser::query::Directive {
+            position: #position,
+            name: #name,
+            arguments: #arguments,
+        })
+    });
+    quote!(::std::vec![#(#dirs),*])
+}
@@ -0,0 +1,116 @@
+//! Compile-time validated GraphQL literals
+//! ======================================
+//!
+//! The [`query!`] and [`schema!`] macros parse a string literal with
+//! `graphql_parser::parse_query` and `graphql_parser::parse_schema` while
+//! the crate is being compiled. Syntax errors are reported as compile errors
+//! pointing into the literal, and a successfully parsed document is expanded
+//! into an expression constructing the AST, so there is no parsing at
+//! runtime at all.
+//!
+//! The macros live in a separate crate because procedural macros have to,
+//! and because they use `graphql-parser` itself to parse the literals,
+//! `graphql-parser` can't depend on (and reexport) them. Add both crates to
+//! your dependencies:
+//!
+//! ```rust
+//! # extern crate graphql_parser;
+//! # extern crate graphql_parser_macros;
+//! use graphql_parser::query::Document;
+//! use graphql_parser_macros::query;
+//!
+//! let ast: Document<'static> = query!("query MyQuery { field1, field2 }");
+//! assert_eq!(ast.to_string(), "\
+//! query MyQuery {
+//!   field1
+//!   field2
+//! }
+//! ");
+//! ```
+//!
+//! Invalid documents don't compile:
+//!
+//! ```compile_fail
+//! # extern crate graphql_parser_macros;
+//! use graphql_parser_macros::query;
+//!
+//! let ast = query!("query MyQuery { field1(x: ) }");
+//! ```
+extern crate proc_macro;
+
+mod common;
+mod query;
+mod schema;
+
+use graphql_parser::Pos;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use syn::{parse_macro_input, LitStr};
+
+/// Parses a query document at compile time
+///
+/// Expands to an expression of type `graphql_parser::query::Document<'static>`.
+#[proc_macro]
+pub fn query(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    let source = lit.value();
+    match graphql_parser::parse_query(&source) {
+        Ok(doc) => query::document(&doc).into(),
+        Err(e) => error(&lit, &source, e.position(), &e.to_string()),
+    }
+}
+
+/// Parses a schema document at compile time
+///
+/// Expands to an expression of type `graphql_parser::schema::Document<'static>`.
+#[proc_macro]
+pub fn schema(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    let source = lit.value();
+    match graphql_parser::parse_schema(&source) {
+        Ok(doc) => schema::document(&doc).into(),
+        Err(e) => error(&lit, &source, e.position(), &e.to_string()),
+    }
+}
+
+fn error(lit: &LitStr, source: &str, pos: Pos, message: &str) -> TokenStream {
+    syn::Error::new(error_span(lit, source, pos), message.trim_end())
+        .to_compile_error()
+        .into()
+}
+
+/// Finds the span of the character at `pos` inside of the literal
+///
+/// Falls back to the span of the whole literal when the compiler can't
+/// produce sub-spans or when offsets in the source code can't be computed
+/// from the position: escape sequences change offsets, and columns of the
+/// parser don't match bytes for non-ASCII text, tabs and carriage returns.
+fn error_span(lit: &LitStr, source: &str, pos: Pos) -> Span {
+    if !source.is_ascii() || source.contains(['\t', '\r']) {
+        return lit.span();
+    }
+    let token = lit.token();
+    let repr = token.to_string();
+    let prefix = if repr.starts_with('r') {
+        match repr.find('"') {
+            Some(quote) => quote + 1,
+            None => return lit.span(),
+        }
+    } else if repr.contains('\\') {
+        return lit.span();
+    } else {
+        1
+    };
+    let mut offset = 0;
+    for (idx, line) in source.split('\n').enumerate() {
+        if idx + 1 == pos.line {
+            offset += pos.column.saturating_sub(1).min(line.len());
+            break;
+        }
+        offset += line.len() + 1;
+    }
+    let start = prefix + offset.min(source.len());
+    token
+        .subspan(start..start + 1)
+        .unwrap_or_else(|| lit.span())
+}
@@ -0,0 +1,161 @@
+use graphql_parser::query::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::common::{arguments, directives, opt_str, opt_value, pos, var_type};
+
+pub fn document(doc: &Document) -> TokenStream {
+    let definitions = doc.definitions.iter().map(definition);
+    quote!(::graphql_parser::query::Document {
+        definitions: ::std::vec![#(#definitions),*],
+    })
+}
+
+fn definition(def: &Definition) -> TokenStream {
+    let path = quote!(::graphql_parser::query::Definition);
+    match *def {
+        Definition::Operation(ref op) => {
+            let op = operation(op);
+            quote!(#path::Operation(#op))
+        }
+        Definition::Fragment(ref frag) => {
+            let position = pos(&frag.position);
+            let name = frag.name;
+            let type_condition = type_condition(&frag.type_condition);
+            let directives = directives(&frag.directives);
+            let selection_set = selection_set(&frag.selection_set);
+            quote!(#path::Fragment(::graphql_parser::query::FragmentDefinition {
+                position: #position,
+                name: #name,
+                type_condition: #type_condition,
+                directives: #directives,
+                selection_set: #selection_set,
+            }))
+        }
+    }
+}
+
+fn operation(op: &OperationDefinition) -> TokenStream {
+    let path = quote!(::graphql_parser::query::OperationDefinition);
+    let (kind, position, name, vars, dirs, set) = match *op {
+        OperationDefinition::SelectionSet(ref set) => {
+            let set = selection_set(set);
+            return quote!(#path::SelectionSet(#set));
+        }
+        OperationDefinition::Query(ref q) => (
+            quote!(Query),
+            q.position,
+            q.name,
+            &q.variable_definitions,
+            &q.directives,
+            &q.selection_set,
+        ),
+        OperationDefinition::Mutation(ref m) => (
+            quote!(Mutation),
+            m.position,
+            m.name,
+            &m.variable_definitions,
+            &m.directives,
+            &m.selection_set,
+        ),
+        OperationDefinition::Subscription(ref s) => (
+            quote!(Subscription),
+            s.position,
+            s.name,
+            &s.variable_definitions,
+            &s.directives,
+            &s.selection_set,
+        ),
+    };
+    let position = pos(&position);
+    let name = opt_str(name);
+    let vars = vars.iter().map(variable_definition);
+    let dirs = directives(dirs);
+    let set = selection_set(set);
+    quote!(#path::#kind(::graphql_parser::query::#kind {
+        position: #position,
+        name: #name,
+        variable_definitions: ::std::vec![#(#vars),*],
+        directives: #dirs,
+        selection_set: #set,
+    }))
+}
+
+fn variable_definition(var: &VariableDefinition) -> TokenStream {
+    let position = pos(&var.position);
+    let name = var.name;
+    let var_type = var_type(&var.var_type);
+    let default_value = opt_value(&var.default_value);
+    quote!(::graphql_parser::query::VariableDefinition {
+        position: #position,
+        name: #name,
+        var_type: #var_type,
+        default_value: #default_value,
+    })
+}
+
+fn type_condition(cond: &TypeCondition) -> TokenStream {
+    match *cond {
+        TypeCondition::On(name) => quote!(::graphql_parser::query::TypeCondition::On(#name)),
+    }
+}
+
+fn selection_set(set: &SelectionSet) -> TokenStream {
+    let start = pos(&set.span.0);
+    let end = pos(&set.span.1);
+    let items = set.items.iter().map(selection);
+    quote!(::graphql_parser::query::SelectionSet {
+        span: (#start, #end),
+        items: ::std::vec![#(#items),*],
+    })
+}
+
+fn selection(sel: &Selection) -> TokenStream {
+    let path = quote!(::graphql_parser::query::Selection);
+    match *sel {
+        Selection::Field(ref field) => {
+            let position = pos(&field.position);
+            let alias = opt_str(field.alias);
+            let name = field.name;
+            let arguments = arguments(&field.arguments);
+            let directives = directives(&field.directives);
+            let selection_set = selection_set(&field.selection_set);
+            quote!(#path::Field(::graphql_parser::query::Field {
+                position: #position,
+                alias: #alias,
+                name: #name,
+                arguments: #arguments,
+                directives: #directives,
+                selection_set: #selection_set,
+            }))
+        }
+        Selection::FragmentSpread(ref spread) => {
+            let position = pos(&spread.position);
+            let fragment_name = spread.fragment_name;
+            let directives = directives(&spread.directives);
+            quote!(#path::FragmentSpread(::graphql_parser::query::FragmentSpread {
+                position: #position,
+                fragment_name: #fragment_name,
+                directives: #directives,
+            }))
+        }
+        Selection::InlineFragment(ref frag) => {
+            let position = pos(&frag.position);
+            let type_condition = match frag.type_condition {
+                Some(ref cond) => {
+                    let cond = type_condition(cond);
+                    quote!(::std::option::Option::Some(#cond))
+                }
+                None => quote!(::std::option::Option::None),
+            };
+            let directives = directives(&frag.directives);
+            let selection_set = selection_set(&frag.selection_set);
+            quote!(#path::InlineFragment(::graphql_parser::query::InlineFragment {
+                position: #position,
+                type_condition: #type_condition,
+                directives: #directives,
+                selection_set: #selection_set,
+            }))
+        }
+    }
+}
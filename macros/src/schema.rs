@@ -0,0 +1,291 @@
+use graphql_parser::schema::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::common::{description, directives, opt_str, opt_value, pos, var_type};
+
+pub fn document(doc: &Document) -> TokenStream {
+    let definitions = doc.definitions.iter().map(definition);
+    quote!(::graphql_parser::schema::Document {
+        definitions: ::std::vec![#(#definitions),*],
+    })
+}
+
+fn definition(def: &Definition) -> TokenStream {
+    let path = quote!(::graphql_parser::schema::Definition);
+    match *def {
+        Definition::SchemaDefinition(ref s) => {
+            let position = pos(&s.position);
+            let directives = directives(&s.directives);
+            let query = opt_str(s.query);
+            let mutation = opt_str(s.mutation);
+            let subscription = opt_str(s.subscription);
+            quote!(#path::SchemaDefinition(::graphql_parser::schema::SchemaDefinition {
+                position: #position,
+                directives: #directives,
+                query: #query,
+                mutation: #mutation,
+                subscription: #subscription,
+            }))
+        }
+        Definition::TypeDefinition(ref t) => {
+            let t = type_definition(t);
+            quote!(#path::TypeDefinition(#t))
+        }
+        Definition::TypeExtension(ref e) => {
+            let e = type_extension(e);
+            quote!(#path::TypeExtension(#e))
+        }
+        Definition::DirectiveDefinition(ref d) => {
+            let position = pos(&d.position);
+            let description = description(&d.description);
+            let name = d.name;
+            let arguments = input_values(&d.arguments);
+            let repeatable = d.repeatable;
+            let locations = d.locations.iter().map(|loc| {
+                let loc: TokenStream = format!("{:?}", loc).parse().expect("valid identifier");
+                quote!(::graphql_parser::schema::DirectiveLocation::#loc)
+            });
+            quote!(#path::DirectiveDefinition(::graphql_parser::schema::DirectiveDefinition {
+                position: #position,
+                description: #description,
+                name: #name,
+                arguments: #arguments,
+                repeatable: #repeatable,
+                locations: ::std::vec![#(#locations),*],
+            }))
+        }
+    }
+}
+
+fn names(names: &[&str]) -> TokenStream {
+    quote!(::std::vec![#(#names),*])
+}
+
+fn type_definition(def: &TypeDefinition) -> TokenStream {
+    let path = quote!(::graphql_parser::schema::TypeDefinition);
+    match *def {
+        TypeDefinition::Scalar(ref t) => {
+            let position = pos(&t.position);
+            let description = description(&t.description);
+            let name = t.name;
+            let directives = directives(&t.directives);
+            quote!(#path::Scalar(::graphql_parser::schema::ScalarType {
+                position: #position,
+                description: #description,
+                name: #name,
+                directives: #directives,
+            }))
+        }
+        TypeDefinition::Object(ref t) => {
+            let position = pos(&t.position);
+            let description = description(&t.description);
+            let name = t.name;
+            let implements_interfaces = names(&t.implements_interfaces);
+            let directives = directives(&t.directives);
+            let fields = fields(&t.fields);
+            quote!(#path::Object(::graphql_parser::schema::ObjectType {
+                position: #position,
+                description: #description,
+                name: #name,
+                implements_interfaces: #implements_interfaces,
+                directives: #directives,
+                fields: #fields,
+            }))
+        }
+        TypeDefinition::Interface(ref t) => {
+            let position = pos(&t.position);
+            let description = description(&t.description);
+            let name = t.name;
+            let implements_interfaces = names(&t.implements_interfaces);
+            let directives = directives(&t.directives);
+            let fields = fields(&t.fields);
+            quote!(#path::Interface(::graphql_parser::schema::InterfaceType {
+                position: #position,
+                description: #description,
+                name: #name,
+                implements_interfaces: #implements_interfaces,
+                directives: #directives,
+                fields: #fields,
+            }))
+        }
+        TypeDefinition::Union(ref t) => {
+            let position = pos(&t.position);
+            let description = description(&t.description);
+            let name = t.name;
+            let directives = directives(&t.directives);
+            let types = names(&t.types);
+            quote!(#path::Union(::graphql_parser::schema::UnionType {
+                position: #position,
+                description: #description,
+                name: #name,
+                directives: #directives,
+                types: #types,
+            }))
+        }
+        TypeDefinition::Enum(ref t) => {
+            let position = pos(&t.position);
+            let description = description(&t.description);
+            let name = t.name;
+            let directives = directives(&t.directives);
+            let values = enum_values(&t.values);
+            quote!(#path::Enum(::graphql_parser::schema::EnumType {
+                position: #position,
+                description: #description,
+                name: #name,
+                directives: #directives,
+                values: #values,
+            }))
+        }
+        TypeDefinition::InputObject(ref t) => {
+            let position = pos(&t.position);
+            let description = description(&t.description);
+            let name = t.name;
+            let directives = directives(&t.directives);
+            let fields = input_values(&t.fields);
+            quote!(#path::InputObject(::graphql_parser::schema::InputObjectType {
+                position: #position,
+                description: #description,
+                name: #name,
+                directives: #directives,
+                fields: #fields,
+            }))
+        }
+    }
+}
+
+fn type_extension(ext: &TypeExtension) -> TokenStream {
+    let path = quote!(::graphql_parser::schema::TypeExtension);
+    match *ext {
+        TypeExtension::Scalar(ref e) => {
+            let position = pos(&e.position);
+            let name = e.name;
+            let directives = directives(&e.directives);
+            quote!(#path::Scalar(::graphql_parser::schema::ScalarTypeExtension {
+                position: #position,
+                name: #name,
+                directives: #directives,
+            }))
+        }
+        TypeExtension::Object(ref e) => {
+            let position = pos(&e.position);
+            let name = e.name;
+            let implements_interfaces = names(&e.implements_interfaces);
+            let directives = directives(&e.directives);
+            let fields = fields(&e.fields);
+            quote!(#path::Object(::graphql_parser::schema::ObjectTypeExtension {
+                position: #position,
+                name: #name,
+                implements_interfaces: #implements_interfaces,
+                directives: #directives,
+                fields: #fields,
+            }))
+        }
+        TypeExtension::Interface(ref e) => {
+            let position = pos(&e.position);
+            let name = e.name;
+            let implements_interfaces = names(&e.implements_interfaces);
+            let directives = directives(&e.directives);
+            let fields = fields(&e.fields);
+            quote!(#path::Interface(::graphql_parser::schema::InterfaceTypeExtension {
+                position: #position,
+                name: #name,
+                implements_interfaces: #implements_interfaces,
+                directives: #directives,
+                fields: #fields,
+            }))
+        }
+        TypeExtension::Union(ref e) => {
+            let position = pos(&e.position);
+            let name = e.name;
+            let directives = directives(&e.directives);
+            let types = names(&e.types);
+            quote!(#path::Union(::graphql_parser::schema::UnionTypeExtension {
+                position: #position,
+                name: #name,
+                directives: #directives,
+                types: #types,
+            }))
+        }
+        TypeExtension::Enum(ref e) => {
+            let position = pos(&e.position);
+            let name = e.name;
+            let directives = directives(&e.directives);
+            let values = enum_values(&e.values);
+            quote!(#path::Enum(::graphql_parser::schema::EnumTypeExtension {
+                position: #position,
+                name: #name,
+                directives: #directives,
+                values: #values,
+            }))
+        }
+        TypeExtension::InputObject(ref e) => {
+            let position = pos(&e.position);
+            let name = e.name;
+            let directives = directives(&e.directives);
+            let fields = input_values(&e.fields);
+            quote!(#path::InputObject(::graphql_parser::schema::InputObjectTypeExtension {
+                position: #position,
+                name: #name,
+                directives: #directives,
+                fields: #fields,
+            }))
+        }
+    }
+}
+
+fn fields(fields: &[Field]) -> TokenStream {
+    let fields = fields.iter().map(|f| {
+        let position = pos(&f.position);
+        let description = description(&f.description);
+        let name = f.name;
+        let arguments = input_values(&f.arguments);
+        let field_type = var_type(&f.field_type);
+        let directives = directives(&f.directives);
+        quote!(::graphql_parser::schema::Field {
+            position: #position,
+            description: #description,
+            name: #name,
+            arguments: #arguments,
+            field_type: #field_type,
+            directives: #directives,
+        })
+    });
+    quote!(::std::vec![#(#fields),*])
+}
+
+fn input_values(inputs: &[InputValue]) -> TokenStream {
+    let inputs = inputs.iter().map(|i| {
+        let position = pos(&i.position);
+        let description = description(&i.description);
+        let name = i.name;
+        let value_type = var_type(&i.value_type);
+        let default_value = opt_value(&i.default_value);
+        let directives = directives(&i.directives);
+        quote!(::graphql_parser::schema::InputValue {
+            position: #position,
+            description: #description,
+            name: #name,
+            value_type: #value_type,
+            default_value: #default_value,
+            directives: #directives,
+        })
+    });
+    quote!(::std::vec![#(#inputs),*])
+}
+
+fn enum_values(values: &[EnumValue]) -> TokenStream {
+    let values = values.iter().map(|v| {
+        let position = pos(&v.position);
+        let description = description(&v.description);
+        let name = v.name;
+        let directives = directives(&v.directives);
+        quote!(::graphql_parser::schema::EnumValue {
+            position: #position,
+            description: #description,
+            name: #name,
+            directives: #directives,
+        })
+    });
+    quote!(::std::vec![#(#values),*])
+}
@@ -0,0 +1,91 @@
+extern crate graphql_parser;
+extern crate graphql_parser_macros;
+
+use graphql_parser::{parse_query, parse_schema};
+use graphql_parser_macros::{query, schema};
+
+#[test]
+fn query_matches_runtime_parser() {
+    let ast = query!(
+        r#"
+        query Q($id: ID! = "x", $list: [Int]) @dir(a: 1.5) {
+            user: node(id: $id, filter: {name: "bob", ids: [1, -2], flag: true}) {
+                ... on User @skip(if: false) { name }
+                ...Frag
+                ... { nothing: null, e: ENUM }
+            }
+        }
+        fragment Frag on User { id }
+        subscription { events }
+        mutation M { run }
+        { short }
+        "#
+    );
+    let expected = parse_query(
+        r#"
+        query Q($id: ID! = "x", $list: [Int]) @dir(a: 1.5) {
+            user: node(id: $id, filter: {name: "bob", ids: [1, -2], flag: true}) {
+                ... on User @skip(if: false) { name }
+                ...Frag
+                ... { nothing: null, e: ENUM }
+            }
+        }
+        fragment Frag on User { id }
+        subscription { events }
+        mutation M { run }
+        { short }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(ast, expected);
+}
+
+#[test]
+fn schema_matches_runtime_parser() {
+    let ast = schema!(
+        r#"
+        schema @dir { query: Query, mutation: Mutation }
+        "A user"
+        type User implements Node & Entity @key(fields: "id") {
+            id: ID!
+            friends(first: Int = 10 @deprecated): [User!]
+        }
+        interface Node { id: ID! }
+        union Search = User | Post
+        enum Role { ADMIN @internal, USER }
+        input Filter { name: String = "x" }
+        scalar Date
+        directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+        extend type User { age: Int }
+        extend interface Node @dir
+        extend union Search = Comment
+        extend enum Role { GUEST }
+        extend input Filter { age: Int }
+        extend scalar Date @dir
+        "#
+    );
+    let expected = parse_schema(
+        r#"
+        schema @dir { query: Query, mutation: Mutation }
+        "A user"
+        type User implements Node & Entity @key(fields: "id") {
+            id: ID!
+            friends(first: Int = 10 @deprecated): [User!]
+        }
+        interface Node { id: ID! }
+        union Search = User | Post
+        enum Role { ADMIN @internal, USER }
+        input Filter { name: String = "x" }
+        scalar Date
+        directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+        extend type User { age: Int }
+        extend interface Node @dir
+        extend union Search = Comment
+        extend enum Role { GUEST }
+        extend input Filter { age: Int }
+        extend scalar Date @dir
+        "#,
+    )
+    .unwrap();
+    assert_eq!(ast, expected);
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
-use combine::combinator::{choice, many, many1, optional, position};
-use combine::easy::Error;
+use combine::combinator::{choice, eof, many, many1, optional, position};
+use combine::easy::{Error, Errors};
 use combine::error::StreamError;
 use combine::{parser, ParseResult, Parser};
 
@@ -13,9 +13,11 @@ use crate::tokenizer::{Kind as T, Token, TokenStream};
 /// It is used to make the AST generic over the string type.
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Directive<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub arguments: Vec<(&'a str, Value<'a>)>,
 }
 
@@ -26,11 +28,14 @@ pub struct Directive<'a> {
 /// in `serde_json`: encapsulate value in new-type, allowing type
 /// to be extended later.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // we use i64 as a reference implementation: graphql-js thinks even 32bit
 // integers is enough. We might consider lift this limit later though
 pub struct Number(pub(crate) i64);
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Value<'a> {
     Variable(&'a str),
     Int(Number),
@@ -39,14 +44,20 @@ pub enum Value<'a> {
     Boolean(bool),
     Null,
     Enum(&'a str),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     List(Vec<Value<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Object(BTreeMap<&'a str, Value<'a>>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Type<'a> {
     NamedType(&'a str),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ListType(Box<Type<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     NonNullType(Box<Type<'a>>),
 }
 
@@ -63,6 +74,70 @@ impl From<i32> for Number {
     }
 }
 
+impl From<i64> for Number {
+    fn from(i: i64) -> Self {
+        Number(i)
+    }
+}
+
+impl<'a> From<i32> for Value<'a> {
+    fn from(i: i32) -> Self {
+        Value::Int(i.into())
+    }
+}
+
+impl<'a> From<i64> for Value<'a> {
+    fn from(i: i64) -> Self {
+        Value::Int(i.into())
+    }
+}
+
+impl<'a> From<f64> for Value<'a> {
+    fn from(f: f64) -> Self {
+        Value::Float(f)
+    }
+}
+
+impl<'a> From<bool> for Value<'a> {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
+impl<'a> From<String> for Value<'a> {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl<'a, 'b> From<&'b str> for Value<'a> {
+    fn from(s: &'b str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl<'a, V: Into<Value<'a>>> From<Vec<V>> for Value<'a> {
+    fn from(items: Vec<V>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'a, V: Into<Value<'a>>> From<Option<V>> for Value<'a> {
+    fn from(opt: Option<V>) -> Self {
+        opt.map(Into::into).unwrap_or(Value::Null)
+    }
+}
+
+/// Returns `true` if the string is a valid graphql `Name`
+pub fn is_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
 pub fn directives<'a>(
     input: &mut TokenStream<'a>,
 ) -> ParseResult<Vec<Directive<'a>>, TokenStream<'a>>
@@ -316,6 +391,16 @@ pub fn parse_type<'a>(input: &mut TokenStream<'a>) -> ParseResult<Type<'a>, Toke
         .parse_stream(input)
 }
 
+/// Parses a standalone type reference such as `[ID!]!`
+pub fn type_from_str(s: &str) -> Result<Type<'_>, Errors<Token<'_>, Token<'_>, Pos>> {
+    let mut tokens = TokenStream::new(s);
+    let (typ, _) = parser(parse_type)
+        .skip(eof())
+        .parse_stream(&mut tokens)
+        .map_err(|e| e.into_inner().error)?;
+    Ok(typ)
+}
+
 #[cfg(test)]
 mod tests {
     use super::unquote_string;
@@ -0,0 +1,555 @@
+//! Conversion between JSON and the AST
+//!
+//! This module converts values from and to plain JSON, and contains shared
+//! parts of the graphql-js AST JSON conversion.
+//!
+//! graphql-js nodes are written as objects with a `kind` field matching
+//! `Kind` names. Positions are stored as `loc.startToken.{line,column}`,
+//! which is what graphql-js keeps in its tokens. Offsets aren't known, so
+//! `loc.start` and `loc.end` are not written, and on import nodes without a
+//! `loc` get the default position.
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value as Json};
+use thiserror::Error;
+
+use crate::common::{is_name, Directive, Number, Type, Value};
+use crate::position::Pos;
+
+/// Error converting graphql-js AST JSON into a document
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    #[error("expected {expected} node, found {found:?}")]
+    UnexpectedKind {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("missing field {field:?} in {kind} node")]
+    MissingField { kind: String, field: &'static str },
+    #[error("invalid field {field:?} in {kind} node")]
+    InvalidField { kind: String, field: &'static str },
+}
+
+/// Error converting a value from or to JSON
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ValueJsonError {
+    #[error("variable ${} is not defined", _0)]
+    UndefinedVariable(String),
+    #[error("float {} can't be represented in JSON", _0)]
+    NonFiniteFloat(f64),
+    #[error("integer {} is out of range", _0)]
+    IntOutOfRange(String),
+}
+
+impl<'a> Value<'a> {
+    /// Converts the value to JSON, resolving variables from `variables`
+    ///
+    /// Enum values become strings.
+    pub fn to_json(&self, variables: &Map<String, Json>) -> Result<Json, ValueJsonError> {
+        let json = match self {
+            Value::Variable(name) => variables
+                .get(*name)
+                .cloned()
+                .ok_or_else(|| ValueJsonError::UndefinedVariable(name.to_string()))?,
+            Value::Int(num) => Json::from(num.0),
+            Value::Float(val) => serde_json::Number::from_f64(*val)
+                .map(Json::Number)
+                .ok_or(ValueJsonError::NonFiniteFloat(*val))?,
+            Value::String(val) => Json::from(val.as_str()),
+            Value::Boolean(val) => Json::from(*val),
+            Value::Null => Json::Null,
+            Value::Enum(val) => Json::from(*val),
+            Value::List(items) => items
+                .iter()
+                .map(|item| item.to_json(variables))
+                .collect::<Result<_, _>>()?,
+            Value::Object(fields) => Json::Object(
+                fields
+                    .iter()
+                    .map(|(key, val)| Ok((key.to_string(), val.to_json(variables)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(json)
+    }
+
+    /// Converts JSON to a value, strings become string values
+    ///
+    /// Integers outside of the `i64` range are errors, as this is the range
+    /// of `Number`.
+    pub fn from_json(json: &'a Json) -> Result<Value<'a>, ValueJsonError> {
+        Value::convert_json(json, false)
+    }
+
+    /// Converts JSON to a value, strings which are names become enum values
+    ///
+    /// Useful when the expected type is known to be an enum, for example
+    /// for arguments of enum type. Other strings are kept as string values,
+    /// as well as `"true"`, `"false"` and `"null"` which can't be enum values.
+    pub fn from_json_enums(json: &'a Json) -> Result<Value<'a>, ValueJsonError> {
+        Value::convert_json(json, true)
+    }
+
+    fn convert_json(json: &'a Json, enums: bool) -> Result<Value<'a>, ValueJsonError> {
+        let value = match json {
+            Json::Null => Value::Null,
+            Json::Bool(val) => Value::Boolean(*val),
+            Json::Number(num) => {
+                if let Some(int) = num.as_i64() {
+                    Value::Int(Number(int))
+                } else if num.is_u64() {
+                    return Err(ValueJsonError::IntOutOfRange(num.to_string()));
+                } else {
+                    Value::Float(num.as_f64().expect("number is a float"))
+                }
+            }
+            Json::String(val) if enums && is_enum_value(val) => Value::Enum(val),
+            Json::String(val) => Value::String(val.clone()),
+            Json::Array(items) => Value::List(
+                items
+                    .iter()
+                    .map(|item| Value::convert_json(item, enums))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Json::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, val)| Ok((key.as_str(), Value::convert_json(val, enums)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(value)
+    }
+}
+
+fn is_enum_value(name: &str) -> bool {
+    is_name(name) && !matches!(name, "true" | "false" | "null")
+}
+
+/// Finishes a node, omitting fields which are `null`
+///
+/// This is how `JSON.stringify` treats `undefined` fields of graphql-js
+/// nodes.
+pub(crate) fn node(json: Json) -> Json {
+    match json {
+        Json::Object(map) => Json::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .collect(),
+        ),
+        json => json,
+    }
+}
+
+pub(crate) fn loc(pos: Pos) -> Json {
+    json!({ "startToken": token(pos) })
+}
+
+pub(crate) fn span(start: Pos, end: Pos) -> Json {
+    json!({ "startToken": token(start), "endToken": token(end) })
+}
+
+fn token(pos: Pos) -> Json {
+    json!({ "line": pos.line, "column": pos.column })
+}
+
+pub(crate) fn name(name: &str) -> Json {
+    json!({ "kind": "Name", "value": name })
+}
+
+pub(crate) fn named_type(type_name: &str) -> Json {
+    json!({ "kind": "NamedType", "name": name(type_name) })
+}
+
+pub(crate) fn description(description: &Option<String>) -> Json {
+    match description {
+        Some(text) => json!({
+            "kind": "StringValue",
+            "value": text,
+            "block": text.contains('\n'),
+        }),
+        None => Json::Null,
+    }
+}
+
+pub(crate) fn var_type(var_type: &Type) -> Json {
+    match var_type {
+        Type::NamedType(type_name) => named_type(type_name),
+        Type::ListType(inner) => json!({ "kind": "ListType", "type": self::var_type(inner) }),
+        Type::NonNullType(inner) => {
+            json!({ "kind": "NonNullType", "type": self::var_type(inner) })
+        }
+    }
+}
+
+pub(crate) fn value(value: &Value) -> Json {
+    match value {
+        Value::Variable(var) => json!({ "kind": "Variable", "name": name(var) }),
+        Value::Int(num) => json!({ "kind": "IntValue", "value": num.0.to_string() }),
+        Value::Float(val) => json!({ "kind": "FloatValue", "value": format!("{}", val) }),
+        Value::String(val) => json!({ "kind": "StringValue", "value": val, "block": false }),
+        Value::Boolean(val) => json!({ "kind": "BooleanValue", "value": val }),
+        Value::Null => json!({ "kind": "NullValue" }),
+        Value::Enum(val) => json!({ "kind": "EnumValue", "value": val }),
+        Value::List(items) => json!({
+            "kind": "ListValue",
+            "values": items.iter().map(self::value).collect::<Vec<_>>(),
+        }),
+        Value::Object(fields) => json!({
+            "kind": "ObjectValue",
+            "fields": fields
+                .iter()
+                .map(|(key, val)| json!({
+                    "kind": "ObjectField",
+                    "name": name(key),
+                    "value": self::value(val),
+                }))
+                .collect::<Vec<_>>(),
+        }),
+    }
+}
+
+pub(crate) fn arguments(arguments: &[(&str, Value)]) -> Json {
+    arguments
+        .iter()
+        .map(|(arg, val)| json!({ "kind": "Argument", "name": name(arg), "value": value(val) }))
+        .collect()
+}
+
+pub(crate) fn directives(directives: &[Directive]) -> Json {
+    directives
+        .iter()
+        .map(|dir| {
+            json!({
+                "kind": "Directive",
+                "name": name(dir.name),
+                "arguments": arguments(&dir.arguments),
+                "loc": loc(dir.position),
+            })
+        })
+        .collect()
+}
+
+/// A graphql-js node being read
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Node<'a> {
+    kind: &'a str,
+    fields: &'a Map<String, Json>,
+}
+
+impl<'a> Node<'a> {
+    /// Reads a node of any kind
+    pub fn new(json: &'a Json, expected: &'static str) -> Result<Node<'a>, JsonError> {
+        let fields = json.as_object().ok_or_else(|| JsonError::UnexpectedKind {
+            expected,
+            found: json.to_string(),
+        })?;
+        let kind =
+            fields
+                .get("kind")
+                .and_then(Json::as_str)
+                .ok_or_else(|| JsonError::UnexpectedKind {
+                    expected,
+                    found: json.to_string(),
+                })?;
+        Ok(Node { kind, fields })
+    }
+
+    /// Reads a node of the specified kind
+    pub fn expect(json: &'a Json, kind: &'static str) -> Result<Node<'a>, JsonError> {
+        let node = Node::new(json, kind)?;
+        if node.kind != kind {
+            return Err(node.unexpected(kind));
+        }
+        Ok(node)
+    }
+
+    pub fn kind(&self) -> &'a str {
+        self.kind
+    }
+
+    pub fn unexpected(&self, expected: &'static str) -> JsonError {
+        JsonError::UnexpectedKind {
+            expected,
+            found: self.kind.to_string(),
+        }
+    }
+
+    pub fn invalid(&self, field: &'static str) -> JsonError {
+        JsonError::InvalidField {
+            kind: self.kind.to_string(),
+            field,
+        }
+    }
+
+    fn missing(&self, field: &'static str) -> JsonError {
+        JsonError::MissingField {
+            kind: self.kind.to_string(),
+            field,
+        }
+    }
+
+    /// Returns a field, treating `null` the same as a missing field
+    pub fn get(&self, field: &'static str) -> Option<&'a Json> {
+        self.fields.get(field).filter(|json| !json.is_null())
+    }
+
+    pub fn required(&self, field: &'static str) -> Result<&'a Json, JsonError> {
+        self.get(field).ok_or_else(|| self.missing(field))
+    }
+
+    pub fn str(&self, field: &'static str) -> Result<&'a str, JsonError> {
+        self.required(field)?
+            .as_str()
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    pub fn bool(&self, field: &'static str) -> Result<bool, JsonError> {
+        match self.get(field) {
+            Some(json) => json.as_bool().ok_or_else(|| self.invalid(field)),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns items of a list field, a missing list is empty
+    pub fn list(&self, field: &'static str) -> Result<&'a [Json], JsonError> {
+        match self.get(field) {
+            Some(json) => json
+                .as_array()
+                .map(|items| &items[..])
+                .ok_or_else(|| self.invalid(field)),
+            None => Ok(&[]),
+        }
+    }
+
+    pub fn name(&self, field: &'static str) -> Result<&'a str, JsonError> {
+        Node::expect(self.required(field)?, "Name")?.str("value")
+    }
+
+    pub fn opt_name(&self, field: &'static str) -> Result<Option<&'a str>, JsonError> {
+        self.get(field)
+            .map(|json| Node::expect(json, "Name")?.str("value"))
+            .transpose()
+    }
+
+    pub fn named_type(&self, field: &'static str) -> Result<&'a str, JsonError> {
+        Node::expect(self.required(field)?, "NamedType")?.name("name")
+    }
+
+    pub fn named_types(&self, field: &'static str) -> Result<Vec<&'a str>, JsonError> {
+        self.list(field)?
+            .iter()
+            .map(|json| Node::expect(json, "NamedType")?.name("name"))
+            .collect()
+    }
+
+    pub fn var_type(&self, field: &'static str) -> Result<Type<'a>, JsonError> {
+        var_type_from(self.required(field)?)
+    }
+
+    pub fn description(&self) -> Result<Option<String>, JsonError> {
+        self.get("description")
+            .map(|json| Ok(Node::expect(json, "StringValue")?.str("value")?.to_string()))
+            .transpose()
+    }
+
+    pub fn default_value(&self) -> Result<Option<Value<'a>>, JsonError> {
+        self.get("defaultValue").map(value_from).transpose()
+    }
+
+    pub fn arguments(&self) -> Result<Vec<(&'a str, Value<'a>)>, JsonError> {
+        self.list("arguments")?
+            .iter()
+            .map(|json| {
+                let arg = Node::expect(json, "Argument")?;
+                Ok((arg.name("name")?, value_from(arg.required("value")?)?))
+            })
+            .collect()
+    }
+
+    pub fn directives(&self) -> Result<Vec<Directive<'a>>, JsonError> {
+        self.list("directives")?
+            .iter()
+            .map(|json| {
+                let dir = Node::expect(json, "Directive")?;
+                Ok(Directive {
+                    position: dir.position(),
+                    name: dir.name("name")?,
+                    arguments: dir.arguments()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Position of the node's start token, the default if unknown
+    pub fn position(&self) -> Pos {
+        self.token("startToken")
+    }
+
+    /// Position of the node's end token, the default if unknown
+    pub fn end_position(&self) -> Pos {
+        self.token("endToken")
+    }
+
+    fn token(&self, which: &str) -> Pos {
+        let token = self.get("loc").and_then(|loc| loc.get(which));
+        let field = |name| {
+            token
+                .and_then(|tok| tok.get(name))
+                .and_then(Json::as_u64)
+                .unwrap_or(0) as usize
+        };
+        Pos {
+            line: field("line"),
+            column: field("column"),
+        }
+    }
+}
+
+fn var_type_from(json: &Json) -> Result<Type<'_>, JsonError> {
+    let node = Node::new(json, "Type")?;
+    match node.kind() {
+        "NamedType" => Ok(Type::NamedType(node.name("name")?)),
+        "ListType" => Ok(Type::ListType(Box::new(node.var_type("type")?))),
+        "NonNullType" => Ok(Type::NonNullType(Box::new(node.var_type("type")?))),
+        _ => Err(node.unexpected("Type")),
+    }
+}
+
+pub(crate) fn value_from(json: &Json) -> Result<Value<'_>, JsonError> {
+    let node = Node::new(json, "Value")?;
+    let val = match node.kind() {
+        "Variable" => Value::Variable(node.name("name")?),
+        "IntValue" => Value::Int(Number(
+            node.str("value")?
+                .parse()
+                .map_err(|_| node.invalid("value"))?,
+        )),
+        "FloatValue" => Value::Float(
+            node.str("value")?
+                .parse()
+                .map_err(|_| node.invalid("value"))?,
+        ),
+        "StringValue" => Value::String(node.str("value")?.to_string()),
+        "BooleanValue" => Value::Boolean(
+            node.required("value")?
+                .as_bool()
+                .ok_or_else(|| node.invalid("value"))?,
+        ),
+        "NullValue" => Value::Null,
+        "EnumValue" => Value::Enum(node.str("value")?),
+        "ListValue" => Value::List(
+            node.list("values")?
+                .iter()
+                .map(value_from)
+                .collect::<Result<_, _>>()?,
+        ),
+        "ObjectValue" => Value::Object(
+            node.list("fields")?
+                .iter()
+                .map(|json| {
+                    let field = Node::expect(json, "ObjectField")?;
+                    Ok((field.name("name")?, value_from(field.required("value")?)?))
+                })
+                .collect::<Result<BTreeMap<_, _>, _>>()?,
+        ),
+        _ => return Err(node.unexpected("Value")),
+    };
+    Ok(val)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ValueJsonError;
+    use crate::common::{Number, Value};
+    use crate::query::{parse_query, Definition, OperationDefinition, Selection};
+
+    fn argument(source: &str) -> Value<'_> {
+        let doc = parse_query(source).unwrap();
+        match doc.definitions.into_iter().next() {
+            Some(Definition::Operation(OperationDefinition::SelectionSet(set))) => {
+                match set.items.into_iter().next() {
+                    Some(Selection::Field(mut field)) => field.arguments.remove(0).1,
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn to_json() {
+        let vars = json!({"v": [1, "x"]});
+        let vars = vars.as_object().unwrap();
+        let value = argument(
+            r#"{ a(x: {int: -1, float: 1.5, s: "s", b: true,
+                      n: null, e: RED, v: $v, l: [1, 2]}) }"#,
+        );
+        assert_eq!(
+            value.to_json(vars).unwrap(),
+            json!({
+                "int": -1, "float": 1.5, "s": "s", "b": true,
+                "n": null, "e": "RED", "v": [1, "x"], "l": [1, 2],
+            })
+        );
+        assert_eq!(
+            argument("{ a(x: [$missing]) }").to_json(vars),
+            Err(ValueJsonError::UndefinedVariable("missing".into()))
+        );
+        assert_eq!(
+            Value::Float(f64::INFINITY).to_json(vars),
+            Err(ValueJsonError::NonFiniteFloat(f64::INFINITY))
+        );
+    }
+
+    #[test]
+    fn from_json() {
+        let json = json!({"a": [1, 2.5, "RED", "not a name", null, true]});
+        let value = Value::from_json(&json).unwrap();
+        assert_eq!(value.to_json(&Default::default()).unwrap(), json);
+        match Value::from_json_enums(&json).unwrap() {
+            Value::Object(fields) => assert_eq!(
+                fields["a"],
+                Value::List(vec![
+                    Value::Int(Number(1)),
+                    Value::Float(2.5),
+                    Value::Enum("RED"),
+                    Value::String("not a name".into()),
+                    Value::Null,
+                    Value::Boolean(true),
+                ])
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn enum_literals() {
+        let json = json!(["true", "false", "null", "TRUE"]);
+        assert_eq!(
+            Value::from_json_enums(&json).unwrap().to_string(),
+            r#"["true", "false", "null", TRUE]"#
+        );
+    }
+
+    #[test]
+    fn number_range() {
+        let max = json!(i64::MAX);
+        assert_eq!(
+            Value::from_json(&max).unwrap(),
+            Value::Int(Number(i64::MAX))
+        );
+        let min = json!(i64::MIN);
+        assert_eq!(
+            Value::from_json(&min).unwrap(),
+            Value::Int(Number(i64::MIN))
+        );
+        let big = json!(u64::MAX);
+        assert_eq!(
+            Value::from_json(&big),
+            Err(ValueJsonError::IntOutOfRange(u64::MAX.to_string()))
+        );
+    }
+}
@@ -95,16 +95,22 @@
 #[macro_use]
 extern crate pretty_assertions;
 
+#[macro_use]
+pub mod semantic;
 mod common;
 #[macro_use]
 mod format;
 mod helpers;
+#[cfg(feature = "serde_json")]
+mod json;
 mod position;
 pub mod query;
 pub mod schema;
 mod tokenizer;
 
 pub use crate::format::Style;
+#[cfg(feature = "serde_json")]
+pub use crate::json::{JsonError, ValueJsonError};
 pub use crate::position::Pos;
 pub use crate::query::minify_query;
 pub use crate::query::parse_query;
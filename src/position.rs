@@ -2,6 +2,7 @@ use std::fmt;
 
 /// Original position of element in source code
 #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pos {
     /// One-based line number
     pub line: usize,
@@ -20,3 +21,23 @@ impl fmt::Display for Pos {
         write!(f, "{}:{}", self.line, self.column)
     }
 }
+
+/// Displays positions separated by commas, followed by a colon
+///
+/// Nothing is displayed when there are no positions.
+pub(crate) struct Positions<'a>(pub &'a [Pos]);
+
+impl<'a> fmt::Display for Positions<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, pos) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", pos)?;
+        }
+        if !self.0.is_empty() {
+            f.write_str(": ")?;
+        }
+        Ok(())
+    }
+}
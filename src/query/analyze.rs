@@ -0,0 +1,357 @@
+//! Schema-less analysis of query complexity
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::fragments::{CycleSpread, FragmentError};
+
+/// Metrics of an operation, as computed by [`analyze`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryAnalysis {
+    /// Maximum nesting of fields, root fields have depth 1
+    pub depth: usize,
+    /// Total number of fields, counted once per fragment spread
+    pub fields: usize,
+    pub aliases: usize,
+    /// Number of fields in the root selection set
+    pub root_fields: usize,
+    pub directives: usize,
+    /// Positions of the largest group of fields sharing a response key in
+    /// one selection set, empty if no field is repeated
+    pub repeated_fields: Vec<Pos>,
+}
+
+/// Thresholds checked by [`analyze_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnalysisLimits {
+    max_depth: Option<usize>,
+    max_fields: Option<usize>,
+    max_aliases: Option<usize>,
+    max_root_fields: Option<usize>,
+    max_directives: Option<usize>,
+    max_repeated_fields: Option<usize>,
+}
+
+impl AnalysisLimits {
+    pub fn max_depth(&mut self, value: usize) -> &mut Self {
+        self.max_depth = Some(value);
+        self
+    }
+    pub fn max_fields(&mut self, value: usize) -> &mut Self {
+        self.max_fields = Some(value);
+        self
+    }
+    pub fn max_aliases(&mut self, value: usize) -> &mut Self {
+        self.max_aliases = Some(value);
+        self
+    }
+    pub fn max_root_fields(&mut self, value: usize) -> &mut Self {
+        self.max_root_fields = Some(value);
+        self
+    }
+    pub fn max_directives(&mut self, value: usize) -> &mut Self {
+        self.max_directives = Some(value);
+        self
+    }
+    /// Maximum number of fields with the same response key in one
+    /// selection set
+    pub fn max_repeated_fields(&mut self, value: usize) -> &mut Self {
+        self.max_repeated_fields = Some(value);
+        self
+    }
+}
+
+/// Error analyzing a query
+///
+/// Positions point at the first node exceeding the limit.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    #[error(transparent)]
+    Fragment(#[from] FragmentError),
+    #[error("query depth exceeds limit of {} at {}", limit, position)]
+    Depth { limit: usize, position: Pos },
+    #[error("number of fields exceeds limit of {} at {}", limit, position)]
+    Fields { limit: usize, position: Pos },
+    #[error("number of aliases exceeds limit of {} at {}", limit, position)]
+    Aliases { limit: usize, position: Pos },
+    #[error("number of root fields exceeds limit of {} at {}", limit, position)]
+    RootFields { limit: usize, position: Pos },
+    #[error("number of directives exceeds limit of {} at {}", limit, position)]
+    Directives { limit: usize, position: Pos },
+    #[error(
+        "field {:?} is repeated more than {} times at {}",
+        name,
+        limit,
+        position
+    )]
+    RepeatedFields {
+        name: String,
+        limit: usize,
+        position: Pos,
+    },
+}
+
+/// Computes complexity metrics of the operation
+///
+/// Fragment spreads are followed, so fields of a fragment count once for
+/// every spread. As this is exponential for fragments spreading other
+/// fragments several times, use [`analyze_with`] for untrusted queries.
+/// The operation is selected by [`Document::operation`].
+pub fn analyze(doc: &Document, operation: Option<&str>) -> Result<QueryAnalysis, FragmentError> {
+    analyze_with(doc, operation, &AnalysisLimits::default()).map_err(|e| match e {
+        AnalysisError::Fragment(e) => e,
+        _ => unreachable!("no limits are set"),
+    })
+}
+
+/// Computes complexity metrics of the operation, checking the limits
+///
+/// Analysis stops at the first exceeded limit, which bounds the work done
+/// for abusive queries by the limits.
+pub fn analyze_with(
+    doc: &Document,
+    operation: Option<&str>,
+    limits: &AnalysisLimits,
+) -> Result<QueryAnalysis, AnalysisError> {
+    let op = doc.operation(operation).map_err(FragmentError::from)?;
+    let mut analyzer = Analyzer {
+        fragments: doc.fragments().map(|frag| (frag.name, frag)).collect(),
+        limits,
+        stack: Vec::new(),
+        analysis: QueryAnalysis::default(),
+        error: None,
+    };
+    analyzer.directives(op.directives());
+    let mut keys = HashMap::new();
+    analyzer.selection_set(op.selection_set(), 1, &mut keys);
+    match analyzer.error {
+        Some(err) => Err(err),
+        None => Ok(analyzer.analysis),
+    }
+}
+
+struct Analyzer<'b, 'a> {
+    fragments: HashMap<&'a str, &'b FragmentDefinition<'a>>,
+    limits: &'b AnalysisLimits,
+    stack: Vec<CycleSpread>,
+    analysis: QueryAnalysis,
+    error: Option<AnalysisError>,
+}
+
+/// Returns the limit if `count` exceeds it
+fn exceeds(limit: Option<usize>, count: usize) -> Option<usize> {
+    limit.filter(|&limit| count > limit)
+}
+
+impl<'b, 'a> Analyzer<'b, 'a> {
+    fn fail(&mut self, err: AnalysisError) {
+        self.error.get_or_insert(err);
+    }
+
+    fn directives(&mut self, directives: &[Directive<'a>]) {
+        for dir in directives {
+            self.analysis.directives += 1;
+            if let Some(limit) = exceeds(self.limits.max_directives, self.analysis.directives) {
+                return self.fail(AnalysisError::Directives {
+                    limit,
+                    position: dir.position,
+                });
+            }
+        }
+    }
+
+    /// Analyzes a selection set, `keys` collects positions of fields by
+    /// response key in the enclosing selection set, across fragments
+    fn selection_set(
+        &mut self,
+        set: &'b SelectionSet<'a>,
+        depth: usize,
+        keys: &mut HashMap<&'a str, Vec<Pos>>,
+    ) {
+        for item in &set.items {
+            if self.error.is_some() {
+                return;
+            }
+            match item {
+                Selection::Field(field) => self.field(field, depth, keys),
+                Selection::InlineFragment(frag) => {
+                    self.directives(&frag.directives);
+                    self.selection_set(&frag.selection_set, depth, keys);
+                }
+                Selection::FragmentSpread(spread) => self.spread(spread, depth, keys),
+            }
+        }
+    }
+
+    fn field(&mut self, field: &'b Field<'a>, depth: usize, keys: &mut HashMap<&'a str, Vec<Pos>>) {
+        let limits = self.limits;
+        let analysis = &mut self.analysis;
+        let position = field.position;
+        analysis.fields += 1;
+        analysis.depth = analysis.depth.max(depth);
+        if depth == 1 {
+            analysis.root_fields += 1;
+        }
+        if field.alias.is_some() {
+            analysis.aliases += 1;
+        }
+        let key = field.alias.unwrap_or(field.name);
+        let repeated = keys.entry(key).or_default();
+        repeated.push(position);
+        if repeated.len() > 1 && repeated.len() > analysis.repeated_fields.len() {
+            analysis.repeated_fields = repeated.clone();
+        }
+        let error = if let Some(limit) = exceeds(limits.max_depth, depth) {
+            Some(AnalysisError::Depth { limit, position })
+        } else if let Some(limit) = exceeds(limits.max_fields, analysis.fields) {
+            Some(AnalysisError::Fields { limit, position })
+        } else if let Some(limit) = exceeds(limits.max_aliases, analysis.aliases) {
+            Some(AnalysisError::Aliases { limit, position })
+        } else if let Some(limit) = exceeds(limits.max_root_fields, analysis.root_fields) {
+            Some(AnalysisError::RootFields { limit, position })
+        } else {
+            exceeds(limits.max_repeated_fields, repeated.len()).map(|limit| {
+                AnalysisError::RepeatedFields {
+                    name: key.to_string(),
+                    limit,
+                    position,
+                }
+            })
+        };
+        if let Some(err) = error {
+            return self.fail(err);
+        }
+        self.directives(&field.directives);
+        if self.error.is_none() {
+            self.selection_set(&field.selection_set, depth + 1, &mut HashMap::new());
+        }
+    }
+
+    fn spread(
+        &mut self,
+        spread: &'b FragmentSpread<'a>,
+        depth: usize,
+        keys: &mut HashMap<&'a str, Vec<Pos>>,
+    ) {
+        self.directives(&spread.directives);
+        let frag = match self.fragments.get(spread.fragment_name) {
+            Some(&frag) => frag,
+            None => {
+                return self.fail(
+                    FragmentError::UnknownFragment {
+                        name: spread.fragment_name.to_string(),
+                        position: spread.position,
+                    }
+                    .into(),
+                )
+            }
+        };
+        self.stack.push(CycleSpread {
+            fragment_name: frag.name.to_string(),
+            position: spread.position,
+        });
+        if let Some(start) = self.stack[..self.stack.len() - 1]
+            .iter()
+            .position(|s| s.fragment_name == frag.name)
+        {
+            let cycle = self.stack[start..].to_vec();
+            return self.fail(FragmentError::Cycle(cycle).into());
+        }
+        self.directives(&frag.directives);
+        self.selection_set(&frag.selection_set, depth, keys);
+        self.stack.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze, analyze_with, AnalysisError, AnalysisLimits, QueryAnalysis};
+    use crate::position::Pos;
+    use crate::query::{parse_query, FragmentError};
+
+    const QUERY: &str = "query Q @a {\n\
+                         a { b { c } }\n\
+                         x: a @skip(if: false) { ...F }\n\
+                         ...F\n\
+                         }\n\
+                         fragment F on T { b y: b ... @c { b } }";
+
+    #[test]
+    fn metrics() {
+        let doc = parse_query(QUERY).unwrap();
+        let analysis = analyze(&doc, None).unwrap();
+        let pos = |line, column| Pos { line, column };
+        assert_eq!(
+            analysis,
+            QueryAnalysis {
+                depth: 3,
+                fields: 10,
+                aliases: 3,
+                root_fields: 5,
+                directives: 4,
+                repeated_fields: vec![pos(6, 19), pos(6, 35)],
+            }
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let doc = parse_query(QUERY).unwrap();
+        let check = |f: &dyn Fn(&mut AnalysisLimits)| {
+            let mut limits = AnalysisLimits::default();
+            f(&mut limits);
+            analyze_with(&doc, None, &limits).unwrap_err()
+        };
+        assert_eq!(
+            check(&|l| {
+                l.max_depth(2);
+            }),
+            AnalysisError::Depth {
+                limit: 2,
+                position: Pos { line: 2, column: 9 },
+            }
+        );
+        assert_eq!(
+            check(&|l| {
+                l.max_aliases(1);
+            }),
+            AnalysisError::Aliases {
+                limit: 1,
+                position: Pos {
+                    line: 6,
+                    column: 21
+                },
+            }
+        );
+        assert_eq!(
+            check(&|l| {
+                l.max_root_fields(2);
+            })
+            .to_string(),
+            "number of root fields exceeds limit of 2 at 6:19"
+        );
+        assert_eq!(
+            check(&|l| {
+                l.max_repeated_fields(1);
+            })
+            .to_string(),
+            "field \"b\" is repeated more than 1 times at 6:35"
+        );
+        let mut limits = AnalysisLimits::default();
+        limits.max_depth(3).max_fields(10).max_directives(4);
+        assert!(analyze_with(&doc, None, &limits).is_ok());
+    }
+
+    #[test]
+    fn fragment_errors() {
+        let doc = parse_query("{ ...A } fragment A on T { a { ...A } }").unwrap();
+        assert!(matches!(analyze(&doc, None), Err(FragmentError::Cycle(_))));
+        let doc = parse_query("{ ...A }").unwrap();
+        assert!(matches!(
+            analyze(&doc, None),
+            Err(FragmentError::UnknownFragment { .. })
+        ));
+    }
+}
@@ -5,12 +5,18 @@
 //!
 //! [graphql grammar]: http://facebook.github.io/graphql/October2016/#sec-Appendix-Grammar-Summary
 //!
+use std::fmt;
+
+use thiserror::Error;
+
 pub use crate::common::{Directive, Number, Type, Value};
 use crate::position::Pos;
 
 /// Root of query data
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub definitions: Vec<Definition<'a>>,
 }
 
@@ -34,102 +40,331 @@ pub struct Document<'a> {
 // }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Definition<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Operation(OperationDefinition<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Fragment(FragmentDefinition<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FragmentDefinition<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub type_condition: TypeCondition<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selection_set: SelectionSet<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum OperationDefinition<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     SelectionSet(SelectionSet<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Query(Query<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Mutation(Mutation<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Subscription(Subscription<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Query<'a> {
     pub position: Pos,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub variable_definitions: Vec<VariableDefinition<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selection_set: SelectionSet<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mutation<'a> {
     pub position: Pos,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub variable_definitions: Vec<VariableDefinition<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selection_set: SelectionSet<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Subscription<'a> {
     pub position: Pos,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub variable_definitions: Vec<VariableDefinition<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selection_set: SelectionSet<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectionSet<'a> {
     pub span: (Pos, Pos),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub items: Vec<Selection<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariableDefinition<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub var_type: Type<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub default_value: Option<Value<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Selection<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Field(Field<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     FragmentSpread(FragmentSpread<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     InlineFragment(InlineFragment<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field<'a> {
     pub position: Pos,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub alias: Option<&'a str>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub arguments: Vec<(&'a str, Value<'a>)>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selection_set: SelectionSet<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FragmentSpread<'a> {
     pub position: Pos,
     pub fragment_name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum TypeCondition<'a> {
     On(&'a str),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InlineFragment<'a> {
     pub position: Pos,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub type_condition: Option<TypeCondition<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selection_set: SelectionSet<'a>,
 }
+
+/// Kind of an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            OperationKind::Query => "query",
+            OperationKind::Mutation => "mutation",
+            OperationKind::Subscription => "subscription",
+        }
+    }
+}
+
+impl fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error selecting an operation from a document
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum OperationError {
+    #[error("document contains no operations")]
+    NoOperations,
+    #[error("operation name is required when document contains multiple operations")]
+    MultipleOperations,
+    #[error("unknown operation named {:?}", _0)]
+    UnknownOperation(String),
+}
+
+impl<'a> Document<'a> {
+    /// Iterates over operations of the document
+    pub fn operations(&self) -> impl Iterator<Item = &OperationDefinition<'a>> {
+        self.definitions.iter().filter_map(|def| match def {
+            Definition::Operation(op) => Some(op),
+            Definition::Fragment(_) => None,
+        })
+    }
+
+    /// Iterates over fragment definitions of the document
+    pub fn fragments(&self) -> impl Iterator<Item = &FragmentDefinition<'a>> {
+        self.definitions.iter().filter_map(|def| match def {
+            Definition::Fragment(frag) => Some(frag),
+            Definition::Operation(_) => None,
+        })
+    }
+
+    /// Selects the operation to execute, as the `GetOperation` algorithm
+    /// of the specification does
+    ///
+    /// Without a name the document must contain exactly one operation.
+    pub fn operation(
+        &self,
+        name: Option<&str>,
+    ) -> Result<&OperationDefinition<'a>, OperationError> {
+        match name {
+            Some(name) => self
+                .operations()
+                .find(|op| op.name() == Some(name))
+                .ok_or_else(|| OperationError::UnknownOperation(name.to_string())),
+            None => {
+                let mut operations = self.operations();
+                let op = operations.next().ok_or(OperationError::NoOperations)?;
+                if operations.next().is_some() {
+                    return Err(OperationError::MultipleOperations);
+                }
+                Ok(op)
+            }
+        }
+    }
+}
+
+impl<'a> OperationDefinition<'a> {
+    /// Kind of the operation, shorthand operations are queries
+    pub fn kind(&self) -> OperationKind {
+        match *self {
+            OperationDefinition::SelectionSet(_) | OperationDefinition::Query(_) => {
+                OperationKind::Query
+            }
+            OperationDefinition::Mutation(_) => OperationKind::Mutation,
+            OperationDefinition::Subscription(_) => OperationKind::Subscription,
+        }
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        match *self {
+            OperationDefinition::SelectionSet(_) => None,
+            OperationDefinition::Query(ref q) => q.name,
+            OperationDefinition::Mutation(ref m) => m.name,
+            OperationDefinition::Subscription(ref s) => s.name,
+        }
+    }
+
+    /// Position of the operation, the start of the selection set for
+    /// shorthand operations
+    pub fn position(&self) -> Pos {
+        match *self {
+            OperationDefinition::SelectionSet(ref set) => set.span.0,
+            OperationDefinition::Query(ref q) => q.position,
+            OperationDefinition::Mutation(ref m) => m.position,
+            OperationDefinition::Subscription(ref s) => s.position,
+        }
+    }
+
+    pub fn variable_definitions(&self) -> &[VariableDefinition<'a>] {
+        match *self {
+            OperationDefinition::SelectionSet(_) => &[],
+            OperationDefinition::Query(ref q) => &q.variable_definitions,
+            OperationDefinition::Mutation(ref m) => &m.variable_definitions,
+            OperationDefinition::Subscription(ref s) => &s.variable_definitions,
+        }
+    }
+
+    pub fn directives(&self) -> &[Directive<'a>] {
+        match *self {
+            OperationDefinition::SelectionSet(_) => &[],
+            OperationDefinition::Query(ref q) => &q.directives,
+            OperationDefinition::Mutation(ref m) => &m.directives,
+            OperationDefinition::Subscription(ref s) => &s.directives,
+        }
+    }
+
+    pub fn selection_set(&self) -> &SelectionSet<'a> {
+        match *self {
+            OperationDefinition::SelectionSet(ref set) => set,
+            OperationDefinition::Query(ref q) => &q.selection_set,
+            OperationDefinition::Mutation(ref m) => &m.selection_set,
+            OperationDefinition::Subscription(ref s) => &s.selection_set,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OperationError, OperationKind};
+    use crate::query::parse_query;
+
+    #[test]
+    fn select_operation() {
+        let doc =
+            parse_query("query A($x: Int) { a } mutation B { b } fragment F on T { f }").unwrap();
+        let op = doc.operation(Some("B")).unwrap();
+        assert_eq!(op.kind(), OperationKind::Mutation);
+        assert_eq!(op.name(), Some("B"));
+        assert_eq!(
+            doc.operation(Some("A"))
+                .unwrap()
+                .variable_definitions()
+                .len(),
+            1
+        );
+        assert_eq!(
+            doc.operation(Some("C")),
+            Err(OperationError::UnknownOperation("C".into()))
+        );
+        assert_eq!(doc.operation(None), Err(OperationError::MultipleOperations));
+        assert_eq!(doc.fragments().count(), 1);
+    }
+
+    #[test]
+    fn single_operation() {
+        let doc = parse_query("{ a }").unwrap();
+        let op = doc.operation(None).unwrap();
+        assert_eq!(op.kind(), OperationKind::Query);
+        assert_eq!(op.name(), None);
+        assert_eq!(op.selection_set().items.len(), 1);
+        assert!(doc.operation(Some("a")).is_err());
+        let doc = parse_query("fragment F on T { f }").unwrap();
+        assert_eq!(doc.operation(None), Err(OperationError::NoOperations));
+    }
+}
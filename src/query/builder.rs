@@ -0,0 +1,495 @@
+//! Fluent API for constructing query documents
+//!
+//! ```rust
+//! # extern crate graphql_parser;
+//! use graphql_parser::query::builder::{var, QueryBuilder};
+//!
+//! let doc = QueryBuilder::query("User")
+//!     .var("id", "ID!")
+//!     .field_with("user", |f| f
+//!         .arg("id", var("id"))
+//!         .field("name")
+//!         .spread("Avatar"))
+//!     .fragment("Avatar", "User", |f| f.field("avatarUrl"))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(doc.to_string(), "\
+//! query User($id: ID!) {
+//!   user(id: $id) {
+//!     name
+//!     ...Avatar
+//!   }
+//! }
+//!
+//! fragment Avatar on User {
+//!   avatarUrl
+//! }
+//! ");
+//! ```
+//!
+//! As in the [schema builder](crate::schema::builder), `field` adds a
+//! field without arguments and subselections, and `field_with` builds a
+//! field in a closure.
+//!
+//! Names and type references are checked when the document is built, all
+//! invalid ones are returned as [`BuildError`]s.
+use thiserror::Error;
+
+use crate::common::{is_name, type_from_str};
+use crate::position::Pos;
+use crate::query::ast::*;
+
+/// Error building a document
+///
+/// It's shared by the query and the schema builders.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BuildError {
+    #[error("invalid name {0:?}")]
+    InvalidName(String),
+    #[error("invalid type {0:?}: {1}")]
+    InvalidType(String, String),
+}
+
+/// Returns a reference to the variable `$name`
+pub fn var(name: &str) -> Value<'_> {
+    Value::Variable(name)
+}
+
+/// Returns an enum value
+pub fn enum_value(name: &str) -> Value<'_> {
+    Value::Enum(name)
+}
+
+pub(crate) fn check_name(name: &str, errors: &mut Vec<BuildError>) {
+    if !is_name(name) {
+        errors.push(BuildError::InvalidName(name.to_string()));
+    }
+}
+
+/// Parses a type reference, an invalid one is recorded and replaced by a
+/// named type so that building can continue
+pub(crate) fn check_type<'a>(typ: &'a str, errors: &mut Vec<BuildError>) -> Type<'a> {
+    match type_from_str(typ) {
+        Ok(typ) => typ,
+        Err(e) => {
+            let msg = e.to_string().trim_end().replace('\n', "; ");
+            errors.push(BuildError::InvalidType(typ.to_string(), msg));
+            Type::NamedType(typ)
+        }
+    }
+}
+
+fn empty_set<'a>() -> SelectionSet<'a> {
+    SelectionSet {
+        span: (Pos::default(), Pos::default()),
+        items: Vec::new(),
+    }
+}
+
+/// Builds a directive and its arguments
+#[derive(Debug)]
+pub struct DirectiveBuilder<'a> {
+    directive: Directive<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> DirectiveBuilder<'a> {
+    pub(crate) fn new(name: &'a str) -> DirectiveBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        DirectiveBuilder {
+            directive: Directive {
+                position: Pos::default(),
+                name,
+                arguments: Vec::new(),
+            },
+            errors,
+        }
+    }
+
+    /// Adds an argument to the directive
+    pub fn arg<V: Into<Value<'a>>>(mut self, name: &'a str, value: V) -> Self {
+        check_name(name, &mut self.errors);
+        self.directive.arguments.push((name, value.into()));
+        self
+    }
+
+    pub(crate) fn finish(self, errors: &mut Vec<BuildError>) -> Directive<'a> {
+        errors.extend(self.errors);
+        self.directive
+    }
+}
+
+// Methods shared by everything that has a selection set, the path after
+// `=>` points to the selection set of the node being built
+macro_rules! impl_selections {
+    ($( $typ: ident => $( $set: ident ).+, )+) => {
+        $(
+            impl<'a> $typ<'a> {
+                /// Adds a field with arguments, directives or subselections
+                pub fn field_with<F>(mut self, name: &'a str, build: F) -> Self
+                where
+                    F: FnOnce(FieldBuilder<'a>) -> FieldBuilder<'a>,
+                {
+                    let field = build(FieldBuilder::new(name));
+                    self.errors.extend(field.errors);
+                    self.$($set).+.items.push(Selection::Field(field.field));
+                    self
+                }
+
+                /// Adds a field without arguments and subselections
+                pub fn field(self, name: &'a str) -> Self {
+                    self.field_with(name, |f| f)
+                }
+
+                /// Adds a fragment spread
+                pub fn spread(mut self, fragment_name: &'a str) -> Self {
+                    check_name(fragment_name, &mut self.errors);
+                    self.$($set).+.items.push(Selection::FragmentSpread(FragmentSpread {
+                        position: Pos::default(),
+                        fragment_name,
+                        directives: Vec::new(),
+                    }));
+                    self
+                }
+
+                /// Adds an inline fragment with a type condition
+                pub fn on<F>(self, type_name: &'a str, build: F) -> Self
+                where
+                    F: FnOnce(InlineFragmentBuilder<'a>) -> InlineFragmentBuilder<'a>,
+                {
+                    self.inline_fragment(Some(type_name), build)
+                }
+
+                /// Adds an inline fragment with an optional type condition
+                pub fn inline_fragment<F>(mut self, type_name: Option<&'a str>, build: F) -> Self
+                where
+                    F: FnOnce(InlineFragmentBuilder<'a>) -> InlineFragmentBuilder<'a>,
+                {
+                    let frag = build(InlineFragmentBuilder::new(type_name));
+                    self.errors.extend(frag.errors);
+                    self.$($set).+.items.push(Selection::InlineFragment(frag.fragment));
+                    self
+                }
+            }
+        )+
+    };
+}
+
+/// Builds a field
+#[derive(Debug)]
+pub struct FieldBuilder<'a> {
+    field: Field<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> FieldBuilder<'a> {
+    fn new(name: &'a str) -> FieldBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        FieldBuilder {
+            field: Field {
+                position: Pos::default(),
+                alias: None,
+                name,
+                arguments: Vec::new(),
+                directives: Vec::new(),
+                selection_set: empty_set(),
+            },
+            errors,
+        }
+    }
+
+    /// Sets the alias of the field
+    pub fn alias(mut self, alias: &'a str) -> Self {
+        check_name(alias, &mut self.errors);
+        self.field.alias = Some(alias);
+        self
+    }
+
+    /// Adds an argument to the field
+    pub fn arg<V: Into<Value<'a>>>(mut self, name: &'a str, value: V) -> Self {
+        check_name(name, &mut self.errors);
+        self.field.arguments.push((name, value.into()));
+        self
+    }
+
+    /// Adds a directive to the field
+    pub fn directive<F>(mut self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(DirectiveBuilder<'a>) -> DirectiveBuilder<'a>,
+    {
+        let dir = build(DirectiveBuilder::new(name)).finish(&mut self.errors);
+        self.field.directives.push(dir);
+        self
+    }
+}
+
+/// Builds an inline fragment
+#[derive(Debug)]
+pub struct InlineFragmentBuilder<'a> {
+    fragment: InlineFragment<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> InlineFragmentBuilder<'a> {
+    fn new(type_name: Option<&'a str>) -> InlineFragmentBuilder<'a> {
+        let mut errors = Vec::new();
+        if let Some(name) = type_name {
+            check_name(name, &mut errors);
+        }
+        InlineFragmentBuilder {
+            fragment: InlineFragment {
+                position: Pos::default(),
+                type_condition: type_name.map(TypeCondition::On),
+                directives: Vec::new(),
+                selection_set: empty_set(),
+            },
+            errors,
+        }
+    }
+
+    /// Adds a directive to the inline fragment
+    pub fn directive<F>(mut self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(DirectiveBuilder<'a>) -> DirectiveBuilder<'a>,
+    {
+        let dir = build(DirectiveBuilder::new(name)).finish(&mut self.errors);
+        self.fragment.directives.push(dir);
+        self
+    }
+}
+
+/// Builds a fragment definition
+#[derive(Debug)]
+pub struct FragmentBuilder<'a> {
+    fragment: FragmentDefinition<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> FragmentBuilder<'a> {
+    fn new(name: &'a str, type_name: &'a str) -> FragmentBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        check_name(type_name, &mut errors);
+        FragmentBuilder {
+            fragment: FragmentDefinition {
+                position: Pos::default(),
+                name,
+                type_condition: TypeCondition::On(type_name),
+                directives: Vec::new(),
+                selection_set: empty_set(),
+            },
+            errors,
+        }
+    }
+
+    /// Adds a directive to the fragment definition
+    pub fn directive<F>(mut self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(DirectiveBuilder<'a>) -> DirectiveBuilder<'a>,
+    {
+        let dir = build(DirectiveBuilder::new(name)).finish(&mut self.errors);
+        self.fragment.directives.push(dir);
+        self
+    }
+}
+
+/// Builds a document containing a single operation and fragments
+#[derive(Debug)]
+pub struct QueryBuilder<'a> {
+    kind: OperationKind,
+    name: Option<&'a str>,
+    variable_definitions: Vec<VariableDefinition<'a>>,
+    directives: Vec<Directive<'a>>,
+    selection_set: SelectionSet<'a>,
+    fragments: Vec<FragmentDefinition<'a>>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> QueryBuilder<'a> {
+    fn new(kind: OperationKind, name: Option<&'a str>) -> QueryBuilder<'a> {
+        let mut errors = Vec::new();
+        if let Some(name) = name {
+            check_name(name, &mut errors);
+        }
+        QueryBuilder {
+            kind,
+            name,
+            variable_definitions: Vec::new(),
+            directives: Vec::new(),
+            selection_set: empty_set(),
+            fragments: Vec::new(),
+            errors,
+        }
+    }
+
+    /// Starts a named query
+    pub fn query(name: &'a str) -> QueryBuilder<'a> {
+        QueryBuilder::new(OperationKind::Query, Some(name))
+    }
+
+    /// Starts an anonymous query
+    pub fn anonymous_query() -> QueryBuilder<'a> {
+        QueryBuilder::new(OperationKind::Query, None)
+    }
+
+    /// Starts a named mutation
+    pub fn mutation(name: &'a str) -> QueryBuilder<'a> {
+        QueryBuilder::new(OperationKind::Mutation, Some(name))
+    }
+
+    /// Starts a named subscription
+    pub fn subscription(name: &'a str) -> QueryBuilder<'a> {
+        QueryBuilder::new(OperationKind::Subscription, Some(name))
+    }
+
+    /// Declares a variable, the type is written in graphql syntax (`[ID!]!`)
+    pub fn var(self, name: &'a str, var_type: &'a str) -> Self {
+        self.var_definition(name, var_type, None)
+    }
+
+    /// Declares a variable with a default value
+    pub fn var_with_default<V: Into<Value<'a>>>(
+        self,
+        name: &'a str,
+        var_type: &'a str,
+        default: V,
+    ) -> Self {
+        self.var_definition(name, var_type, Some(default.into()))
+    }
+
+    fn var_definition(
+        mut self,
+        name: &'a str,
+        var_type: &'a str,
+        default_value: Option<Value<'a>>,
+    ) -> Self {
+        check_name(name, &mut self.errors);
+        let var_type = check_type(var_type, &mut self.errors);
+        self.variable_definitions.push(VariableDefinition {
+            position: Pos::default(),
+            name,
+            var_type,
+            default_value,
+        });
+        self
+    }
+
+    /// Adds a directive to the operation
+    pub fn directive<F>(mut self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(DirectiveBuilder<'a>) -> DirectiveBuilder<'a>,
+    {
+        let dir = build(DirectiveBuilder::new(name)).finish(&mut self.errors);
+        self.directives.push(dir);
+        self
+    }
+
+    /// Adds a fragment definition after the operation
+    pub fn fragment<F>(mut self, name: &'a str, type_name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(FragmentBuilder<'a>) -> FragmentBuilder<'a>,
+    {
+        let frag = build(FragmentBuilder::new(name, type_name));
+        self.errors.extend(frag.errors);
+        self.fragments.push(frag.fragment);
+        self
+    }
+
+    /// Returns the document or all invalid names and types encountered
+    pub fn build(self) -> Result<Document<'a>, Vec<BuildError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+        let op = match self.kind {
+            OperationKind::Query => OperationDefinition::Query(Query {
+                position: Pos::default(),
+                name: self.name,
+                variable_definitions: self.variable_definitions,
+                directives: self.directives,
+                selection_set: self.selection_set,
+            }),
+            OperationKind::Mutation => OperationDefinition::Mutation(Mutation {
+                position: Pos::default(),
+                name: self.name,
+                variable_definitions: self.variable_definitions,
+                directives: self.directives,
+                selection_set: self.selection_set,
+            }),
+            OperationKind::Subscription => OperationDefinition::Subscription(Subscription {
+                position: Pos::default(),
+                name: self.name,
+                variable_definitions: self.variable_definitions,
+                directives: self.directives,
+                selection_set: self.selection_set,
+            }),
+        };
+        let mut definitions = vec![Definition::Operation(op)];
+        definitions.extend(self.fragments.into_iter().map(Definition::Fragment));
+        Ok(Document { definitions })
+    }
+}
+
+impl_selections!(
+    QueryBuilder => selection_set,
+    FieldBuilder => field.selection_set,
+    InlineFragmentBuilder => fragment.selection_set,
+    FragmentBuilder => fragment.selection_set,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_query;
+
+    #[test]
+    fn nested_selections() {
+        let doc = QueryBuilder::mutation("Update")
+            .var("input", "[UserInput!]!")
+            .var_with_default("dry", "Boolean", false)
+            .directive("trace", |d| d.arg("level", enum_value("DEBUG")))
+            .field_with("update", |f| {
+                f.alias("result")
+                    .arg("input", var("input"))
+                    .arg("tags", vec!["a", "b"])
+                    .directive("include", |d| d.arg("if", var("dry")))
+                    .on("User", |f| f.field("id").field("name"))
+                    .inline_fragment(None, |f| f.field("__typename"))
+            })
+            .build()
+            .unwrap();
+        let expected = parse_query(
+            r#"mutation Update($input: [UserInput!]!, $dry: Boolean = false) @trace(level: DEBUG) {
+                result: update(input: $input, tags: ["a", "b"]) @include(if: $dry) {
+                    ... on User { id name }
+                    ... { __typename }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(doc.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn invalid_names() {
+        let err = QueryBuilder::query("Q")
+            .field_with("user", |f| f.field("full name").alias("bad alias"))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            vec![
+                BuildError::InvalidName("full name".into()),
+                BuildError::InvalidName("bad alias".into()),
+            ]
+        );
+
+        let err = QueryBuilder::anonymous_query()
+            .var("id", "[ID!")
+            .field("user")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err[..], [BuildError::InvalidType(ref typ, _)] if typ == "[ID!"));
+    }
+}
@@ -0,0 +1,461 @@
+//! Schema-aware query cost calculation
+//!
+//! Costs are declared in the schema with directives of the IBM GraphQL
+//! cost specification:
+//!
+//! ```graphql
+//! directive @cost(weight: String!) on FIELD_DEFINITION | OBJECT | SCALAR | ENUM
+//! directive @listSize(
+//!   assumedSize: Int
+//!   slicingArguments: [String!]
+//!   sizedFields: [String!]
+//! ) on FIELD_DEFINITION
+//! ```
+//!
+//! The cost of a field is its weight plus the cost of its selections,
+//! multiplied by the size of the list it returns. The weight is taken from
+//! `@cost` on the field definition, then on the returned type, and defaults
+//! to 1 for composite types and 0 for scalars and enums. Weights may be
+//! strings, as in the specification, or numbers.
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::fragments::{inline_fragments, FragmentError};
+use crate::schema::index::{is_list, named_type, type_directives};
+use crate::schema::Schema;
+
+/// Options of cost calculation
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostOptions {
+    default_list_size: u64,
+}
+
+impl Default for CostOptions {
+    fn default() -> CostOptions {
+        CostOptions {
+            default_list_size: 1,
+        }
+    }
+}
+
+impl CostOptions {
+    /// Size of lists without `@listSize` or slicing arguments in the query
+    ///
+    /// Defaults to 1.
+    pub fn default_list_size(&mut self, value: u64) -> &mut Self {
+        self.default_list_size = value;
+        self
+    }
+}
+
+/// Cost of a single field of the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCost {
+    /// Response keys from the root of the operation to the field
+    pub path: Vec<String>,
+    /// Type the field is defined on
+    pub type_name: String,
+    pub field_name: String,
+    pub position: Pos,
+    pub weight: f64,
+    /// Assumed size of the list returned by the field, 1 if it's not a list
+    pub multiplier: u64,
+    /// Cost of the field including its selections
+    pub cost: f64,
+}
+
+/// Cost of an operation, as computed by [`query_cost`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryCost {
+    pub total: f64,
+    /// Costs of all fields in the order they appear in the query,
+    /// fragments being inlined
+    pub fields: Vec<FieldCost>,
+}
+
+/// Error calculating query cost
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum CostError {
+    #[error(transparent)]
+    Fragment(#[from] FragmentError),
+    #[error("schema has no {} type", _0)]
+    NoRootType(OperationKind),
+    #[error("unknown type {:?} at {}", name, position)]
+    UnknownType { name: String, position: Pos },
+    #[error("type {:?} has no field {:?} at {}", type_name, field_name, position)]
+    UnknownField {
+        type_name: String,
+        field_name: String,
+        position: Pos,
+    },
+    #[error("invalid @{} directive at {} in schema", name, position)]
+    InvalidDirective { name: String, position: Pos },
+}
+
+/// Calculates the cost of the operation with default options
+///
+/// See [`query_cost_with`].
+pub fn query_cost(
+    schema: &Schema,
+    doc: &Document,
+    operation: Option<&str>,
+    variables: &BTreeMap<&str, Value>,
+) -> Result<QueryCost, CostError> {
+    query_cost_with(schema, doc, operation, variables, &CostOptions::default())
+}
+
+/// Calculates the cost of the operation against the schema
+///
+/// Fragments are inlined first, so run [`analyze_with`] on untrusted
+/// queries to limit their size. Slicing arguments set to variables use
+/// the value of the variable in `variables`, then its default value, and
+/// the assumed size when neither is set. JSON variables can be converted
+/// with `Value::from_json`. The operation is selected by
+/// [`Document::operation`].
+///
+/// [`analyze_with`]: crate::query::analyze_with
+pub fn query_cost_with(
+    schema: &Schema,
+    doc: &Document,
+    operation: Option<&str>,
+    variables: &BTreeMap<&str, Value>,
+    options: &CostOptions,
+) -> Result<QueryCost, CostError> {
+    let inlined = inline_fragments(doc, operation)?;
+    let op = inlined.operation(None).map_err(FragmentError::from)?;
+    let kind = op.kind();
+    let root = match kind {
+        OperationKind::Query => schema.query_type(),
+        OperationKind::Mutation => schema.mutation_type(),
+        OperationKind::Subscription => schema.subscription_type(),
+    };
+    let root = root.ok_or(CostError::NoRootType(kind))?;
+    let mut calculator = Calculator {
+        schema,
+        options,
+        definitions: op.variable_definitions(),
+        variables,
+        path: Vec::new(),
+        fields: Vec::new(),
+    };
+    let total = calculator.selection_set(op.selection_set(), root, None)?;
+    Ok(QueryCost {
+        total,
+        fields: calculator.fields,
+    })
+}
+
+struct Calculator<'b, 'a, 'q> {
+    schema: &'b Schema<'b, 'a>,
+    options: &'b CostOptions,
+    definitions: &'b [VariableDefinition<'q>],
+    variables: &'b BTreeMap<&'b str, Value<'b>>,
+    path: Vec<String>,
+    fields: Vec<FieldCost>,
+}
+
+/// Size applied to the fields with listed names, from `sizedFields`
+type SizedFields<'s> = Option<(u64, &'s [String])>;
+
+impl<'b, 'a, 'q> Calculator<'b, 'a, 'q> {
+    fn selection_set(
+        &mut self,
+        set: &SelectionSet<'q>,
+        type_name: &str,
+        sized: SizedFields,
+    ) -> Result<f64, CostError> {
+        let mut total = 0.0;
+        for item in &set.items {
+            total += match item {
+                Selection::Field(field) => self.field(field, type_name, sized)?,
+                Selection::InlineFragment(frag) => {
+                    let type_name = match frag.type_condition {
+                        Some(TypeCondition::On(name)) => {
+                            if self.schema.get_type(name).is_none() {
+                                return Err(CostError::UnknownType {
+                                    name: name.to_string(),
+                                    position: frag.position,
+                                });
+                            }
+                            name
+                        }
+                        None => type_name,
+                    };
+                    self.selection_set(&frag.selection_set, type_name, sized)?
+                }
+                // fragments are inlined, only unknown ones are left
+                Selection::FragmentSpread(_) => 0.0,
+            };
+        }
+        Ok(total)
+    }
+
+    fn field(
+        &mut self,
+        field: &Field<'q>,
+        type_name: &str,
+        sized: SizedFields,
+    ) -> Result<f64, CostError> {
+        if field.name.starts_with("__") {
+            return Ok(0.0);
+        }
+        let def =
+            self.schema
+                .field(type_name, field.name)
+                .ok_or_else(|| CostError::UnknownField {
+                    type_name: type_name.to_string(),
+                    field_name: field.name.to_string(),
+                    position: field.position,
+                })?;
+        let returns = named_type(&def.field_type);
+        let weight = match cost(&def.directives)? {
+            Some(weight) => weight,
+            None => match self.schema.get_type(returns) {
+                Some(ty) => cost(type_directives(ty))?,
+                None => None,
+            }
+            .unwrap_or(if self.schema.is_composite(returns) {
+                1.0
+            } else {
+                0.0
+            }),
+        };
+        let list_size = def.directives.iter().find(|dir| dir.name == "listSize");
+        let size = match list_size {
+            Some(dir) => self.list_size(dir, field)?,
+            None => self.options.default_list_size,
+        };
+        let sized_fields = match list_size {
+            Some(dir) => strings(dir, "sizedFields")?,
+            None => Vec::new(),
+        };
+        let parent_size = sized
+            .filter(|(_, names)| names.iter().any(|name| name == field.name))
+            .map(|(size, _)| size);
+        let (multiplier, inner) = match parent_size {
+            Some(parent_size) => (parent_size, None),
+            None if !sized_fields.is_empty() => (1, Some((size, &sized_fields[..]))),
+            None if is_list(&def.field_type) => (size, None),
+            None => (1, None),
+        };
+
+        self.path
+            .push(field.alias.unwrap_or(field.name).to_string());
+        let idx = self.fields.len();
+        self.fields.push(FieldCost {
+            path: self.path.clone(),
+            type_name: type_name.to_string(),
+            field_name: field.name.to_string(),
+            position: field.position,
+            weight,
+            multiplier,
+            cost: 0.0,
+        });
+        let children = self.selection_set(&field.selection_set, returns, inner);
+        self.path.pop();
+        let cost = multiplier as f64 * (weight + children?);
+        self.fields[idx].cost = cost;
+        Ok(cost)
+    }
+
+    /// Returns the size of the list from slicing arguments of the field,
+    /// or the assumed size
+    fn list_size(&self, dir: &Directive<'a>, field: &Field<'q>) -> Result<u64, CostError> {
+        let mut size = None;
+        for name in strings(dir, "slicingArguments")? {
+            let value = field
+                .arguments
+                .iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| value);
+            let value = match value {
+                Some(Value::Variable(var)) => self.variables.get(var).or_else(|| {
+                    self.definitions
+                        .iter()
+                        .find(|def| def.name == *var)
+                        .and_then(|def| def.default_value.as_ref())
+                }),
+                value => value,
+            };
+            if let Some(Value::Int(n)) = value {
+                let n = n.as_i64().unwrap_or(0).max(0) as u64;
+                size = Some(size.map_or(n, |size: u64| size.max(n)));
+            }
+        }
+        if let Some(size) = size {
+            return Ok(size);
+        }
+        match argument(dir, "assumedSize") {
+            Some(Value::Int(n)) => Ok(n.as_i64().unwrap_or(0).max(0) as u64),
+            Some(Value::Null) | None => Ok(self.options.default_list_size),
+            Some(_) => Err(invalid(dir)),
+        }
+    }
+}
+
+fn invalid(dir: &Directive) -> CostError {
+    CostError::InvalidDirective {
+        name: dir.name.to_string(),
+        position: dir.position,
+    }
+}
+
+fn argument<'d, 'a>(dir: &'d Directive<'a>, name: &str) -> Option<&'d Value<'a>> {
+    dir.arguments
+        .iter()
+        .find(|(arg, _)| *arg == name)
+        .map(|(_, value)| value)
+}
+
+/// Returns the weight from the `@cost` directive, if any
+fn cost(directives: &[Directive]) -> Result<Option<f64>, CostError> {
+    let dir = match directives.iter().find(|dir| dir.name == "cost") {
+        Some(dir) => dir,
+        None => return Ok(None),
+    };
+    match argument(dir, "weight") {
+        Some(Value::String(s)) => s.trim().parse().map(Some).map_err(|_| invalid(dir)),
+        Some(Value::Int(n)) => Ok(n.as_i64().map(|n| n as f64)),
+        Some(Value::Float(f)) => Ok(Some(*f)),
+        _ => Err(invalid(dir)),
+    }
+}
+
+/// Returns a list of strings argument, a single string is a list of one
+fn strings(dir: &Directive, name: &str) -> Result<Vec<String>, CostError> {
+    match argument(dir, name) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => Ok(vec![s.clone()]),
+        Some(Value::List(items)) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(invalid(dir)),
+            })
+            .collect(),
+        Some(_) => Err(invalid(dir)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{query_cost, query_cost_with, CostError, CostOptions};
+    use crate::query::{parse_query, Number, Value};
+    use crate::schema::{parse_schema, Schema};
+
+    const SCHEMA: &str = r#"
+        type Query {
+            users(first: Int, last: Int): [User!]!
+                @listSize(slicingArguments: ["first", "last"], assumedSize: 50)
+            search(term: String): SearchConnection
+                @listSize(assumedSize: 20, sizedFields: ["edges"])
+            expensive: Int @cost(weight: "10.5")
+            node: Node
+        }
+        interface Node { id: ID! }
+        type User implements Node @cost(weight: 2) {
+            id: ID!
+            name: String
+            friends: [User]
+        }
+        type SearchConnection { edges: [User] count: Int }
+    "#;
+
+    fn cost(query: &str) -> Result<f64, CostError> {
+        cost_with_variables(query, &BTreeMap::new())
+    }
+
+    fn cost_with_variables(
+        query: &str,
+        variables: &BTreeMap<&str, Value>,
+    ) -> Result<f64, CostError> {
+        let schema = parse_schema(SCHEMA).unwrap();
+        let doc = parse_query(query).unwrap();
+        query_cost(&Schema::new(&schema), &doc, None, variables).map(|cost| cost.total)
+    }
+
+    #[test]
+    fn total() {
+        assert_eq!(cost("{ users(first: 5) { name } }").unwrap(), 10.0);
+        assert_eq!(cost("{ users(first: 5, last: 7) { id } }").unwrap(), 14.0);
+        assert_eq!(cost("{ users { id } }").unwrap(), 100.0);
+        assert_eq!(
+            cost("query($n: Int = 3) { users(first: $n) { friends { id } } }").unwrap(),
+            3.0 * (2.0 + 2.0)
+        );
+        assert_eq!(cost("{ expensive __typename }").unwrap(), 10.5);
+        assert_eq!(
+            cost("{ search { count edges { id } } }").unwrap(),
+            1.0 + 20.0 * 2.0
+        );
+        assert_eq!(
+            cost("{ node { id ... on User { name } } ...F } fragment F on Query { expensive }")
+                .unwrap(),
+            11.5
+        );
+    }
+
+    #[test]
+    fn variables() {
+        let query = "query($n: Int = 3) { users(first: $n) { id } }";
+        let mut variables = BTreeMap::new();
+        variables.insert("n", Value::Int(Number::from(1000)));
+        assert_eq!(cost_with_variables(query, &variables).unwrap(), 2000.0);
+        assert_eq!(cost(query).unwrap(), 6.0);
+        variables.insert("n", Value::Null);
+        assert_eq!(cost_with_variables(query, &variables).unwrap(), 100.0);
+        let query = "query($n: Int) { users(first: $n) { id } }";
+        assert_eq!(cost(query).unwrap(), 100.0);
+    }
+
+    #[test]
+    fn breakdown() {
+        let schema = parse_schema(SCHEMA).unwrap();
+        let doc = parse_query("{ all: users(first: 2) { friends { id } } }").unwrap();
+        let mut options = CostOptions::default();
+        options.default_list_size(10);
+        let cost = query_cost_with(
+            &Schema::new(&schema),
+            &doc,
+            None,
+            &BTreeMap::new(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(cost.total, 2.0 * (2.0 + 10.0 * 2.0));
+        let fields = cost
+            .fields
+            .iter()
+            .map(|f| (f.path.join("."), f.multiplier, f.cost))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("all".to_string(), 2, 44.0),
+                ("all.friends".to_string(), 10, 20.0),
+                ("all.friends.id".to_string(), 1, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            cost("{ users { nope } }").unwrap_err().to_string(),
+            "type \"User\" has no field \"nope\" at 1:11"
+        );
+        assert_eq!(
+            cost("mutation { a }").unwrap_err().to_string(),
+            "schema has no mutation type"
+        );
+        assert!(matches!(
+            cost("{ ...Missing }"),
+            Err(CostError::Fragment(_))
+        ));
+    }
+}
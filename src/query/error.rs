@@ -19,3 +19,10 @@ impl<'a> From<InternalError<'a>> for ParseError<'a> {
         ParseError(e)
     }
 }
+
+impl<'a> ParseError<'a> {
+    /// Returns the position in the source where parsing failed
+    pub fn position(&self) -> Pos {
+        self.0.position
+    }
+}
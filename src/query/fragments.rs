@@ -0,0 +1,394 @@
+//! Replacing fragment spreads with inline fragments
+use std::collections::HashMap;
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::visit::{self, Control, Fold, Path, Visitor};
+
+/// Options of fragment inlining
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InlineOptions {
+    merge_unconditional: bool,
+}
+
+impl InlineOptions {
+    /// Merge inline fragments without type condition and directives into
+    /// the parent selection set
+    pub fn merge_unconditional(&mut self, value: bool) -> &mut Self {
+        self.merge_unconditional = value;
+        self
+    }
+}
+
+/// A fragment spread which is a part of a cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleSpread {
+    pub fragment_name: String,
+    pub position: Pos,
+}
+
+/// Error inlining fragments
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FragmentError {
+    #[error(transparent)]
+    Operation(#[from] OperationError),
+    #[error("unknown fragment {:?} spread at {}", name, position)]
+    UnknownFragment { name: String, position: Pos },
+    #[error("fragment spreads form a cycle: {}", Cycle(_0))]
+    Cycle(Vec<CycleSpread>),
+}
+
+struct Cycle<'a>(&'a [CycleSpread]);
+
+impl<'a> fmt::Display for Cycle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, spread) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" -> ")?;
+            }
+            write!(f, "{} at {}", spread.fragment_name, spread.position)?;
+        }
+        Ok(())
+    }
+}
+
+/// Inlines fragments of the operation with default options
+///
+/// See [`inline_fragments_with`].
+pub fn inline_fragments<'a>(
+    doc: &Document<'a>,
+    operation: Option<&str>,
+) -> Result<Document<'a>, FragmentError> {
+    inline_fragments_with(doc, operation, &InlineOptions::default())
+}
+
+/// Returns a document containing only the selected operation, with every
+/// fragment spread replaced by an equivalent inline fragment
+///
+/// Inline fragments get the type condition of the fragment, and the
+/// directives of the spread followed by the directives of the fragment.
+/// The operation is selected by [`Document::operation`].
+pub fn inline_fragments_with<'a>(
+    doc: &Document<'a>,
+    operation: Option<&str>,
+    options: &InlineOptions,
+) -> Result<Document<'a>, FragmentError> {
+    let op = doc.operation(operation)?.clone();
+    let mut inliner = Inliner::new(doc, options.merge_unconditional);
+    let op = inliner.fold_operation(op);
+    match inliner.error {
+        Some(err) => Err(err),
+        None => Ok(Document {
+            definitions: vec![Definition::Operation(op)],
+        }),
+    }
+}
+
+impl<'a> Document<'a> {
+    /// Returns a document with the selected operation and all fragments it
+    /// uses, directly or through other fragments
+    ///
+    /// Fragments follow the operation in the order they are first spread,
+    /// breadth first. Every spread of a fragment which isn't defined is
+    /// reported as an error, in the same order. The operation is selected
+    /// by [`Document::operation`].
+    pub fn extract_operation(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Document<'a>, Vec<FragmentError>> {
+        let op = self.operation(name).map_err(|e| vec![e.into()])?;
+        let used = used_fragments(self, op);
+        if !used.unknown.is_empty() {
+            return Err(used
+                .unknown
+                .iter()
+                .map(|spread| FragmentError::UnknownFragment {
+                    name: spread.fragment_name.to_string(),
+                    position: spread.position,
+                })
+                .collect());
+        }
+        let mut definitions = vec![Definition::Operation(op.clone())];
+        definitions.extend(
+            used.indices
+                .iter()
+                .map(|&idx| self.definitions[idx].clone()),
+        );
+        Ok(Document { definitions })
+    }
+}
+
+/// Fragments used by an operation, directly or through other fragments
+pub(crate) struct UsedFragments<'b> {
+    /// Indices of fragment definitions in the document, breadth first
+    pub indices: Vec<usize>,
+    /// Spreads of fragments which aren't defined
+    pub unknown: Vec<&'b FragmentSpread<'b>>,
+}
+
+pub(crate) fn used_fragments<'b>(
+    doc: &'b Document<'b>,
+    op: &'b OperationDefinition<'b>,
+) -> UsedFragments<'b> {
+    let fragments = doc
+        .definitions
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, def)| match def {
+            Definition::Fragment(frag) => Some((frag.name, (idx, frag))),
+            Definition::Operation(_) => None,
+        })
+        .collect::<HashMap<_, _>>();
+    let mut spreads = SpreadCollector::default();
+    visit::walk_operation(&mut spreads, op, &mut Path::new());
+    let mut used = UsedFragments {
+        indices: Vec::new(),
+        unknown: Vec::new(),
+    };
+    let mut next = 0;
+    while let Some(spread) = spreads.spreads.get(next) {
+        next += 1;
+        match fragments.get(spread.fragment_name) {
+            Some(&(idx, frag)) => {
+                if !used.indices.contains(&idx) {
+                    used.indices.push(idx);
+                    visit::walk_fragment_definition(&mut spreads, frag, &mut Path::new());
+                }
+            }
+            None => used.unknown.push(spread),
+        }
+    }
+    used
+}
+
+#[derive(Default)]
+struct SpreadCollector<'b> {
+    spreads: Vec<&'b FragmentSpread<'b>>,
+}
+
+impl<'b> Visitor<'b> for SpreadCollector<'b> {
+    fn enter_fragment_spread(&mut self, spread: &'b FragmentSpread<'b>, _: &Path<'b>) -> Control {
+        self.spreads.push(spread);
+        Control::Continue
+    }
+}
+
+/// Folder replacing fragment spreads
+///
+/// Spreads of unknown fragments and spreads forming a cycle are kept, and
+/// the first such problem is recorded in `error`.
+pub(crate) struct Inliner<'a> {
+    fragments: HashMap<&'a str, FragmentDefinition<'a>>,
+    stack: Vec<CycleSpread>,
+    merge_unconditional: bool,
+    pub error: Option<FragmentError>,
+}
+
+impl<'a> Inliner<'a> {
+    pub fn new(doc: &Document<'a>, merge_unconditional: bool) -> Inliner<'a> {
+        Inliner {
+            fragments: doc
+                .fragments()
+                .map(|frag| (frag.name, frag.clone()))
+                .collect(),
+            stack: Vec::new(),
+            merge_unconditional,
+            error: None,
+        }
+    }
+
+    fn cycle(&self, name: &str, position: Pos) -> Option<FragmentError> {
+        let start = self.stack.iter().position(|s| s.fragment_name == name)?;
+        let mut cycle = self.stack[start..].to_vec();
+        cycle.push(CycleSpread {
+            fragment_name: name.to_string(),
+            position,
+        });
+        Some(FragmentError::Cycle(cycle))
+    }
+}
+
+impl<'a> Fold<'a> for Inliner<'a> {
+    fn fold_fragment_definition(&mut self, frag: FragmentDefinition<'a>) -> FragmentDefinition<'a> {
+        self.stack.push(CycleSpread {
+            fragment_name: frag.name.to_string(),
+            position: frag.position,
+        });
+        let frag = visit::fold_fragment_definition(self, frag);
+        self.stack.pop();
+        frag
+    }
+    fn fold_selection_set(&mut self, set: SelectionSet<'a>) -> SelectionSet<'a> {
+        let mut set = visit::fold_selection_set(self, set);
+        if self.merge_unconditional {
+            set.items = set
+                .items
+                .into_iter()
+                .flat_map(|sel| match sel {
+                    Selection::InlineFragment(frag)
+                        if frag.type_condition.is_none() && frag.directives.is_empty() =>
+                    {
+                        frag.selection_set.items
+                    }
+                    sel => vec![sel],
+                })
+                .collect();
+        }
+        set
+    }
+    fn fold_selection(&mut self, selection: Selection<'a>) -> Option<Selection<'a>> {
+        let spread = match selection {
+            Selection::FragmentSpread(spread) => spread,
+            selection => return visit::fold_selection(self, selection),
+        };
+        let problem = match self.fragments.get(spread.fragment_name) {
+            None => Some(FragmentError::UnknownFragment {
+                name: spread.fragment_name.to_string(),
+                position: spread.position,
+            }),
+            Some(_) => self.cycle(spread.fragment_name, spread.position),
+        };
+        if let Some(err) = problem {
+            self.error.get_or_insert(err);
+            return Some(Selection::FragmentSpread(spread));
+        }
+        let frag = self.fragments[spread.fragment_name].clone();
+        self.stack.push(CycleSpread {
+            fragment_name: frag.name.to_string(),
+            position: spread.position,
+        });
+        let selection_set = self.fold_selection_set(frag.selection_set);
+        self.stack.pop();
+        let mut directives = spread.directives;
+        directives.extend(frag.directives);
+        Some(Selection::InlineFragment(InlineFragment {
+            position: spread.position,
+            type_condition: Some(frag.type_condition),
+            directives,
+            selection_set,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        inline_fragments, inline_fragments_with, CycleSpread, FragmentError, InlineOptions,
+    };
+    use crate::position::Pos;
+    use crate::query::{parse_query, OperationError};
+
+    #[test]
+    fn inlines() {
+        let doc = parse_query(
+            "query A { ...F @skip(if: false) } query B { b } \
+             fragment F on T @dir { a ...G } fragment G on U { g }",
+        )
+        .unwrap();
+        assert_eq!(
+            inline_fragments(&doc, Some("A")).unwrap().to_string(),
+            "query A {\n  ... on T @skip(if: false) @dir {\n    a\n    ... on \
+             U {\n      g\n    }\n  }\n}\n"
+        );
+        assert_eq!(
+            inline_fragments(&doc, None),
+            Err(FragmentError::Operation(OperationError::MultipleOperations))
+        );
+    }
+
+    #[test]
+    fn merges_unconditional() {
+        let doc = parse_query("{ a ... { b ... { c } } ... @skip(if: true) { d } ... on T { e } }")
+            .unwrap();
+        let mut options = InlineOptions::default();
+        options.merge_unconditional(true);
+        assert_eq!(
+            inline_fragments_with(&doc, None, &options)
+                .unwrap()
+                .to_string(),
+            "{\n  a\n  b\n  c\n  ... @skip(if: true) {\n    d\n  }\n  ... on T {\n    e\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn extract_operation() {
+        let doc = parse_query(
+            "query A { ...F ...G } query B { ...H }\n\
+             fragment H on T { h }\n\
+             fragment G on T { ...H g }\n\
+             fragment F on T { ...F ...G f }",
+        )
+        .unwrap();
+        assert_eq!(
+            doc.extract_operation(Some("A")).unwrap().to_string(),
+            "query A {\n  ...F\n  ...G\n}\n\n\
+             fragment F on T {\n  ...F\n  ...G\n  f\n}\n\n\
+             fragment G on T {\n  ...H\n  g\n}\n\n\
+             fragment H on T {\n  h\n}\n"
+        );
+        assert_eq!(
+            doc.extract_operation(Some("B")).unwrap().definitions.len(),
+            2
+        );
+        let doc = parse_query("{ ...F ...Other }\nfragment F on T { ...Missing }").unwrap();
+        assert_eq!(
+            doc.extract_operation(None),
+            Err(vec![
+                FragmentError::UnknownFragment {
+                    name: "Other".into(),
+                    position: Pos {
+                        line: 1,
+                        column: 11
+                    },
+                },
+                FragmentError::UnknownFragment {
+                    name: "Missing".into(),
+                    position: Pos {
+                        line: 2,
+                        column: 22
+                    },
+                },
+            ])
+        );
+        assert_eq!(
+            doc.extract_operation(Some("A")),
+            Err(vec![FragmentError::Operation(
+                OperationError::UnknownOperation("A".into())
+            )])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let doc = parse_query("{ ...Missing }").unwrap();
+        assert_eq!(
+            inline_fragments(&doc, None),
+            Err(FragmentError::UnknownFragment {
+                name: "Missing".into(),
+                position: Pos { line: 1, column: 6 },
+            })
+        );
+        let doc =
+            parse_query("{ ...A }\nfragment A on T { ...B }\nfragment B on T { ...A }").unwrap();
+        let err = inline_fragments(&doc, None).unwrap_err();
+        let spread = |name: &str, line, column| CycleSpread {
+            fragment_name: name.into(),
+            position: Pos { line, column },
+        };
+        assert_eq!(
+            err,
+            FragmentError::Cycle(vec![
+                spread("A", 1, 6),
+                spread("B", 2, 22),
+                spread("A", 3, 22)
+            ])
+        );
+        assert_eq!(
+            err.to_string(),
+            "fragment spreads form a cycle: A at 1:6 -> B at 2:22 -> A at 3:22"
+        );
+    }
+}
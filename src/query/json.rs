@@ -0,0 +1,342 @@
+//! Conversion of query documents from and to graphql-js AST JSON
+use serde_json::{json, Value as Json};
+
+use crate::json::{self, node, JsonError, Node};
+use crate::position::Pos;
+use crate::query::ast::*;
+
+/// Converts a query document into graphql-js AST JSON
+///
+/// Shorthand queries (`{ field }`) are written as anonymous `query`
+/// operations, just like graphql-js parses them.
+pub fn to_graphql_js_json(doc: &Document) -> Json {
+    json!({
+        "kind": "Document",
+        "definitions": doc.definitions.iter().map(definition).collect::<Vec<_>>(),
+    })
+}
+
+/// Reads a query document from graphql-js AST JSON
+///
+/// Names in the returned document borrow from `json`.
+pub fn from_graphql_js_json(json: &Json) -> Result<Document<'_>, JsonError> {
+    let doc = Node::expect(json, "Document")?;
+    Ok(Document {
+        definitions: doc
+            .list("definitions")?
+            .iter()
+            .map(definition_from)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn definition(def: &Definition) -> Json {
+    match def {
+        Definition::Operation(OperationDefinition::SelectionSet(set)) => {
+            operation("query", set.span.0, None, &[], &[], set)
+        }
+        Definition::Operation(OperationDefinition::Query(q)) => operation(
+            "query",
+            q.position,
+            q.name,
+            &q.variable_definitions,
+            &q.directives,
+            &q.selection_set,
+        ),
+        Definition::Operation(OperationDefinition::Mutation(m)) => operation(
+            "mutation",
+            m.position,
+            m.name,
+            &m.variable_definitions,
+            &m.directives,
+            &m.selection_set,
+        ),
+        Definition::Operation(OperationDefinition::Subscription(s)) => operation(
+            "subscription",
+            s.position,
+            s.name,
+            &s.variable_definitions,
+            &s.directives,
+            &s.selection_set,
+        ),
+        Definition::Fragment(frag) => node(json!({
+            "kind": "FragmentDefinition",
+            "name": json::name(frag.name),
+            "typeCondition": type_condition(&frag.type_condition),
+            "directives": json::directives(&frag.directives),
+            "selectionSet": selection_set(&frag.selection_set),
+            "loc": json::loc(frag.position),
+        })),
+    }
+}
+
+fn operation(
+    operation: &str,
+    position: Pos,
+    name: Option<&str>,
+    variable_definitions: &[VariableDefinition],
+    directives: &[Directive],
+    set: &SelectionSet,
+) -> Json {
+    node(json!({
+        "kind": "OperationDefinition",
+        "operation": operation,
+        "name": name.map(json::name),
+        "variableDefinitions": variable_definitions
+            .iter()
+            .map(variable_definition)
+            .collect::<Vec<_>>(),
+        "directives": json::directives(directives),
+        "selectionSet": selection_set(set),
+        "loc": json::loc(position),
+    }))
+}
+
+fn variable_definition(var: &VariableDefinition) -> Json {
+    node(json!({
+        "kind": "VariableDefinition",
+        "variable": { "kind": "Variable", "name": json::name(var.name) },
+        "type": json::var_type(&var.var_type),
+        "defaultValue": var.default_value.as_ref().map(json::value),
+        "directives": [],
+        "loc": json::loc(var.position),
+    }))
+}
+
+fn type_condition(cond: &TypeCondition) -> Json {
+    match cond {
+        TypeCondition::On(name) => json::named_type(name),
+    }
+}
+
+fn selection_set(set: &SelectionSet) -> Json {
+    json!({
+        "kind": "SelectionSet",
+        "selections": set.items.iter().map(selection).collect::<Vec<_>>(),
+        "loc": json::span(set.span.0, set.span.1),
+    })
+}
+
+fn selection(sel: &Selection) -> Json {
+    match sel {
+        Selection::Field(field) => node(json!({
+            "kind": "Field",
+            "alias": field.alias.map(json::name),
+            "name": json::name(field.name),
+            "arguments": json::arguments(&field.arguments),
+            "directives": json::directives(&field.directives),
+            "selectionSet": if field.selection_set.items.is_empty() {
+                Json::Null
+            } else {
+                selection_set(&field.selection_set)
+            },
+            "loc": json::loc(field.position),
+        })),
+        Selection::FragmentSpread(spread) => json!({
+            "kind": "FragmentSpread",
+            "name": json::name(spread.fragment_name),
+            "directives": json::directives(&spread.directives),
+            "loc": json::loc(spread.position),
+        }),
+        Selection::InlineFragment(frag) => node(json!({
+            "kind": "InlineFragment",
+            "typeCondition": frag.type_condition.as_ref().map(type_condition),
+            "directives": json::directives(&frag.directives),
+            "selectionSet": selection_set(&frag.selection_set),
+            "loc": json::loc(frag.position),
+        })),
+    }
+}
+
+fn definition_from(json: &Json) -> Result<Definition<'_>, JsonError> {
+    let def = Node::new(json, "ExecutableDefinition")?;
+    match def.kind() {
+        "OperationDefinition" => operation_from(def).map(Definition::Operation),
+        "FragmentDefinition" => Ok(Definition::Fragment(FragmentDefinition {
+            position: def.position(),
+            name: def.name("name")?,
+            type_condition: TypeCondition::On(def.named_type("typeCondition")?),
+            directives: def.directives()?,
+            selection_set: selection_set_from(def.required("selectionSet")?)?,
+        })),
+        _ => Err(def.unexpected("ExecutableDefinition")),
+    }
+}
+
+fn operation_from(op: Node<'_>) -> Result<OperationDefinition<'_>, JsonError> {
+    let position = op.position();
+    let name = op.opt_name("name")?;
+    let variable_definitions = op
+        .list("variableDefinitions")?
+        .iter()
+        .map(variable_definition_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    let directives = op.directives()?;
+    let selection_set = selection_set_from(op.required("selectionSet")?)?;
+    let operation = op.str("operation")?;
+    // the shorthand form is an anonymous query starting at its selection set
+    if operation == "query"
+        && name.is_none()
+        && variable_definitions.is_empty()
+        && directives.is_empty()
+        && position == selection_set.span.0
+    {
+        return Ok(OperationDefinition::SelectionSet(selection_set));
+    }
+    let def = match operation {
+        "query" => OperationDefinition::Query(Query {
+            position,
+            name,
+            variable_definitions,
+            directives,
+            selection_set,
+        }),
+        "mutation" => OperationDefinition::Mutation(Mutation {
+            position,
+            name,
+            variable_definitions,
+            directives,
+            selection_set,
+        }),
+        "subscription" => OperationDefinition::Subscription(Subscription {
+            position,
+            name,
+            variable_definitions,
+            directives,
+            selection_set,
+        }),
+        _ => return Err(op.invalid("operation")),
+    };
+    Ok(def)
+}
+
+fn variable_definition_from(json: &Json) -> Result<VariableDefinition<'_>, JsonError> {
+    let var = Node::expect(json, "VariableDefinition")?;
+    Ok(VariableDefinition {
+        position: var.position(),
+        name: Node::expect(var.required("variable")?, "Variable")?.name("name")?,
+        var_type: var.var_type("type")?,
+        default_value: var.default_value()?,
+    })
+}
+
+fn selection_set_from(json: &Json) -> Result<SelectionSet<'_>, JsonError> {
+    let set = Node::expect(json, "SelectionSet")?;
+    Ok(SelectionSet {
+        span: (set.position(), set.end_position()),
+        items: set
+            .list("selections")?
+            .iter()
+            .map(selection_from)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn selection_from(json: &Json) -> Result<Selection<'_>, JsonError> {
+    let sel = Node::new(json, "Selection")?;
+    let position = sel.position();
+    match sel.kind() {
+        "Field" => Ok(Selection::Field(Field {
+            position,
+            alias: sel.opt_name("alias")?,
+            name: sel.name("name")?,
+            arguments: sel.arguments()?,
+            directives: sel.directives()?,
+            selection_set: match sel.get("selectionSet") {
+                Some(set) => selection_set_from(set)?,
+                None => SelectionSet {
+                    span: (position, position),
+                    items: Vec::new(),
+                },
+            },
+        })),
+        "FragmentSpread" => Ok(Selection::FragmentSpread(FragmentSpread {
+            position,
+            fragment_name: sel.name("name")?,
+            directives: sel.directives()?,
+        })),
+        "InlineFragment" => Ok(Selection::InlineFragment(InlineFragment {
+            position,
+            type_condition: match sel.get("typeCondition") {
+                Some(_) => Some(TypeCondition::On(sel.named_type("typeCondition")?)),
+                None => None,
+            },
+            directives: sel.directives()?,
+            selection_set: selection_set_from(sel.required("selectionSet")?)?,
+        })),
+        _ => Err(sel.unexpected("Selection")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{from_graphql_js_json, to_graphql_js_json, JsonError};
+    use crate::query::parse_query;
+
+    fn roundtrip(source: &str) {
+        let doc = parse_query(source).unwrap();
+        let json = to_graphql_js_json(&doc);
+        assert_eq!(from_graphql_js_json(&json).unwrap(), doc);
+    }
+
+    #[test]
+    fn roundtrips() {
+        roundtrip("{ a }");
+        roundtrip("query { a }");
+        roundtrip("query Q($x: [Int!]! = [1], $y: In = {a: 1.5}) @dir(a: $x) { a: b(c: \"d\") }");
+        roundtrip("mutation M { a { b ...F ... on T { c } ... @skip(if: true) { d } } }");
+        roundtrip("subscription { a(x: null, y: ENUM) }\nfragment F on T { a }");
+    }
+
+    #[test]
+    fn field_layout() {
+        let doc = parse_query("query { user(id: 1) { name } }").unwrap();
+        let json = to_graphql_js_json(&doc);
+        let op = &json["definitions"][0];
+        assert_eq!(op["kind"], "OperationDefinition");
+        assert_eq!(op["operation"], "query");
+        assert!(op.get("name").is_none());
+        let field = &op["selectionSet"]["selections"][0];
+        assert_eq!(field["name"], json!({"kind": "Name", "value": "user"}));
+        assert_eq!(
+            field["arguments"][0]["value"],
+            json!({"kind": "IntValue", "value": "1"})
+        );
+        assert_eq!(field["loc"]["startToken"], json!({"line": 1, "column": 9}));
+        let leaf = &field["selectionSet"]["selections"][0];
+        assert!(leaf.get("selectionSet").is_none());
+    }
+
+    #[test]
+    fn without_loc() {
+        let json = json!({
+            "kind": "Document",
+            "definitions": [{
+                "kind": "OperationDefinition",
+                "operation": "query",
+                "selectionSet": {
+                    "kind": "SelectionSet",
+                    "selections": [{"kind": "Field", "name": {"kind": "Name", "value": "a"}}],
+                },
+            }],
+        });
+        let doc = from_graphql_js_json(&json).unwrap();
+        assert_eq!(doc.to_string(), "{\n  a\n}\n");
+    }
+
+    #[test]
+    fn errors() {
+        let json = json!({"kind": "Document", "definitions": [{"kind": "ObjectTypeDefinition"}]});
+        assert_eq!(
+            from_graphql_js_json(&json).unwrap_err(),
+            JsonError::UnexpectedKind {
+                expected: "ExecutableDefinition",
+                found: "ObjectTypeDefinition".into(),
+            }
+        );
+        let json = json!({"kind": "Document", "definitions": [{"kind": "FragmentSpread"}]});
+        assert!(from_graphql_js_json(&json).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! Loading query documents split into several files
+//!
+//! Files share fragments using `#import` comments, as understood by
+//! graphql-tag and Apollo tooling:
+//!
+//! ```graphql
+//! #import "./UserFields.graphql"
+//!
+//! query User { user { ...UserFields } }
+//! ```
+//!
+//! Paths are relative to the importing file. Since AST nodes borrow the
+//! source text, loading is split in two steps: [`Sources::load`] reads all
+//! files, and [`Sources::parse`] parses them into a single document.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::query::ast::{Definition, Document};
+use crate::query::grammar::parse_query;
+
+/// Error loading or parsing a multi-file document
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("can't read {}: {}", _0.display(), _1)]
+    Io(PathBuf, #[source] io::Error),
+    #[error("can't parse {}: {}", _0.display(), _1)]
+    Parse(PathBuf, String),
+    #[error("import cycle: {}", ImportCycle(_0))]
+    ImportCycle(Vec<PathBuf>),
+}
+
+struct ImportCycle<'a>(&'a [PathBuf]);
+
+impl<'a> fmt::Display for ImportCycle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, path) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" -> ")?;
+            }
+            write!(f, "{}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// A file read by [`Sources::load`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    path: PathBuf,
+    text: String,
+    imports: Vec<PathBuf>,
+}
+
+impl SourceFile {
+    /// Path of the file, as given or joined with the importing directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Paths of imported files, as resolved against this file
+    pub fn imports(&self) -> &[PathBuf] {
+        &self.imports
+    }
+}
+
+/// Source text of a root file and all files it imports
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sources {
+    files: Vec<SourceFile>,
+}
+
+impl Sources {
+    /// Reads the root file and all files imported from it, recursively
+    ///
+    /// Each file is read once, even if imported several times. Files are
+    /// ordered by the first time they are reached, root first and imports
+    /// depth first.
+    pub fn load<P: AsRef<Path>>(root: P) -> Result<Sources, LoadError> {
+        let mut loader = Loader {
+            files: Vec::new(),
+            canonical: Vec::new(),
+            stack: Vec::new(),
+        };
+        loader.load(root.as_ref().to_path_buf())?;
+        Ok(Sources {
+            files: loader.files,
+        })
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Parses all files and merges their definitions into one document
+    pub fn parse(&self) -> Result<LoadedDocument<'_>, LoadError> {
+        let mut definitions = Vec::new();
+        let mut origins = Vec::new();
+        for (idx, file) in self.files.iter().enumerate() {
+            let doc = parse_query(&file.text)
+                .map_err(|e| LoadError::Parse(file.path.clone(), e.to_string()))?;
+            origins.extend(doc.definitions.iter().map(|_| idx));
+            definitions.extend(doc.definitions);
+        }
+        Ok(LoadedDocument {
+            document: Document { definitions },
+            origins,
+            sources: self,
+        })
+    }
+}
+
+/// A document merged from several files
+///
+/// Positions of nodes are relative to the file their definition came from,
+/// which is returned by [`LoadedDocument::source`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedDocument<'a> {
+    document: Document<'a>,
+    origins: Vec<usize>,
+    sources: &'a Sources,
+}
+
+impl<'a> LoadedDocument<'a> {
+    pub fn document(&self) -> &Document<'a> {
+        &self.document
+    }
+
+    pub fn into_document(self) -> Document<'a> {
+        self.document
+    }
+
+    /// Returns the file the definition with the index came from
+    pub fn source(&self, definition: usize) -> Option<&'a SourceFile> {
+        let sources = self.sources;
+        self.origins.get(definition).map(|&idx| &sources.files[idx])
+    }
+
+    /// Iterates over definitions along with the files they came from
+    pub fn definitions(&self) -> impl Iterator<Item = (&'a SourceFile, &Definition<'a>)> {
+        let sources = self.sources;
+        self.origins
+            .iter()
+            .map(move |&idx| &sources.files[idx])
+            .zip(&self.document.definitions)
+    }
+}
+
+struct Loader {
+    files: Vec<SourceFile>,
+    canonical: Vec<PathBuf>,
+    stack: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Loader {
+    fn load(&mut self, path: PathBuf) -> Result<(), LoadError> {
+        let canonical = fs::canonicalize(&path).map_err(|e| LoadError::Io(path.clone(), e))?;
+        if let Some(start) = self.stack.iter().position(|(c, _)| *c == canonical) {
+            let mut cycle = self.stack[start..]
+                .iter()
+                .map(|(_, path)| path.clone())
+                .collect::<Vec<_>>();
+            cycle.push(path);
+            return Err(LoadError::ImportCycle(cycle));
+        }
+        if self.canonical.contains(&canonical) {
+            return Ok(());
+        }
+        let text = fs::read_to_string(&path).map_err(|e| LoadError::Io(path.clone(), e))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let imports = imports(&text)
+            .map(|import| resolve(dir, import))
+            .collect::<Vec<_>>();
+        self.canonical.push(canonical.clone());
+        self.files.push(SourceFile {
+            path: path.clone(),
+            text,
+            imports: imports.clone(),
+        });
+        self.stack.push((canonical, path));
+        for import in imports {
+            self.load(import)?;
+        }
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// Joins the import path to the directory, dropping `.` components
+fn resolve(dir: &Path, import: &str) -> PathBuf {
+    dir.join(import)
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .collect()
+}
+
+/// Returns paths of `#import "path"` comments starting a line
+fn imports(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().filter_map(|line| {
+        let rest = line.trim_start().strip_prefix('#')?.trim_start();
+        let rest = rest.strip_prefix("import")?;
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let rest = &rest[1..];
+        rest.find(quote).map(|end| &rest[..end])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::imports;
+
+    #[test]
+    fn import_comments() {
+        let text = "#import \"./a.graphql\"\n  # import 'b.graphql'\n\
+                    #imports \"c.graphql\"\n{ a } # import \"d.graphql\"\n#import e.graphql";
+        assert_eq!(
+            imports(text).collect::<Vec<_>>(),
+            vec!["./a.graphql", "b.graphql"]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use combine::StreamOnce;
 use thiserror::Error;
 
 /// Error minifying query
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 #[error("query minify error: {}", _0)]
 pub struct MinifyError(String);
 
@@ -1,12 +1,41 @@
 //! Query language AST and parsing utilities
 //!
+mod analyze;
 mod ast;
+pub mod builder;
+mod cost;
 mod error;
 mod format;
+mod fragments;
 mod grammar;
+#[cfg(feature = "serde_json")]
+mod json;
+mod loader;
 mod minify;
+mod normalize;
+#[cfg(feature = "persisted")]
+pub mod persisted;
+mod semantic;
+mod tree_shake;
+pub mod validation;
+#[cfg(feature = "serde_json")]
+mod variables;
+pub mod visit;
 
 pub use self::grammar::{parse_query, consume_definition};
 pub use self::error::ParseError;
 pub use self::ast::*;
+pub use self::cost::{query_cost, query_cost_with, CostError, CostOptions, FieldCost, QueryCost};
+pub use self::analyze::{analyze, analyze_with, AnalysisError, AnalysisLimits, QueryAnalysis};
+pub use self::loader::{LoadError, LoadedDocument, SourceFile, Sources};
 pub use self::minify::minify_query;
+pub use self::fragments::{
+    inline_fragments, inline_fragments_with, CycleSpread, FragmentError, InlineOptions,
+};
+#[cfg(feature = "serde_json")]
+pub use self::variables::{inline_variables, InlineError, Variables};
+pub use self::tree_shake::{tree_shake, RemovedDirective, RemovedVariable, TreeShakeReport};
+pub use self::validation::{validate, validate_document};
+pub use self::normalize::{normalize, normalize_with, normalized_query, NormalizeOptions};
+#[cfg(feature = "serde_json")]
+pub use self::json::{from_graphql_js_json, to_graphql_js_json};
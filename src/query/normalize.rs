@@ -0,0 +1,219 @@
+//! Normalization of query documents to a canonical form
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::fragments::Inliner;
+use crate::query::minify::minify_query;
+use crate::query::visit::{self, Fold};
+
+/// Options of query normalization
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizeOptions {
+    inline_fragments: bool,
+}
+
+impl NormalizeOptions {
+    /// Replace fragment spreads with inline fragments
+    ///
+    /// Fragment definitions are removed if all of their spreads could be
+    /// inlined, i.e. when no fragment is missing or recursive.
+    pub fn inline_fragments(&mut self, value: bool) -> &mut Self {
+        self.inline_fragments = value;
+        self
+    }
+}
+
+/// Normalizes the document with default options
+///
+/// See [`normalize_with`].
+pub fn normalize<'a>(doc: &Document<'a>) -> Document<'a> {
+    normalize_with(doc, &NormalizeOptions::default())
+}
+
+/// Returns a canonical form of the document
+///
+/// The following is normalized:
+///
+/// * arguments of fields and directives are sorted by name
+/// * variable definitions are sorted by name
+/// * selections are sorted: fields by response key, then fragment spreads
+///   and inline fragments
+/// * fragment definitions are sorted by name and put after operations
+/// * positions are reset, so normalized documents can be compared by `==`
+///
+/// Aliases and the order of directives are kept, since they change the
+/// meaning of the query. Object values are always sorted.
+pub fn normalize_with<'a>(doc: &Document<'a>, options: &NormalizeOptions) -> Document<'a> {
+    let mut doc = doc.clone();
+    if options.inline_fragments {
+        doc = inline(doc);
+    }
+    let mut doc = Normalizer.fold_document(doc);
+    doc.definitions
+        .sort_by(|a, b| definition_key(a).cmp(&definition_key(b)));
+    doc
+}
+
+/// Returns the normalized document in compact form, suitable for hashing
+pub fn normalized_query(doc: &Document, options: &NormalizeOptions) -> String {
+    minify_query(normalize_with(doc, options).to_string())
+        .expect("formatted document is always valid")
+}
+
+fn definition_key<'a>(def: &Definition<'a>) -> Option<&'a str> {
+    match def {
+        Definition::Operation(_) => None,
+        Definition::Fragment(frag) => Some(frag.name),
+    }
+}
+
+fn selection_key<'a>(sel: &Selection<'a>) -> (u8, &'a str, &'a str) {
+    match sel {
+        Selection::Field(field) => (0, field.alias.unwrap_or(field.name), field.name),
+        Selection::FragmentSpread(spread) => (1, spread.fragment_name, ""),
+        Selection::InlineFragment(frag) => match frag.type_condition {
+            Some(TypeCondition::On(name)) => (2, name, ""),
+            None => (2, "", ""),
+        },
+    }
+}
+
+fn sort_arguments(arguments: &mut Vec<(&str, Value)>) {
+    arguments.sort_by(|a, b| a.0.cmp(b.0));
+}
+
+struct Normalizer;
+
+impl<'a> Fold<'a> for Normalizer {
+    fn fold_operation(&mut self, op: OperationDefinition<'a>) -> OperationDefinition<'a> {
+        let mut op = visit::fold_operation(self, op);
+        let vars = match op {
+            OperationDefinition::SelectionSet(_) => return op,
+            OperationDefinition::Query(ref mut q) => {
+                q.position = Pos::default();
+                &mut q.variable_definitions
+            }
+            OperationDefinition::Mutation(ref mut m) => {
+                m.position = Pos::default();
+                &mut m.variable_definitions
+            }
+            OperationDefinition::Subscription(ref mut s) => {
+                s.position = Pos::default();
+                &mut s.variable_definitions
+            }
+        };
+        vars.sort_by(|a, b| a.name.cmp(b.name));
+        op
+    }
+    fn fold_fragment_definition(&mut self, frag: FragmentDefinition<'a>) -> FragmentDefinition<'a> {
+        FragmentDefinition {
+            position: Pos::default(),
+            ..visit::fold_fragment_definition(self, frag)
+        }
+    }
+    fn fold_variable_definition(&mut self, var: VariableDefinition<'a>) -> VariableDefinition<'a> {
+        VariableDefinition {
+            position: Pos::default(),
+            ..visit::fold_variable_definition(self, var)
+        }
+    }
+    fn fold_selection_set(&mut self, set: SelectionSet<'a>) -> SelectionSet<'a> {
+        let mut set = visit::fold_selection_set(self, set);
+        set.span = (Pos::default(), Pos::default());
+        set.items
+            .sort_by(|a, b| selection_key(a).cmp(&selection_key(b)));
+        set
+    }
+    fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+        let mut field = visit::fold_field(self, field);
+        field.position = Pos::default();
+        sort_arguments(&mut field.arguments);
+        field
+    }
+    fn fold_inline_fragment(&mut self, frag: InlineFragment<'a>) -> InlineFragment<'a> {
+        InlineFragment {
+            position: Pos::default(),
+            ..visit::fold_inline_fragment(self, frag)
+        }
+    }
+    fn fold_fragment_spread(&mut self, spread: FragmentSpread<'a>) -> FragmentSpread<'a> {
+        FragmentSpread {
+            position: Pos::default(),
+            ..visit::fold_fragment_spread(self, spread)
+        }
+    }
+    fn fold_directive(&mut self, dir: Directive<'a>) -> Option<Directive<'a>> {
+        visit::fold_directive(self, dir).map(|mut dir| {
+            dir.position = Pos::default();
+            sort_arguments(&mut dir.arguments);
+            dir
+        })
+    }
+}
+
+/// Replaces fragment spreads by inline fragments
+fn inline(doc: Document) -> Document {
+    let mut inliner = Inliner::new(&doc, false);
+    let doc = inliner.fold_document(doc);
+    if inliner.error.is_some() {
+        return doc;
+    }
+    Document {
+        definitions: doc
+            .definitions
+            .into_iter()
+            .filter(|def| matches!(def, Definition::Operation(_)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, normalized_query, NormalizeOptions};
+    use crate::query::parse_query;
+
+    fn norm(source: &str) -> String {
+        normalized_query(&parse_query(source).unwrap(), &NormalizeOptions::default())
+    }
+
+    fn inlined(source: &str) -> String {
+        let mut options = NormalizeOptions::default();
+        options.inline_fragments(true);
+        normalized_query(&parse_query(source).unwrap(), &options)
+    }
+
+    #[test]
+    fn sorts() {
+        assert_eq!(
+            norm("query Q($b: Int, $a: Int) { z y(b: 2, a: {d: 1, c: 2}) @skip(if: $a) x: w }"),
+            "query Q($a:Int$b:Int){x:w y(a:{c:2 d:1}b:2)@skip(if:$a)z}"
+        );
+        assert_eq!(
+            norm("fragment B on T { b } { ... on T { a } ...B c } fragment A on T { a }"),
+            "{c...B...on T{a}}fragment A on T{a}fragment B on T{b}"
+        );
+    }
+
+    #[test]
+    fn same_for_equivalent_queries() {
+        let a = parse_query("query Q { a(x: 1, y: 2) { b c } }").unwrap();
+        let b = parse_query("query Q {\n  a(y: 2, x: 1) {\n    c, b\n  }\n}").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(normalize(&a), normalize(&b));
+    }
+
+    #[test]
+    fn inline_fragments() {
+        assert_eq!(
+            inlined("{ ...A @include(if: true) } fragment A on T { a ...B } fragment B on T { b }"),
+            "{...on T@include(if:true){a...on T{b}}}"
+        );
+        assert_eq!(
+            inlined("{ ...A ...Missing } fragment A on T { a }"),
+            "{...Missing...on T{a}}fragment A on T{a}"
+        );
+        assert_eq!(
+            inlined("{ ...A } fragment A on T { ...A }"),
+            "{...on T{...A}}fragment A on T{...A}"
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Persisted queries: hashing, manifests and allowlists
+//!
+//! Documents are identified by the SHA-256 hash of their canonical text,
+//! which is the formatted document passed through [`minify_query`]. This
+//! makes the hash independent of whitespace, commas and comments in the
+//! source, so all services hashing the same document agree on its id.
+//!
+//! The hash is compatible with automatic persisted queries (APQ) as long as
+//! clients send the canonical text.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::query::ast::Document;
+use crate::query::grammar::parse_query;
+use crate::query::minify::{minify_query, MinifyError};
+
+/// Error computing the canonical text of a query
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CanonicalError {
+    #[error("{}", _0)]
+    Parse(String),
+    #[error(transparent)]
+    Minify(#[from] MinifyError),
+}
+
+/// Error building or reading a manifest
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("can't read {}: {}", _0.display(), _1)]
+    Io(PathBuf, #[source] io::Error),
+    #[error("can't parse {}: {}", _0.display(), _1)]
+    Parse(PathBuf, #[source] CanonicalError),
+    #[error("invalid manifest: {}", _0)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Error checking a query against an allowlist
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AllowlistError {
+    #[error("query {} is not allowed", _0)]
+    NotAllowed(String),
+    #[error("query text doesn't match hash {}", _0)]
+    HashMismatch(String),
+    #[error("invalid query: {}", _0)]
+    InvalidQuery(#[from] CanonicalError),
+}
+
+/// Returns the canonical text of the document
+pub fn canonical_query(doc: &Document) -> Result<String, CanonicalError> {
+    Ok(minify_query(doc.to_string())?)
+}
+
+/// Returns the hex encoded SHA-256 hash of the query text, as used by APQ
+pub fn query_hash(query: &str) -> String {
+    Sha256::digest(query.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns the hash of the canonical text of the document
+pub fn document_hash(doc: &Document) -> Result<String, CanonicalError> {
+    canonical_query(doc).map(|text| query_hash(&text))
+}
+
+/// Parses the query and returns its canonical text
+fn canonicalize(query: &str) -> Result<String, CanonicalError> {
+    let doc = parse_query(query).map_err(|e| CanonicalError::Parse(e.to_string()))?;
+    canonical_query(&doc)
+}
+
+/// A persisted query manifest: canonical text of documents by their hash
+///
+/// Serialized as a JSON object mapping ids to document text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    documents: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn new() -> Manifest {
+        Manifest::default()
+    }
+
+    /// Builds a manifest of all `.graphql` files in a directory
+    ///
+    /// Subdirectories are searched recursively. Each file is a single
+    /// document, so fragments must be defined in the files using them.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Manifest, ManifestError> {
+        let mut manifest = Manifest::new();
+        manifest.add_dir(dir.as_ref())?;
+        Ok(manifest)
+    }
+
+    fn add_dir(&mut self, dir: &Path) -> Result<(), ManifestError> {
+        let io_error = |e| ManifestError::Io(dir.to_path_buf(), e);
+        let mut entries = fs::read_dir(dir)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error)?;
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                self.add_dir(&path)?;
+            } else if path.extension().is_some_and(|ext| ext == "graphql") {
+                let source =
+                    fs::read_to_string(&path).map_err(|e| ManifestError::Io(path.clone(), e))?;
+                self.add_query(&source)
+                    .map_err(|e| ManifestError::Parse(path.clone(), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a document to the manifest and returns its id
+    pub fn add_document(&mut self, doc: &Document) -> Result<String, CanonicalError> {
+        let text = canonical_query(doc)?;
+        let id = query_hash(&text);
+        self.documents.insert(id.clone(), text);
+        Ok(id)
+    }
+
+    /// Parses a query, adds it to the manifest and returns its id
+    pub fn add_query(&mut self, query: &str) -> Result<String, CanonicalError> {
+        let text = canonicalize(query)?;
+        let id = query_hash(&text);
+        self.documents.insert(id.clone(), text);
+        Ok(id)
+    }
+
+    /// Returns the canonical text of the document with the id
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.documents.get(id).map(|text| text.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Iterates over `(id, text)` pairs ordered by id
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.documents
+            .iter()
+            .map(|(id, text)| (id.as_str(), text.as_str()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.documents).expect("strings always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<Manifest, ManifestError> {
+        Ok(Manifest {
+            documents: serde_json::from_str(json)?,
+        })
+    }
+}
+
+/// Checks incoming queries against a manifest of trusted documents
+#[derive(Debug, Clone, PartialEq)]
+pub struct Allowlist {
+    manifest: Manifest,
+}
+
+impl Allowlist {
+    pub fn new(manifest: Manifest) -> Allowlist {
+        Allowlist { manifest }
+    }
+
+    /// Checks a `(hash, query)` pair and returns the query text to execute
+    ///
+    /// The hash must be in the manifest. When the query text is sent too,
+    /// it must have the same canonical text as the persisted document.
+    pub fn check(&self, hash: &str, query: Option<&str>) -> Result<&str, AllowlistError> {
+        let text = self
+            .manifest
+            .get(hash)
+            .ok_or_else(|| AllowlistError::NotAllowed(hash.to_string()))?;
+        if let Some(query) = query {
+            if canonicalize(query)? != text {
+                return Err(AllowlistError::HashMismatch(hash.to_string()));
+            }
+        }
+        Ok(text)
+    }
+
+    /// Checks a query sent without a hash
+    pub fn check_query(&self, query: &str) -> Result<&str, AllowlistError> {
+        let hash = query_hash(&canonicalize(query)?);
+        self.check(&hash, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        canonical_query, document_hash, query_hash, Allowlist, AllowlistError, CanonicalError,
+        Manifest,
+    };
+    use crate::query::parse_query;
+
+    const TYPENAME_HASH: &str = "ecf4edb46db40b5132295c0291d62fb65d6759a9eedfa4d5d612dd5ec54a6b38";
+
+    #[test]
+    fn hashes() {
+        assert_eq!(query_hash("{__typename}"), TYPENAME_HASH);
+        let doc = parse_query("{\n  __typename,\n}").unwrap();
+        assert_eq!(canonical_query(&doc).unwrap(), "{__typename}");
+        assert_eq!(document_hash(&doc).unwrap(), TYPENAME_HASH);
+    }
+
+    #[test]
+    fn manifest() {
+        let mut manifest = Manifest::new();
+        assert_eq!(manifest.add_query("{ __typename }").unwrap(), TYPENAME_HASH);
+        manifest.add_query("query Q($a: Int) { a(x: $a) }").unwrap();
+        assert!(matches!(
+            manifest.add_query("{ a("),
+            Err(CanonicalError::Parse(_))
+        ));
+        assert_eq!(manifest.len(), 2);
+        let json = manifest.to_json();
+        assert!(json.contains(
+            r#""ecf4edb46db40b5132295c0291d62fb65d6759a9eedfa4d5d612dd5ec54a6b38": "{__typename}""#
+        ));
+        assert_eq!(Manifest::from_json(&json).unwrap(), manifest);
+    }
+
+    #[test]
+    fn allowlist() {
+        let mut manifest = Manifest::new();
+        manifest.add_query("{ __typename }").unwrap();
+        let allowlist = Allowlist::new(manifest);
+        assert_eq!(allowlist.check(TYPENAME_HASH, None), Ok("{__typename}"));
+        assert_eq!(
+            allowlist.check(TYPENAME_HASH, Some("{ __typename, }")),
+            Ok("{__typename}")
+        );
+        assert_eq!(
+            allowlist.check(TYPENAME_HASH, Some("{ a }")),
+            Err(AllowlistError::HashMismatch(TYPENAME_HASH.into()))
+        );
+        assert_eq!(
+            allowlist.check("abc", None),
+            Err(AllowlistError::NotAllowed("abc".into()))
+        );
+        assert_eq!(
+            allowlist.check_query("{\n  __typename\n}"),
+            Ok("{__typename}")
+        );
+        assert!(allowlist.check_query("{ other }").is_err());
+    }
+}
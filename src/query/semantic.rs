@@ -0,0 +1,38 @@
+//! Semantic comparison of query documents, see `crate::semantic`
+use std::hash::{Hash, Hasher};
+
+use crate::query::ast::*;
+use crate::semantic::{SemanticEq, SemanticHash, Semantics};
+
+impl_semantic_struct! {
+    Document { definitions }
+    FragmentDefinition { name, type_condition, directives, selection_set }
+    Query { name, variable_definitions, directives, selection_set }
+    Mutation { name, variable_definitions, directives, selection_set }
+    Subscription { name, variable_definitions, directives, selection_set }
+    SelectionSet { items }
+    VariableDefinition { name, var_type, default_value }
+    Field { alias, name, arguments, directives, selection_set }
+    FragmentSpread { fragment_name, directives }
+    InlineFragment { type_condition, directives, selection_set }
+}
+
+impl_semantic_enum! {
+    Definition { Operation, Fragment }
+    OperationDefinition { SelectionSet, Query, Mutation, Subscription }
+    Selection { Field, FragmentSpread, InlineFragment }
+}
+
+impl<'a> SemanticEq for TypeCondition<'a> {
+    fn semantic_eq_with(&self, other: &Self, _: &Semantics) -> bool {
+        self == other
+    }
+}
+
+impl<'a> SemanticHash for TypeCondition<'a> {
+    fn semantic_hash_with<H: Hasher>(&self, _: &Semantics, state: &mut H) {
+        match self {
+            TypeCondition::On(name) => name.hash(state),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Removing unused parts of query documents
+use std::collections::HashSet;
+use std::mem;
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::fragments::used_fragments;
+use crate::query::visit::{self, Control, Fold, Path, Visitor};
+
+/// A variable definition removed by [`tree_shake`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedVariable {
+    /// Name of the operation which defined the variable
+    pub operation: Option<String>,
+    pub name: String,
+    pub position: Pos,
+}
+
+/// A directive removed by [`tree_shake`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedDirective {
+    pub name: String,
+    pub position: Pos,
+}
+
+/// What [`tree_shake`] removed from a document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeShakeReport {
+    /// Names of removed fragment definitions, in document order
+    pub fragments: Vec<String>,
+    pub variables: Vec<RemovedVariable>,
+    pub directives: Vec<RemovedDirective>,
+}
+
+impl TreeShakeReport {
+    /// Returns `true` if nothing was removed
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty() && self.variables.is_empty() && self.directives.is_empty()
+    }
+}
+
+/// Removes the parts of the document which don't affect execution
+///
+/// The following is removed, in this order:
+///
+/// * `@include(if: true)` and `@skip(if: false)` directives
+/// * fragment definitions which aren't used by any operation, directly or
+///   through other fragments
+/// * variable definitions which aren't referenced in the operation or in
+///   the fragments it uses
+///
+/// Spreads of fragments which aren't defined are kept as is.
+pub fn tree_shake(doc: &mut Document) -> TreeShakeReport {
+    let mut report = TreeShakeReport::default();
+
+    let mut remover = NoopDirectives {
+        removed: Vec::new(),
+    };
+    let definitions = mem::take(&mut doc.definitions);
+    doc.definitions = remover.fold_document(Document { definitions }).definitions;
+    report.directives = remover.removed;
+
+    let mut used = HashSet::new();
+    for def in &doc.definitions {
+        if let Definition::Operation(op) = def {
+            used.extend(used_fragments(doc, op).indices);
+        }
+    }
+    let mut idx = 0;
+    doc.definitions.retain(|def| {
+        let keep = match def {
+            Definition::Operation(_) => true,
+            Definition::Fragment(frag) => {
+                let keep = used.contains(&idx);
+                if !keep {
+                    report.fragments.push(frag.name.to_string());
+                }
+                keep
+            }
+        };
+        idx += 1;
+        keep
+    });
+
+    let usages = doc
+        .definitions
+        .iter()
+        .map(|def| match def {
+            Definition::Operation(op) => variables_used(doc, op),
+            Definition::Fragment(_) => HashSet::new(),
+        })
+        .collect::<Vec<_>>();
+    for (def, used) in doc.definitions.iter_mut().zip(usages) {
+        let (name, vars) = match def {
+            Definition::Operation(OperationDefinition::Query(q)) => {
+                (q.name, &mut q.variable_definitions)
+            }
+            Definition::Operation(OperationDefinition::Mutation(m)) => {
+                (m.name, &mut m.variable_definitions)
+            }
+            Definition::Operation(OperationDefinition::Subscription(s)) => {
+                (s.name, &mut s.variable_definitions)
+            }
+            Definition::Operation(OperationDefinition::SelectionSet(_))
+            | Definition::Fragment(_) => continue,
+        };
+        vars.retain(|var| {
+            let keep = used.contains(var.name);
+            if !keep {
+                report.variables.push(RemovedVariable {
+                    operation: name.map(|n| n.to_string()),
+                    name: var.name.to_string(),
+                    position: var.position,
+                });
+            }
+            keep
+        });
+    }
+    report
+}
+
+/// Returns names of variables used by the operation and its fragments
+fn variables_used(doc: &Document, op: &OperationDefinition) -> HashSet<String> {
+    let mut collector = VariableCollector::default();
+    visit::walk_operation(&mut collector, op, &mut Path::new());
+    for idx in used_fragments(doc, op).indices {
+        if let Definition::Fragment(frag) = &doc.definitions[idx] {
+            visit::walk_fragment_definition(&mut collector, frag, &mut Path::new());
+        }
+    }
+    collector.names
+}
+
+#[derive(Default)]
+struct VariableCollector {
+    names: HashSet<String>,
+}
+
+impl<'b> Visitor<'b> for VariableCollector {
+    fn enter_value(&mut self, value: &'b Value<'b>, _: &Path<'b>) -> Control {
+        if let Value::Variable(name) = value {
+            self.names.insert(name.to_string());
+        }
+        Control::Continue
+    }
+}
+
+struct NoopDirectives {
+    removed: Vec<RemovedDirective>,
+}
+
+impl<'a> Fold<'a> for NoopDirectives {
+    fn fold_directive(&mut self, dir: Directive<'a>) -> Option<Directive<'a>> {
+        let noop = matches!(
+            (dir.name, dir.arguments.as_slice()),
+            ("include", [("if", Value::Boolean(true))]) | ("skip", [("if", Value::Boolean(false))])
+        );
+        if noop {
+            self.removed.push(RemovedDirective {
+                name: dir.name.to_string(),
+                position: dir.position,
+            });
+            return None;
+        }
+        visit::fold_directive(self, dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tree_shake, RemovedDirective, RemovedVariable};
+    use crate::position::Pos;
+    use crate::query::parse_query;
+
+    #[test]
+    fn removes_unused() {
+        let mut doc = parse_query(
+            "query Q($a: Int, $b: Int, $c: Int) { ...F x @include(if: true) @skip(if: $c) }\n\
+             fragment F on T { f(a: [{v: $a}]) ...G @skip(if: false) }\n\
+             fragment G on T { g }\n\
+             fragment Unused on T { ...Unused2 }\n\
+             fragment Unused2 on T { u }",
+        )
+        .unwrap();
+        let report = tree_shake(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            "query Q($a: Int, $c: Int) {\n  ...F\n  x @skip(if: $c)\n}\n\n\
+             fragment F on T {\n  f(a: [{v: $a}])\n  ...G\n}\n\n\
+             fragment G on T {\n  g\n}\n"
+        );
+        assert_eq!(report.fragments, vec!["Unused", "Unused2"]);
+        assert_eq!(
+            report.variables,
+            vec![RemovedVariable {
+                operation: Some("Q".into()),
+                name: "b".into(),
+                position: Pos {
+                    line: 1,
+                    column: 18
+                },
+            }]
+        );
+        assert_eq!(
+            report.directives,
+            vec![
+                RemovedDirective {
+                    name: "include".into(),
+                    position: Pos {
+                        line: 1,
+                        column: 45
+                    },
+                },
+                RemovedDirective {
+                    name: "skip".into(),
+                    position: Pos {
+                        line: 2,
+                        column: 40
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_used() {
+        let source = "query A($v: Int) { ...F } mutation B { ...F ...Missing }\n\
+                      fragment F on T { f(v: $v) @include(if: false) }";
+        let mut doc = parse_query(source).unwrap();
+        let report = tree_shake(&mut doc);
+        assert!(report.is_empty());
+        assert_eq!(doc, parse_query(source).unwrap());
+    }
+}
@@ -0,0 +1,378 @@
+use std::convert::TryFrom;
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::schema::Schema;
+use crate::schema::{self, DirectiveLocation, InputValue, TypeDefinition};
+
+use super::walk::{walk, TypedVisitor};
+use super::{Context, Rule, ValidationError};
+
+/// Calls `check` for arguments of every field and directive with known
+/// definitions
+///
+/// `check` gets the name of the field (`Type.field`) or the directive
+/// (`@dir`), the definitions of the arguments, the arguments and the
+/// position of the field or directive.
+fn walk_arguments<'b, 'a, F>(ctx: &Context<'b, 'a>, check: F)
+where
+    F: FnMut(&str, &'b [InputValue<'a>], &'b [(&'a str, Value<'a>)], Pos),
+{
+    struct Visitor<'c, 'b, 'a, F> {
+        ctx: &'c Context<'b, 'a>,
+        check: F,
+    }
+    impl<'c, 'b, 'a, F> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a, F>
+    where
+        F: FnMut(&str, &'b [InputValue<'a>], &'b [(&'a str, Value<'a>)], Pos),
+    {
+        fn enter_field(
+            &mut self,
+            field: &'b Field<'a>,
+            parent: Option<&'a str>,
+            def: Option<&'b schema::Field<'a>>,
+        ) {
+            if let (Some(parent), Some(def)) = (parent, def) {
+                let name = format!("{}.{}", parent, field.name);
+                (self.check)(&name, &def.arguments, &field.arguments, field.position);
+            }
+        }
+        fn enter_directives(&mut self, directives: &'b [Directive<'a>], _: DirectiveLocation) {
+            for dir in directives {
+                if let Some(def) = self.ctx.get_directive(dir.name) {
+                    let name = format!("@{}", dir.name);
+                    (self.check)(&name, &def.arguments, &dir.arguments, dir.position);
+                }
+            }
+        }
+    }
+    walk(ctx, &mut Visitor { ctx, check });
+}
+
+/// Arguments must be defined by the field or directive
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KnownArgumentNames;
+
+impl Rule for KnownArgumentNames {
+    fn name(&self) -> &'static str {
+        "KnownArgumentNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        walk_arguments(ctx, |name, defs, args, position| {
+            for (arg, _) in args {
+                if !defs.iter().any(|def| def.name == *arg) {
+                    let on = if name.starts_with('@') {
+                        "directive"
+                    } else {
+                        "field"
+                    };
+                    errors.push(ValidationError {
+                        rule: "KnownArgumentNames",
+                        message: format!("Unknown argument {:?} on {} \"{}\".", arg, on, name),
+                        positions: vec![position],
+                    });
+                }
+            }
+        });
+    }
+}
+
+/// Arguments of a field or directive must have unique names
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniqueArgumentNames;
+
+impl Rule for UniqueArgumentNames {
+    fn name(&self) -> &'static str {
+        "UniqueArgumentNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c> {
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c> Visitor<'c> {
+            fn check(&mut self, args: &[(&str, Value)], position: Pos) {
+                for (idx, (name, _)) in args.iter().enumerate() {
+                    if args[..idx].iter().any(|(other, _)| other == name) {
+                        self.errors.push(ValidationError {
+                            rule: "UniqueArgumentNames",
+                            message: format!("There can be only one argument named {:?}.", name),
+                            positions: vec![position],
+                        });
+                    }
+                }
+            }
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c> {
+            fn enter_field(
+                &mut self,
+                field: &'b Field<'a>,
+                _: Option<&'a str>,
+                _: Option<&'b schema::Field<'a>>,
+            ) {
+                self.check(&field.arguments, field.position);
+            }
+            fn enter_directives(&mut self, directives: &'b [Directive<'a>], _: DirectiveLocation) {
+                for dir in directives {
+                    self.check(&dir.arguments, dir.position);
+                }
+            }
+        }
+        walk(ctx, &mut Visitor { errors });
+    }
+}
+
+/// Non-null arguments without default values must be provided
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProvidedRequiredArguments;
+
+impl Rule for ProvidedRequiredArguments {
+    fn name(&self) -> &'static str {
+        "ProvidedRequiredArguments"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        walk_arguments(ctx, |name, defs, args, position| {
+            for def in defs {
+                let required =
+                    matches!(def.value_type, Type::NonNullType(_)) && def.default_value.is_none();
+                if required && !args.iter().any(|(arg, _)| *arg == def.name) {
+                    let (kind, name) = match name.strip_prefix('@') {
+                        Some(_) => ("Directive", name),
+                        None => ("Field", name.rsplit('.').next().unwrap_or(name)),
+                    };
+                    errors.push(ValidationError {
+                        rule: "ProvidedRequiredArguments",
+                        message: format!(
+                            "{} \"{}\" argument {:?} of type \"{}\" is required, \
+                             but it was not provided.",
+                            kind, name, def.name, def.value_type
+                        ),
+                        positions: vec![position],
+                    });
+                }
+            }
+        });
+    }
+}
+
+/// Literal values must be valid for their input types
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValuesOfCorrectType;
+
+impl Rule for ValuesOfCorrectType {
+    fn name(&self) -> &'static str {
+        "ValuesOfCorrectType"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let schema = match ctx.schema() {
+            Some(schema) => schema,
+            None => return,
+        };
+        let mut push = |messages: Vec<String>, position: Pos| {
+            errors.extend(messages.into_iter().map(|message| ValidationError {
+                rule: "ValuesOfCorrectType",
+                message,
+                positions: vec![position],
+            }));
+        };
+        for op in ctx.document().operations() {
+            for var in op.variable_definitions() {
+                if let Some(ref value) = var.default_value {
+                    let mut messages = Vec::new();
+                    value_errors(schema, value, &var.var_type, &mut messages);
+                    push(messages, var.position);
+                }
+            }
+        }
+        walk_arguments(ctx, |_, defs, args, position| {
+            let mut messages = Vec::new();
+            for (arg, value) in args {
+                if let Some(def) = defs.iter().find(|def| def.name == *arg) {
+                    value_errors(schema, value, &def.value_type, &mut messages);
+                }
+            }
+            push(messages, position);
+        });
+    }
+}
+
+/// Pushes messages describing why the value isn't valid for the type
+///
+/// Variables are not checked, this is done by
+/// [`VariablesInAllowedPosition`](super::VariablesInAllowedPosition).
+pub(crate) fn value_errors(schema: &Schema, value: &Value, ty: &Type, messages: &mut Vec<String>) {
+    let expected = |messages: &mut Vec<String>| {
+        messages.push(format!(
+            "Expected value of type \"{}\", found {}.",
+            ty, value
+        ));
+    };
+    match (ty, value) {
+        (_, Value::Variable(_)) => {}
+        (Type::NonNullType(_), Value::Null) => expected(messages),
+        (Type::NonNullType(inner), _) => value_errors(schema, value, inner, messages),
+        (_, Value::Null) => {}
+        (Type::ListType(inner), Value::List(items)) => {
+            for item in items {
+                value_errors(schema, item, inner, messages);
+            }
+        }
+        (Type::ListType(inner), _) => value_errors(schema, value, inner, messages),
+        (Type::NamedType(name), _) => match schema.get_type(name) {
+            Some(TypeDefinition::Scalar(_)) => {
+                let valid = match (*name, value) {
+                    ("Int", Value::Int(n)) => {
+                        let n = n.as_i64().unwrap_or(i64::MAX);
+                        if i32::try_from(n).is_err() {
+                            messages.push(format!(
+                                "Int cannot represent non 32-bit signed integer value: {}",
+                                n
+                            ));
+                        }
+                        true
+                    }
+                    ("Float", Value::Int(_)) | ("Float", Value::Float(_)) => true,
+                    ("String", Value::String(_)) => true,
+                    ("Boolean", Value::Boolean(_)) => true,
+                    ("ID", Value::String(_)) | ("ID", Value::Int(_)) => true,
+                    ("Int", _) | ("Float", _) | ("String", _) | ("Boolean", _) | ("ID", _) => false,
+                    // custom scalars may accept any literal
+                    _ => true,
+                };
+                if !valid {
+                    expected(messages);
+                }
+            }
+            Some(TypeDefinition::Enum(enum_type)) => match value {
+                Value::Enum(v) if enum_type.values.iter().any(|ev| ev.name == *v) => {}
+                Value::Enum(v) => messages.push(format!(
+                    "Value {:?} does not exist in {:?} enum.",
+                    v, enum_type.name
+                )),
+                _ => expected(messages),
+            },
+            Some(TypeDefinition::InputObject(input)) => match value {
+                Value::Object(fields) => {
+                    for (key, value) in fields {
+                        match input.fields.iter().find(|f| f.name == *key) {
+                            Some(field) => value_errors(schema, value, &field.value_type, messages),
+                            None => messages.push(format!(
+                                "Field {:?} is not defined by type {:?}.",
+                                key, input.name
+                            )),
+                        }
+                    }
+                    for field in &input.fields {
+                        let required = matches!(field.value_type, Type::NonNullType(_))
+                            && field.default_value.is_none();
+                        if required && !fields.contains_key(field.name) {
+                            messages.push(format!(
+                                "Field \"{}.{}\" of required type \"{}\" was not provided.",
+                                input.name, field.name, field.value_type
+                            ));
+                        }
+                    }
+                }
+                _ => expected(messages),
+            },
+            _ => {}
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniqueArgumentNames;
+    use super::{KnownArgumentNames, ProvidedRequiredArguments, ValuesOfCorrectType};
+    use crate::query::validation::tests::{check, check_document};
+
+    #[test]
+    fn known_argument_names() {
+        assert!(check(
+            KnownArgumentNames,
+            "{ dog { doesKnowCommand(dogCommand: SIT) name @skip(if: true) } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                KnownArgumentNames,
+                "{ dog { name(foo: 1) @include(when: true) } }"
+            ),
+            vec![
+                r#"1:9: Unknown argument "foo" on field "Dog.name"."#,
+                r#"1:22: Unknown argument "when" on directive "@include"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_argument_names() {
+        assert!(
+            check_document(UniqueArgumentNames, "{ f(a: 1, b: 2) g(a: 1) @d(a: 1) }").is_empty()
+        );
+        assert_eq!(
+            check_document(UniqueArgumentNames, "{ f(a: 1, a: 2) @d(b: 1, b: 2) }"),
+            vec![
+                r#"1:3: There can be only one argument named "a"."#,
+                r#"1:17: There can be only one argument named "b"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn provided_required_arguments() {
+        assert!(check(
+            ProvidedRequiredArguments,
+            "{ complicatedArgs { multipleReqs(req1: 1, req2: 2) multipleOpts } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                ProvidedRequiredArguments,
+                "{ complicatedArgs { multipleReqs(req2: 2) } dog @skip { name } }"
+            ),
+            vec![
+                "1:21: Field \"multipleReqs\" argument \"req1\" of type \"Int!\" is required, but \
+                 it was not provided.",
+                "1:49: Directive \"@skip\" argument \"if\" of type \"Boolean!\" is required, but \
+                 it was not provided.",
+            ]
+        );
+    }
+
+    #[test]
+    fn values_of_correct_type() {
+        assert!(check(
+            ValuesOfCorrectType,
+            "query($a: Int = 1, $l: [String] = \"x\") { complicatedArgs {
+                intArgField(intArg: $a)
+                stringListArgField(stringListArg: [\"a\", null])
+                complexArgField(complexArg: {requiredField: true, stringListField: \"s\"})
+                enumArgField(enumArg: SIT)
+            } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                ValuesOfCorrectType,
+                "query($b: Boolean! = null) { complicatedArgs {
+                    intArgField(intArg: \"3\")
+                    nonNullIntArgField(nonNullIntArg: 3000000000)
+                    stringListNonNullArgField(stringListNonNullArg: [\"a\", null])
+                    complexArgField(complexArg: {intField: 1.5, unknown: 1})
+                    enumArgField(enumArg: JUMP)
+                } }"
+            ),
+            vec![
+                r#"1:7: Expected value of type "Boolean!", found null."#,
+                r#"2:21: Expected value of type "Int", found "3"."#,
+                r#"3:21: Int cannot represent non 32-bit signed integer value: 3000000000"#,
+                r#"4:21: Expected value of type "String!", found null."#,
+                r#"5:21: Expected value of type "Int", found 1.5."#,
+                r#"5:21: Field "unknown" is not defined by type "ComplexInput"."#,
+                "5:21: Field \"ComplexInput.requiredField\" of required type \"Boolean!\" was not \
+                 provided.",
+                r#"6:21: Value "JUMP" does not exist in "DogCommand" enum."#,
+            ]
+        );
+    }
+}
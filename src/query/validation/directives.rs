@@ -0,0 +1,130 @@
+use crate::query::ast::*;
+use crate::schema::DirectiveLocation;
+
+use super::walk::{walk, TypedVisitor};
+use super::{Context, Rule, ValidationError};
+
+/// Directives must be defined and used in allowed locations
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KnownDirectives;
+
+impl Rule for KnownDirectives {
+    fn name(&self) -> &'static str {
+        "KnownDirectives"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_directives(
+                &mut self,
+                directives: &'b [Directive<'a>],
+                location: DirectiveLocation,
+            ) {
+                for dir in directives {
+                    let message = match self.ctx.get_directive(dir.name) {
+                        None => format!("Unknown directive \"@{}\".", dir.name),
+                        Some(def) if !def.locations.contains(&location) => format!(
+                            "Directive \"@{}\" may not be used on {}.",
+                            dir.name,
+                            location.as_str()
+                        ),
+                        Some(_) => continue,
+                    };
+                    self.errors.push(ValidationError {
+                        rule: "KnownDirectives",
+                        message,
+                        positions: vec![dir.position],
+                    });
+                }
+            }
+        }
+        if ctx.has_schema() {
+            walk(ctx, &mut Visitor { ctx, errors });
+        }
+    }
+}
+
+/// Directives which aren't repeatable may be used once per location
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniqueDirectivesPerLocation;
+
+impl Rule for UniqueDirectivesPerLocation {
+    fn name(&self) -> &'static str {
+        "UniqueDirectivesPerLocation"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_directives(&mut self, directives: &'b [Directive<'a>], _: DirectiveLocation) {
+                for (idx, dir) in directives.iter().enumerate() {
+                    let repeatable = self
+                        .ctx
+                        .get_directive(dir.name)
+                        .is_some_and(|def| def.repeatable);
+                    if repeatable {
+                        continue;
+                    }
+                    if let Some(first) = directives[..idx].iter().find(|d| d.name == dir.name) {
+                        self.errors.push(ValidationError {
+                            rule: "UniqueDirectivesPerLocation",
+                            message: format!(
+                                "The directive \"@{}\" can only be used once at this location.",
+                                dir.name
+                            ),
+                            positions: vec![first.position, dir.position],
+                        });
+                    }
+                }
+            }
+        }
+        // without a schema, any directive might be repeatable
+        if ctx.has_schema() {
+            walk(ctx, &mut Visitor { ctx, errors });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KnownDirectives, UniqueDirectivesPerLocation};
+    use crate::query::validation::tests::check;
+
+    #[test]
+    fn known_directives() {
+        assert!(check(
+            KnownDirectives,
+            "{ dog @include(if: true) { name @onField } ...F @repeat } fragment F on QueryRoot { \
+             dog { name } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(KnownDirectives, "query @onField { dog @unknown { name } }"),
+            vec![
+                r#"1:7: Directive "@onField" may not be used on QUERY."#,
+                r#"1:22: Unknown directive "@unknown"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_directives_per_location() {
+        assert!(check(
+            UniqueDirectivesPerLocation,
+            "{ dog @repeat @repeat @skip(if: false) { name @skip(if: true) } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                UniqueDirectivesPerLocation,
+                "{ dog @skip(if: true) @skip(if: false) { name } }"
+            ),
+            vec![r#"1:7, 1:23: The directive "@skip" can only be used once at this location."#]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::query::ast::*;
+use crate::schema;
+use crate::schema::index::named_type;
+
+use super::walk::{walk, TypedVisitor};
+use super::{Context, Rule, ValidationError};
+
+/// Fields must be defined on the type they are selected on
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldsOnCorrectType;
+
+impl Rule for FieldsOnCorrectType {
+    fn name(&self) -> &'static str {
+        "FieldsOnCorrectType"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_field(
+                &mut self,
+                field: &'b Field<'a>,
+                parent: Option<&'a str>,
+                def: Option<&'b schema::Field<'a>>,
+            ) {
+                let parent = match parent {
+                    Some(parent) if def.is_none() => parent,
+                    _ => return,
+                };
+                if let Some(schema) = self.ctx.schema() {
+                    if schema.is_composite(parent) {
+                        self.errors.push(ValidationError {
+                            rule: "FieldsOnCorrectType",
+                            message: format!(
+                                "Cannot query field {:?} on type {:?}.",
+                                field.name, parent
+                            ),
+                            positions: vec![field.position],
+                        });
+                    }
+                }
+            }
+        }
+        walk(ctx, &mut Visitor { ctx, errors });
+    }
+}
+
+/// Leaf fields must not have selections, composite ones must have
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScalarLeafs;
+
+impl Rule for ScalarLeafs {
+    fn name(&self) -> &'static str {
+        "ScalarLeafs"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_field(
+                &mut self,
+                field: &'b Field<'a>,
+                _: Option<&'a str>,
+                def: Option<&'b schema::Field<'a>>,
+            ) {
+                let (def, schema) = match (def, self.ctx.schema()) {
+                    (Some(def), Some(schema)) => (def, schema),
+                    _ => return,
+                };
+                let type_name = named_type(&def.field_type);
+                let has_selection = !field.selection_set.items.is_empty();
+                let message = if schema.is_leaf(type_name) && has_selection {
+                    format!(
+                        "Field {:?} must not have a selection since type \"{}\" has no subfields.",
+                        field.name, def.field_type
+                    )
+                } else if schema.is_composite(type_name) && !has_selection {
+                    format!(
+                        "Field {:?} of type \"{}\" must have a selection of subfields. \
+                         Did you mean \"{} {{ ... }}\"?",
+                        field.name, def.field_type, field.name
+                    )
+                } else {
+                    return;
+                };
+                self.errors.push(ValidationError {
+                    rule: "ScalarLeafs",
+                    message,
+                    positions: vec![field.position],
+                });
+            }
+        }
+        walk(ctx, &mut Visitor { ctx, errors });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldsOnCorrectType, ScalarLeafs};
+    use crate::query::validation::tests::check;
+
+    #[test]
+    fn fields_on_correct_type() {
+        assert!(check(
+            FieldsOnCorrectType,
+            "{ dog { __typename name ... on Dog { barks } } catOrDog { __typename } __schema { \
+             types { name } } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                FieldsOnCorrectType,
+                "{ dog { meowVolume } catOrDog { name } pet { barks } unknown }"
+            ),
+            vec![
+                r#"1:9: Cannot query field "meowVolume" on type "Dog"."#,
+                r#"1:33: Cannot query field "name" on type "CatOrDog"."#,
+                r#"1:46: Cannot query field "barks" on type "Pet"."#,
+                r#"1:54: Cannot query field "unknown" on type "QueryRoot"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn scalar_leafs() {
+        assert!(check(ScalarLeafs, "{ dog { name barks } }").is_empty());
+        assert_eq!(
+            check(ScalarLeafs, "{ human { name { x } pets } }"),
+            vec![
+                "1:11: Field \"name\" must not have a selection since type \"String\" has no \
+                 subfields.",
+                "1:22: Field \"pets\" of type \"[Pet]\" must have a selection of subfields. Did \
+                 you mean \"pets { ... }\"?",
+            ]
+        );
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::fragments::used_fragments;
+use crate::schema::index::named_type;
+
+use super::walk::{walk, TypedVisitor};
+use super::{Context, Rule, ValidationError};
+
+/// Types referenced by variables and fragments must be defined
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KnownTypeNames;
+
+impl Rule for KnownTypeNames {
+    fn name(&self) -> &'static str {
+        "KnownTypeNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> Visitor<'c, 'b, 'a> {
+            fn check(&mut self, name: &str, position: Pos) {
+                if self.ctx.get_type(name).is_none() {
+                    self.errors.push(ValidationError {
+                        rule: "KnownTypeNames",
+                        message: format!("Unknown type {:?}.", name),
+                        positions: vec![position],
+                    });
+                }
+            }
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_operation(&mut self, op: &'b OperationDefinition<'a>, _: Option<&'a str>) {
+                for var in op.variable_definitions() {
+                    self.check(named_type(&var.var_type), var.position);
+                }
+            }
+            fn enter_fragment_definition(&mut self, frag: &'b FragmentDefinition<'a>) {
+                let TypeCondition::On(name) = frag.type_condition;
+                self.check(name, frag.position);
+            }
+            fn enter_inline_fragment(&mut self, frag: &'b InlineFragment<'a>, _: Option<&'a str>) {
+                if let Some(TypeCondition::On(name)) = frag.type_condition {
+                    self.check(name, frag.position);
+                }
+            }
+        }
+        if ctx.has_schema() {
+            walk(ctx, &mut Visitor { ctx, errors });
+        }
+    }
+}
+
+/// Fragments may only be defined on objects, interfaces and unions
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FragmentsOnCompositeTypes;
+
+impl Rule for FragmentsOnCompositeTypes {
+    fn name(&self) -> &'static str {
+        "FragmentsOnCompositeTypes"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> Visitor<'c, 'b, 'a> {
+            /// Returns true for known types which are not composite
+            fn invalid(&self, name: &str) -> bool {
+                match self.ctx.schema() {
+                    Some(schema) => schema.get_type(name).is_some() && !schema.is_composite(name),
+                    None => false,
+                }
+            }
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_fragment_definition(&mut self, frag: &'b FragmentDefinition<'a>) {
+                let TypeCondition::On(name) = frag.type_condition;
+                if self.invalid(name) {
+                    self.errors.push(ValidationError {
+                        rule: "FragmentsOnCompositeTypes",
+                        message: format!(
+                            "Fragment {:?} cannot condition on non composite type {:?}.",
+                            frag.name, name
+                        ),
+                        positions: vec![frag.position],
+                    });
+                }
+            }
+            fn enter_inline_fragment(&mut self, frag: &'b InlineFragment<'a>, _: Option<&'a str>) {
+                if let Some(TypeCondition::On(name)) = frag.type_condition {
+                    if self.invalid(name) {
+                        self.errors.push(ValidationError {
+                            rule: "FragmentsOnCompositeTypes",
+                            message: format!(
+                                "Fragment cannot condition on non composite type {:?}.",
+                                name
+                            ),
+                            positions: vec![frag.position],
+                        });
+                    }
+                }
+            }
+        }
+        walk(ctx, &mut Visitor { ctx, errors });
+    }
+}
+
+/// Fragments must be spread where their type may apply
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PossibleFragmentSpreads;
+
+impl Rule for PossibleFragmentSpreads {
+    fn name(&self) -> &'static str {
+        "PossibleFragmentSpreads"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> Visitor<'c, 'b, 'a> {
+            /// Returns true if both types are composite with no common
+            /// possible types
+            fn disjoint(&self, parent: &str, fragment: &str) -> bool {
+                let schema = match self.ctx.schema() {
+                    Some(schema) => schema,
+                    None => return false,
+                };
+                if !schema.is_composite(parent) || !schema.is_composite(fragment) {
+                    return false;
+                }
+                let fragment_types = schema.possible_types(fragment);
+                !schema
+                    .possible_types(parent)
+                    .iter()
+                    .any(|ty| fragment_types.contains(ty))
+            }
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_inline_fragment(
+                &mut self,
+                frag: &'b InlineFragment<'a>,
+                parent: Option<&'a str>,
+            ) {
+                if let (Some(parent), Some(TypeCondition::On(name))) =
+                    (parent, &frag.type_condition)
+                {
+                    if self.disjoint(parent, name) {
+                        self.errors.push(ValidationError {
+                            rule: "PossibleFragmentSpreads",
+                            message: format!(
+                                "Fragment cannot be spread here as objects of type {:?} \
+                                 can never be of type {:?}.",
+                                parent, name
+                            ),
+                            positions: vec![frag.position],
+                        });
+                    }
+                }
+            }
+            fn enter_fragment_spread(
+                &mut self,
+                spread: &'b FragmentSpread<'a>,
+                parent: Option<&'a str>,
+            ) {
+                let frag = self.ctx.fragment(spread.fragment_name);
+                if let (Some(parent), Some(frag)) = (parent, frag) {
+                    let TypeCondition::On(name) = frag.type_condition;
+                    if self.disjoint(parent, name) {
+                        self.errors.push(ValidationError {
+                            rule: "PossibleFragmentSpreads",
+                            message: format!(
+                                "Fragment {:?} cannot be spread here as objects of type {:?} \
+                                 can never be of type {:?}.",
+                                spread.fragment_name, parent, name
+                            ),
+                            positions: vec![spread.position],
+                        });
+                    }
+                }
+            }
+        }
+        walk(ctx, &mut Visitor { ctx, errors });
+    }
+}
+
+/// Fragments must have unique names
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniqueFragmentNames;
+
+impl Rule for UniqueFragmentNames {
+    fn name(&self) -> &'static str {
+        "UniqueFragmentNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        for frag in ctx.document().fragments() {
+            let first = ctx.fragment(frag.name).expect("fragment is defined");
+            if !std::ptr::eq(first, frag) {
+                errors.push(ValidationError {
+                    rule: "UniqueFragmentNames",
+                    message: format!("There can be only one fragment named {:?}.", frag.name),
+                    positions: vec![first.position, frag.position],
+                });
+            }
+        }
+    }
+}
+
+/// Spread fragments must be defined
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KnownFragmentNames;
+
+impl Rule for KnownFragmentNames {
+    fn name(&self) -> &'static str {
+        "KnownFragmentNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_fragment_spread(
+                &mut self,
+                spread: &'b FragmentSpread<'a>,
+                _: Option<&'a str>,
+            ) {
+                if self.ctx.fragment(spread.fragment_name).is_none() {
+                    self.errors.push(ValidationError {
+                        rule: "KnownFragmentNames",
+                        message: format!("Unknown fragment {:?}.", spread.fragment_name),
+                        positions: vec![spread.position],
+                    });
+                }
+            }
+        }
+        walk(ctx, &mut Visitor { ctx, errors });
+    }
+}
+
+/// Fragments must not spread themselves, directly or through other
+/// fragments
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoFragmentCycles;
+
+impl Rule for NoFragmentCycles {
+    fn name(&self) -> &'static str {
+        "NoFragmentCycles"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Detector<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+            visited: HashSet<&'a str>,
+            /// Spreads followed from the fragment the search started with
+            path: Vec<&'b FragmentSpread<'a>>,
+            /// Index in `path` where each fragment on the path is entered
+            entered: HashMap<&'a str, usize>,
+        }
+        impl<'c, 'b, 'a> Detector<'c, 'b, 'a> {
+            fn detect(&mut self, frag: &'b FragmentDefinition<'a>) {
+                self.visited.insert(frag.name);
+                self.entered.insert(frag.name, self.path.len());
+                let mut spreads = Vec::new();
+                collect_spreads(&frag.selection_set, &mut spreads);
+                for spread in spreads {
+                    let name = spread.fragment_name;
+                    if let Some(&start) = self.entered.get(name) {
+                        let cycle = &self.path[start..];
+                        let via = cycle
+                            .iter()
+                            .map(|spread| format!("{:?}", spread.fragment_name))
+                            .collect::<Vec<_>>();
+                        let message = if via.is_empty() {
+                            format!("Cannot spread fragment {:?} within itself.", name)
+                        } else {
+                            format!(
+                                "Cannot spread fragment {:?} within itself via {}.",
+                                name,
+                                via.join(", ")
+                            )
+                        };
+                        self.errors.push(ValidationError {
+                            rule: "NoFragmentCycles",
+                            message,
+                            positions: cycle
+                                .iter()
+                                .chain(Some(&spread))
+                                .map(|spread| spread.position)
+                                .collect(),
+                        });
+                    } else if !self.visited.contains(name) {
+                        if let Some(target) = self.ctx.fragment(name) {
+                            self.path.push(spread);
+                            self.detect(target);
+                            self.path.pop();
+                        }
+                    }
+                }
+                self.entered.remove(frag.name);
+            }
+        }
+        let mut detector = Detector {
+            ctx,
+            errors,
+            visited: HashSet::new(),
+            path: Vec::new(),
+            entered: HashMap::new(),
+        };
+        for frag in ctx.document().fragments() {
+            if !detector.visited.contains(frag.name) {
+                detector.detect(frag);
+            }
+        }
+    }
+}
+
+fn collect_spreads<'b, 'a>(set: &'b SelectionSet<'a>, spreads: &mut Vec<&'b FragmentSpread<'a>>) {
+    for item in &set.items {
+        match item {
+            Selection::Field(field) => collect_spreads(&field.selection_set, spreads),
+            Selection::InlineFragment(frag) => collect_spreads(&frag.selection_set, spreads),
+            Selection::FragmentSpread(spread) => spreads.push(spread),
+        }
+    }
+}
+
+/// Fragments must be used by an operation, directly or through other
+/// fragments
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoUnusedFragments;
+
+impl Rule for NoUnusedFragments {
+    fn name(&self) -> &'static str {
+        "NoUnusedFragments"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let doc = ctx.document();
+        let used = doc
+            .operations()
+            .flat_map(|op| used_fragments(doc, op).indices)
+            .collect::<HashSet<_>>();
+        for (idx, def) in doc.definitions.iter().enumerate() {
+            if let Definition::Fragment(frag) = def {
+                if !used.contains(&idx) {
+                    errors.push(ValidationError {
+                        rule: "NoUnusedFragments",
+                        message: format!("Fragment {:?} is never used.", frag.name),
+                        positions: vec![frag.position],
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FragmentsOnCompositeTypes, KnownTypeNames, PossibleFragmentSpreads};
+    use super::{KnownFragmentNames, NoFragmentCycles, NoUnusedFragments, UniqueFragmentNames};
+    use crate::query::validation::tests::{check, check_document};
+
+    #[test]
+    fn known_type_names() {
+        assert!(check(
+            KnownTypeNames,
+            "query($id: ID, $c: [DogCommand!]) { dog { ... on Pet { name } } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                KnownTypeNames,
+                "query($a: Unknown) { dog { ... on Bad { name } } } fragment F on Worse { name }"
+            ),
+            vec![
+                r#"1:7: Unknown type "Unknown"."#,
+                r#"1:32: Unknown type "Bad"."#,
+                r#"1:52: Unknown type "Worse"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn fragments_on_composite_types() {
+        assert!(check(
+            FragmentsOnCompositeTypes,
+            "{ dog { ... { name } } } fragment F on CatOrDog { __typename }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                FragmentsOnCompositeTypes,
+                "{ dog { ... on Boolean { x } } } fragment F on DogCommand { x }"
+            ),
+            vec![
+                r#"1:13: Fragment cannot condition on non composite type "Boolean"."#,
+                r#"1:34: Fragment "F" cannot condition on non composite type "DogCommand"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn possible_fragment_spreads() {
+        assert!(check(
+            PossibleFragmentSpreads,
+            "{ pet { ... on Dog { name } ...C } catOrDog { ... on Pet { name } } } fragment C on \
+             Canine { name }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                PossibleFragmentSpreads,
+                "{ dog { ... on Cat { name } ...H } } fragment H on DogOrHuman { __typename } \
+                 fragment X on Human { ...H ... on Pet { name } }"
+            ),
+            vec![
+                "1:13: Fragment cannot be spread here as objects of type \"Dog\" can never be of \
+                 type \"Cat\".",
+                "1:109: Fragment cannot be spread here as objects of type \"Human\" can never be \
+                 of type \"Pet\".",
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_fragment_names() {
+        assert!(check_document(
+            UniqueFragmentNames,
+            "{ ...A ...B } fragment A on T { a } fragment B on T { a }"
+        )
+        .is_empty());
+        assert_eq!(
+            check_document(
+                UniqueFragmentNames,
+                "{ ...A } fragment A on T { a }\nfragment A on T { b }"
+            ),
+            vec![r#"1:10, 2:1: There can be only one fragment named "A"."#]
+        );
+    }
+
+    #[test]
+    fn known_fragment_names() {
+        assert_eq!(
+            check_document(
+                KnownFragmentNames,
+                "{ a { ...A ...B } } fragment A on T { ... { ...C } }"
+            ),
+            vec![
+                r#"1:15: Unknown fragment "B"."#,
+                r#"1:48: Unknown fragment "C"."#
+            ]
+        );
+    }
+
+    #[test]
+    fn no_fragment_cycles() {
+        assert!(check_document(
+            NoFragmentCycles,
+            "fragment A on T { ...B ...B } fragment B on T { a }"
+        )
+        .is_empty());
+        assert_eq!(
+            check_document(
+                NoFragmentCycles,
+                "fragment A on T { ...A }\n\
+                 fragment B on T { a { ...C } }\n\
+                 fragment C on T { ... on T { ...D } }\n\
+                 fragment D on T { ...B ...A }"
+            ),
+            vec![
+                r#"1:22: Cannot spread fragment "A" within itself."#,
+                r#"2:26, 3:33, 4:22: Cannot spread fragment "B" within itself via "C", "D"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn no_unused_fragments() {
+        assert_eq!(
+            check_document(
+                NoUnusedFragments,
+                "{ ...A } fragment A on T { ...B } fragment B on T { a }\n\
+                 fragment C on T { ...D } fragment D on T { ...C }"
+            ),
+            vec![
+                r#"2:1: Fragment "C" is never used."#,
+                r#"2:26: Fragment "D" is never used."#
+            ]
+        );
+    }
+}
@@ -0,0 +1,428 @@
+//! Validation of executable documents
+//!
+//! Implements the rules of the "Validation" section of the GraphQL
+//! specification. Each rule is a [`Rule`], and a [`Validator`] runs a set
+//! of them, so rules can be turned off or custom ones added:
+//!
+//! ```rust
+//! use graphql_parser::{parse_query, parse_schema};
+//! use graphql_parser::query::validation::Validator;
+//! use graphql_parser::schema::Schema;
+//!
+//! let doc = parse_schema("type Query { hello: String }").unwrap();
+//! let schema = Schema::new(&doc);
+//! let query = parse_query("{ hello world }").unwrap();
+//! let errors = Validator::new().validate(&schema, &query).unwrap_err();
+//! assert_eq!(errors[0].message, r#"Cannot query field "world" on type "Query"."#);
+//!
+//! let mut validator = Validator::new();
+//! validator.disable_rule("FieldsOnCorrectType");
+//! assert!(validator.validate(&schema, &query).is_ok());
+//! ```
+//!
+//! Rules which don't need a schema may run on the document alone, with
+//! [`Validator::executable`] or [`validate_document`]:
+//!
+//! ```rust
+//! use graphql_parser::parse_query;
+//! use graphql_parser::query::validation::validate_document;
+//!
+//! let query = parse_query("query($id: ID) { ...User }").unwrap();
+//! let errors = validate_document(&query).unwrap_err();
+//! assert_eq!(errors[0].message, r#"Unknown fragment "User"."#);
+//! assert_eq!(errors[1].message, r#"Variable "$id" is never used."#);
+//! ```
+use std::collections::HashMap;
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::position::{Pos, Positions};
+use crate::query::ast::*;
+use crate::schema;
+use crate::schema::Schema;
+
+mod arguments;
+mod directives;
+mod fields;
+mod fragments;
+mod operations;
+mod overlap;
+mod variables;
+mod walk;
+
+pub(crate) use self::arguments::value_errors;
+pub use self::arguments::{
+    KnownArgumentNames, ProvidedRequiredArguments, UniqueArgumentNames, ValuesOfCorrectType,
+};
+pub use self::directives::{KnownDirectives, UniqueDirectivesPerLocation};
+pub use self::fields::{FieldsOnCorrectType, ScalarLeafs};
+pub use self::fragments::{
+    FragmentsOnCompositeTypes, KnownFragmentNames, KnownTypeNames, NoFragmentCycles,
+    NoUnusedFragments, PossibleFragmentSpreads, UniqueFragmentNames,
+};
+pub use self::operations::{
+    LoneAnonymousOperation, SingleFieldSubscriptions, UniqueOperationNames,
+};
+pub use self::overlap::OverlappingFieldsCanBeMerged;
+pub use self::variables::{
+    NoUndefinedVariables, NoUnusedVariables, UniqueVariableNames, VariablesAreInputTypes,
+    VariablesInAllowedPosition,
+};
+
+/// A validation error
+///
+/// Messages follow the ones of the reference implementation.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{}{}", Positions(positions), message)]
+pub struct ValidationError {
+    /// Name of the rule which produced the error
+    pub rule: &'static str,
+    pub message: String,
+    /// Positions of the nodes involved, in the query document
+    pub positions: Vec<Pos>,
+}
+
+/// A validation rule
+pub trait Rule {
+    /// Name of the rule, used to disable it and to tag errors
+    fn name(&self) -> &'static str;
+    /// Checks the document, pushing errors found
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>);
+}
+
+/// Document and schema being validated
+pub struct Context<'b, 'a> {
+    document: &'b Document<'a>,
+    schema: Option<&'b Schema<'b, 'a>>,
+    fragments: HashMap<&'a str, &'b FragmentDefinition<'a>>,
+}
+
+impl<'b, 'a> fmt::Debug for Context<'b, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("document", &self.document)
+            .field("has_schema", &self.schema.is_some())
+            .finish()
+    }
+}
+
+impl<'b, 'a> Context<'b, 'a> {
+    fn new(document: &'b Document<'a>, schema: Option<&'b Schema<'b, 'a>>) -> Self {
+        let mut fragments = HashMap::new();
+        for frag in document.fragments() {
+            fragments.entry(frag.name).or_insert(frag);
+        }
+        Context {
+            document,
+            schema,
+            fragments,
+        }
+    }
+
+    pub fn document(&self) -> &'b Document<'a> {
+        self.document
+    }
+
+    /// Returns the first fragment definition with the name
+    pub fn fragment(&self, name: &str) -> Option<&'b FragmentDefinition<'a>> {
+        self.fragments.get(name).copied()
+    }
+
+    /// Returns `false` when validating without a schema
+    pub fn has_schema(&self) -> bool {
+        self.schema.is_some()
+    }
+
+    /// Returns the type definition, including built-in scalars
+    pub fn get_type(&self, name: &str) -> Option<&'b schema::TypeDefinition<'a>> {
+        self.schema?.get_type(name)
+    }
+
+    /// Returns the directive definition, including built-in directives
+    pub fn get_directive(&self, name: &str) -> Option<&'b schema::DirectiveDefinition<'a>> {
+        self.schema?.get_directive(name)
+    }
+
+    /// Returns the field of an object or interface type
+    pub fn field(&self, type_name: &str, field_name: &str) -> Option<&'b schema::Field<'a>> {
+        self.schema?.field(type_name, field_name)
+    }
+
+    /// Returns the root type of operations of the kind
+    pub fn root_type(&self, kind: OperationKind) -> Option<&'a str> {
+        let schema = self.schema?;
+        match kind {
+            OperationKind::Query => schema.query_type(),
+            OperationKind::Mutation => schema.mutation_type(),
+            OperationKind::Subscription => schema.subscription_type(),
+        }
+    }
+
+    /// Returns the schema, `None` when validating without a schema
+    pub fn schema(&self) -> Option<&'b Schema<'b, 'a>> {
+        self.schema
+    }
+}
+
+/// Runs a set of validation rules
+pub struct Validator {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|rule| rule.name()))
+            .finish()
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Validator {
+        Validator::new()
+    }
+}
+
+impl Validator {
+    /// Returns a validator with all rules of the specification
+    pub fn new() -> Validator {
+        let mut validator = Validator::executable();
+        validator
+            .add_rule(FieldsOnCorrectType)
+            .add_rule(FragmentsOnCompositeTypes)
+            .add_rule(KnownTypeNames)
+            .add_rule(PossibleFragmentSpreads)
+            .add_rule(ScalarLeafs)
+            .add_rule(KnownArgumentNames)
+            .add_rule(ProvidedRequiredArguments)
+            .add_rule(ValuesOfCorrectType)
+            .add_rule(KnownDirectives)
+            .add_rule(UniqueDirectivesPerLocation)
+            .add_rule(VariablesAreInputTypes)
+            .add_rule(VariablesInAllowedPosition)
+            .add_rule(OverlappingFieldsCanBeMerged);
+        validator
+    }
+
+    /// Returns a validator with the rules which don't need a schema
+    pub fn executable() -> Validator {
+        let mut validator = Validator::empty();
+        validator
+            .add_rule(UniqueOperationNames)
+            .add_rule(LoneAnonymousOperation)
+            .add_rule(SingleFieldSubscriptions)
+            .add_rule(UniqueFragmentNames)
+            .add_rule(KnownFragmentNames)
+            .add_rule(NoUnusedFragments)
+            .add_rule(NoFragmentCycles)
+            .add_rule(UniqueArgumentNames)
+            .add_rule(UniqueVariableNames)
+            .add_rule(NoUndefinedVariables)
+            .add_rule(NoUnusedVariables);
+        validator
+    }
+
+    /// Returns a validator without rules
+    pub fn empty() -> Validator {
+        Validator { rules: Vec::new() }
+    }
+
+    pub fn add_rule<R: Rule + 'static>(&mut self, rule: R) -> &mut Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Removes rules with the name
+    pub fn disable_rule(&mut self, name: &str) -> &mut Self {
+        self.rules.retain(|rule| rule.name() != name);
+        self
+    }
+
+    /// Iterates over names of the rules
+    pub fn rules(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.rules.iter().map(|rule| rule.name())
+    }
+
+    /// Validates the document against the schema
+    ///
+    /// Errors of all rules are returned, in the order of the rules.
+    pub fn validate<'a>(
+        &self,
+        schema: &Schema<'_, 'a>,
+        doc: &Document<'a>,
+    ) -> Result<(), Vec<ValidationError>> {
+        self.run(&Context::new(doc, Some(schema)))
+    }
+
+    /// Validates the document without a schema
+    ///
+    /// Rules needing a schema report nothing.
+    pub fn validate_document(&self, doc: &Document) -> Result<(), Vec<ValidationError>> {
+        self.run(&Context::new(doc, None))
+    }
+
+    fn run(&self, ctx: &Context) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        for rule in &self.rules {
+            rule.validate(ctx, &mut errors);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Validates the document against the schema with all rules
+pub fn validate<'a>(
+    schema: &Schema<'_, 'a>,
+    doc: &Document<'a>,
+) -> Result<(), Vec<ValidationError>> {
+    Validator::new().validate(schema, doc)
+}
+
+/// Validates the document with the rules which don't need a schema
+pub fn validate_document(doc: &Document) -> Result<(), Vec<ValidationError>> {
+    Validator::executable().validate_document(doc)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{Rule, ValidationError, Validator};
+    use crate::query::parse_query;
+    use crate::schema::{parse_schema, Schema};
+
+    pub const SCHEMA: &str = r#"
+        schema { query: QueryRoot mutation: MutationRoot }
+        directive @onField on FIELD
+        directive @repeat repeatable on FIELD | FRAGMENT_SPREAD
+        interface Pet { name(surname: Boolean): String }
+        interface Canine implements Pet { name(surname: Boolean): String }
+        enum DogCommand { SIT DOWN HEEL }
+        type Dog implements Pet & Canine {
+            name(surname: Boolean): String
+            nickname: String
+            barkVolume: Int
+            barks: Boolean
+            doesKnowCommand(dogCommand: DogCommand): Boolean
+            isHouseTrained(atOtherHomes: Boolean = true): Boolean
+            isAtLocation(x: Int, y: Int): Boolean
+        }
+        type Cat implements Pet {
+            name(surname: Boolean): String
+            nickname: String
+            meows: Boolean
+            meowVolume: Int
+            furColor: [String]
+        }
+        union CatOrDog = Cat | Dog
+        type Human { name(surname: Boolean): String pets: [Pet] relatives: [Human] iq: Int }
+        union DogOrHuman = Dog | Human
+        input ComplexInput {
+            requiredField: Boolean!
+            nonNullField: Boolean! = false
+            intField: Int
+            stringListField: [String]
+        }
+        type ComplicatedArgs {
+            intArgField(intArg: Int): String
+            nonNullIntArgField(nonNullIntArg: Int!): String
+            stringListArgField(stringListArg: [String]): String
+            stringListNonNullArgField(stringListNonNullArg: [String!]): String
+            complexArgField(complexArg: ComplexInput): String
+            enumArgField(enumArg: DogCommand): String
+            multipleReqs(req1: Int!, req2: Int!): String
+            multipleOpts(opt1: Int = 0, opt2: Int = 0): String
+        }
+        type QueryRoot {
+            human(id: ID): Human
+            dog: Dog
+            cat: Cat
+            pet: Pet
+            catOrDog: CatOrDog
+            dogOrHuman: DogOrHuman
+            complicatedArgs: ComplicatedArgs
+        }
+        type MutationRoot { addPet(name: String!): Pet }
+    "#;
+
+    /// Returns messages of errors produced by the rule
+    pub fn check<R: Rule + 'static>(rule: R, query: &str) -> Vec<String> {
+        let schema = parse_schema(SCHEMA).unwrap();
+        let doc = parse_query(query).unwrap();
+        let mut validator = Validator::empty();
+        validator.add_rule(rule);
+        match validator.validate(&Schema::new(&schema), &doc) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    /// Returns messages of errors produced by the rule without a schema
+    pub fn check_document<R: Rule + 'static>(rule: R, query: &str) -> Vec<String> {
+        let doc = parse_query(query).unwrap();
+        let mut validator = Validator::empty();
+        validator.add_rule(rule);
+        match validator.validate_document(&doc) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn validator() {
+        let schema = parse_schema(SCHEMA).unwrap();
+        let schema = Schema::new(&schema);
+        let doc = parse_query(
+            "query Q($id: ID) { human(id: $id) { name pets { name ... on Dog { barks } } } }",
+        )
+        .unwrap();
+        assert_eq!(Validator::new().validate(&schema, &doc), Ok(()));
+        let doc = parse_query("{ dog { unknown } cat }").unwrap();
+        let errors = Validator::new().validate(&schema, &doc).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError {
+                    rule: "FieldsOnCorrectType",
+                    message: r#"Cannot query field "unknown" on type "Dog"."#.into(),
+                    positions: vec![crate::Pos { line: 1, column: 9 }],
+                },
+                ValidationError {
+                    rule: "ScalarLeafs",
+                    message: "Field \"cat\" of type \"Cat\" must have a selection of subfields. \
+                              Did you mean \"cat { ... }\"?"
+                        .into(),
+                    positions: vec![crate::Pos {
+                        line: 1,
+                        column: 19
+                    }],
+                },
+            ]
+        );
+        let mut validator = Validator::new();
+        validator.disable_rule("ScalarLeafs");
+        assert_eq!(validator.validate(&schema, &doc).unwrap_err().len(), 1);
+        assert!(!validator.rules().any(|name| name == "ScalarLeafs"));
+    }
+
+    #[test]
+    fn without_schema() {
+        let doc =
+            parse_query("query($a: Int) { dog(x: $a) { unknown ...F } } fragment F on Dog { a }")
+                .unwrap();
+        assert_eq!(Validator::new().validate_document(&doc), Ok(()));
+        let doc = parse_query("query Q { a(x: $b) } query Q { ...F }").unwrap();
+        let errors = Validator::executable().validate_document(&doc).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| e.rule).collect::<Vec<_>>(),
+            vec![
+                "UniqueOperationNames",
+                "KnownFragmentNames",
+                "NoUndefinedVariables"
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::position::Pos;
+use crate::query::ast::*;
+
+use super::{Context, Rule, ValidationError};
+
+/// Named operations must have unique names
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniqueOperationNames;
+
+impl Rule for UniqueOperationNames {
+    fn name(&self) -> &'static str {
+        "UniqueOperationNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let ops = ctx.document().operations().collect::<Vec<_>>();
+        for (idx, op) in ops.iter().enumerate() {
+            let name = match op.name() {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(first) = ops[..idx].iter().find(|other| other.name() == Some(name)) {
+                errors.push(ValidationError {
+                    rule: "UniqueOperationNames",
+                    message: format!("There can be only one operation named {:?}.", name),
+                    positions: vec![first.position(), op.position()],
+                });
+            }
+        }
+    }
+}
+
+/// An anonymous operation must be the only operation of the document
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoneAnonymousOperation;
+
+impl Rule for LoneAnonymousOperation {
+    fn name(&self) -> &'static str {
+        "LoneAnonymousOperation"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        if ctx.document().operations().count() < 2 {
+            return;
+        }
+        for op in ctx.document().operations().filter(|op| op.name().is_none()) {
+            errors.push(ValidationError {
+                rule: "LoneAnonymousOperation",
+                message: "This anonymous operation must be the only defined operation.".into(),
+                positions: vec![op.position()],
+            });
+        }
+    }
+}
+
+/// Subscriptions must select exactly one root field, which isn't an
+/// introspection field
+///
+/// Directives are not evaluated, so fields skipped with `@skip` or
+/// `@include` still count.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SingleFieldSubscriptions;
+
+impl Rule for SingleFieldSubscriptions {
+    fn name(&self) -> &'static str {
+        "SingleFieldSubscriptions"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        for op in ctx.document().operations() {
+            if op.kind() != OperationKind::Subscription {
+                continue;
+            }
+            let subject = match op.name() {
+                Some(name) => format!("Subscription {:?}", name),
+                None => "Anonymous Subscription".into(),
+            };
+            let mut fields = Vec::new();
+            root_fields(ctx, op.selection_set(), &mut Vec::new(), &mut fields);
+            let mut keys = Vec::new();
+            let mut extra = Vec::new();
+            for &(key, _, position) in &fields {
+                if keys.is_empty() || keys.contains(&key) {
+                    keys.push(key);
+                } else {
+                    extra.push(position);
+                }
+            }
+            let introspection = fields
+                .iter()
+                .filter(|(_, _, position)| !extra.contains(position))
+                .filter(|(_, name, _)| name.starts_with("__"))
+                .map(|&(_, _, position)| position)
+                .collect::<Vec<_>>();
+            if !extra.is_empty() {
+                errors.push(ValidationError {
+                    rule: "SingleFieldSubscriptions",
+                    message: format!("{} must select only one top level field.", subject),
+                    positions: extra,
+                });
+            }
+            if !introspection.is_empty() {
+                errors.push(ValidationError {
+                    rule: "SingleFieldSubscriptions",
+                    message: format!(
+                        "{} must not select an introspection top level field.",
+                        subject
+                    ),
+                    positions: introspection,
+                });
+            }
+        }
+    }
+}
+
+/// Collects response keys, names and positions of the fields of the
+/// selection set, following fragments
+fn root_fields<'b, 'a>(
+    ctx: &Context<'b, 'a>,
+    set: &'b SelectionSet<'a>,
+    visited: &mut Vec<&'a str>,
+    fields: &mut Vec<(&'a str, &'a str, Pos)>,
+) {
+    for item in &set.items {
+        match item {
+            Selection::Field(field) => {
+                fields.push((
+                    field.alias.unwrap_or(field.name),
+                    field.name,
+                    field.position,
+                ));
+            }
+            Selection::InlineFragment(frag) => {
+                root_fields(ctx, &frag.selection_set, visited, fields);
+            }
+            Selection::FragmentSpread(spread) => {
+                if visited.contains(&spread.fragment_name) {
+                    continue;
+                }
+                visited.push(spread.fragment_name);
+                if let Some(frag) = ctx.fragment(spread.fragment_name) {
+                    root_fields(ctx, &frag.selection_set, visited, fields);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LoneAnonymousOperation, SingleFieldSubscriptions, UniqueOperationNames};
+    use crate::query::validation::tests::check_document;
+
+    #[test]
+    fn unique_operation_names() {
+        assert!(
+            check_document(UniqueOperationNames, "query A { a } query B { a } { a }").is_empty()
+        );
+        assert_eq!(
+            check_document(UniqueOperationNames, "query A { a } mutation A { a }"),
+            vec![r#"1:1, 1:15: There can be only one operation named "A"."#]
+        );
+    }
+
+    #[test]
+    fn lone_anonymous_operation() {
+        assert!(check_document(LoneAnonymousOperation, "{ a } fragment F on T { a }").is_empty());
+        assert_eq!(
+            check_document(LoneAnonymousOperation, "{ a } query A { a }"),
+            vec![r#"1:1: This anonymous operation must be the only defined operation."#]
+        );
+    }
+
+    #[test]
+    fn single_field_subscriptions() {
+        assert!(check_document(
+            SingleFieldSubscriptions,
+            "subscription S { a a ...F } fragment F on Subscription { a } { a b } \
+             subscription T { __typename: a }"
+        )
+        .is_empty());
+        assert_eq!(
+            check_document(
+                SingleFieldSubscriptions,
+                "subscription { a ... on S { b } } subscription S { __typename }\n\
+                 subscription T { t: __typename }"
+            ),
+            vec![
+                r#"1:29: Anonymous Subscription must select only one top level field."#,
+                r#"1:52: Subscription "S" must not select an introspection top level field."#,
+                r#"2:18: Subscription "T" must not select an introspection top level field."#,
+            ]
+        );
+    }
+}
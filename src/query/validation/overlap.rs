@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::schema;
+use crate::schema::index::named_type;
+
+use super::walk::{walk, TypedVisitor};
+use super::{Context, Rule, ValidationError};
+
+/// Fields with the same response key must be identical, so they can be
+/// merged in the response
+///
+/// Fields on distinct object types may differ in name and arguments, as
+/// they are never selected together, but must return compatible types.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverlappingFieldsCanBeMerged;
+
+impl Rule for OverlappingFieldsCanBeMerged {
+    fn name(&self) -> &'static str {
+        "OverlappingFieldsCanBeMerged"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        struct Visitor<'c, 'b, 'a> {
+            ctx: &'c Context<'b, 'a>,
+            comparer: Comparer<'c, 'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+            reported: HashSet<(Pos, Pos)>,
+        }
+        impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+            fn enter_selection_set(&mut self, set: &'b SelectionSet<'a>, parent: Option<&'a str>) {
+                let mut fields = Vec::new();
+                collect_fields(self.ctx, set, parent, &mut Vec::new(), &mut fields);
+                for (idx, a) in fields.iter().enumerate() {
+                    for b in fields[idx + 1..].iter().filter(|b| b.key == a.key) {
+                        let positions = (a.field.position, b.field.position);
+                        if self.reported.contains(&positions) {
+                            continue;
+                        }
+                        if let Some(reason) = self.comparer.conflict(a, b, false, &[]) {
+                            self.reported.insert(positions);
+                            self.errors.push(ValidationError {
+                                rule: "OverlappingFieldsCanBeMerged",
+                                message: format!(
+                                    "Fields {:?} conflict because {}. Use different aliases \
+                                     on the fields to fetch both if this was intentional.",
+                                    a.key, reason
+                                ),
+                                positions: vec![positions.0, positions.1],
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        walk(
+            ctx,
+            &mut Visitor {
+                ctx,
+                comparer: Comparer {
+                    ctx,
+                    compared: HashMap::new(),
+                },
+                errors,
+                reported: HashSet::new(),
+            },
+        );
+    }
+}
+
+struct FieldInfo<'b, 'a> {
+    key: &'a str,
+    parent: Option<&'a str>,
+    field: &'b Field<'a>,
+    def: Option<&'b schema::Field<'a>>,
+}
+
+/// Collects fields of the selection set, including the ones of inline
+/// fragments and fragment spreads
+fn collect_fields<'b, 'a>(
+    ctx: &Context<'b, 'a>,
+    set: &'b SelectionSet<'a>,
+    parent: Option<&'a str>,
+    visited: &mut Vec<&'a str>,
+    fields: &mut Vec<FieldInfo<'b, 'a>>,
+) {
+    let known = |name: &'a str| ctx.get_type(name).map(|_| name);
+    for item in &set.items {
+        match item {
+            Selection::Field(field) => fields.push(FieldInfo {
+                key: field.alias.unwrap_or(field.name),
+                parent,
+                field,
+                def: parent.and_then(|parent| ctx.field(parent, field.name)),
+            }),
+            Selection::InlineFragment(frag) => {
+                let parent = match frag.type_condition {
+                    Some(TypeCondition::On(name)) => known(name),
+                    None => parent,
+                };
+                collect_fields(ctx, &frag.selection_set, parent, visited, fields);
+            }
+            Selection::FragmentSpread(spread) => {
+                if visited.contains(&spread.fragment_name) {
+                    continue;
+                }
+                visited.push(spread.fragment_name);
+                if let Some(frag) = ctx.fragment(spread.fragment_name) {
+                    let TypeCondition::On(name) = frag.type_condition;
+                    collect_fields(ctx, &frag.selection_set, known(name), visited, fields);
+                }
+            }
+        }
+    }
+}
+
+/// Fields compared, with parents of the fields and whether they are
+/// exclusive
+type Pair<'a> = (
+    *const Field<'a>,
+    *const Field<'a>,
+    Option<&'a str>,
+    Option<&'a str>,
+    bool,
+);
+
+/// Compares fields, remembering the result for each pair so that fields
+/// reached through many paths, like the ones of fragments spread in many
+/// places, are compared once
+struct Comparer<'c, 'b, 'a> {
+    ctx: &'c Context<'b, 'a>,
+    compared: HashMap<Pair<'a>, Option<String>>,
+}
+
+impl<'c, 'b, 'a> Comparer<'c, 'b, 'a> {
+    /// Returns the reason why the fields can't be merged
+    ///
+    /// `exclusive` is true when parents of the fields are distinct object
+    /// types, so the fields are never selected on the same object.
+    /// Fragments in `path` are already spread by the fields being compared
+    /// and aren't followed again, which stops at fragment cycles.
+    fn conflict(
+        &mut self,
+        a: &FieldInfo<'b, 'a>,
+        b: &FieldInfo<'b, 'a>,
+        exclusive: bool,
+        path: &[&'a str],
+    ) -> Option<String> {
+        if std::ptr::eq(a.field, b.field) && a.parent == b.parent {
+            return None;
+        }
+        let pair: Pair = (a.field, b.field, a.parent, b.parent, exclusive);
+        if let Some(reason) = self.compared.get(&pair) {
+            return reason.clone();
+        }
+        let reason = self.compare(a, b, exclusive, path);
+        self.compared.insert(pair, reason.clone());
+        reason
+    }
+
+    fn compare(
+        &mut self,
+        a: &FieldInfo<'b, 'a>,
+        b: &FieldInfo<'b, 'a>,
+        exclusive: bool,
+        path: &[&'a str],
+    ) -> Option<String> {
+        let ctx = self.ctx;
+        let is_object = |name: Option<&str>| {
+            matches!(
+                name.and_then(|name| ctx.get_type(name)),
+                Some(schema::TypeDefinition::Object(_))
+            )
+        };
+        let exclusive =
+            exclusive || (a.parent != b.parent && is_object(a.parent) && is_object(b.parent));
+        if !exclusive {
+            if a.field.name != b.field.name {
+                return Some(format!(
+                    "{:?} and {:?} are different fields",
+                    a.field.name, b.field.name
+                ));
+            }
+            if !same_arguments(&a.field.arguments, &b.field.arguments) {
+                return Some("they have differing arguments".into());
+            }
+        }
+        if let (Some(def_a), Some(def_b)) = (a.def, b.def) {
+            if types_conflict(ctx, &def_a.field_type, &def_b.field_type) {
+                return Some(format!(
+                    "they return conflicting types \"{}\" and \"{}\"",
+                    def_a.field_type, def_b.field_type
+                ));
+            }
+        }
+        if a.field.selection_set.items.is_empty() || b.field.selection_set.items.is_empty() {
+            return None;
+        }
+        let inner = |info: &FieldInfo<'b, 'a>| {
+            info.def
+                .map(|def| named_type(&def.field_type))
+                .filter(|name| ctx.get_type(name).is_some())
+        };
+        let mut fields_a = Vec::new();
+        let mut visited = path.to_vec();
+        collect_fields(
+            ctx,
+            &a.field.selection_set,
+            inner(a),
+            &mut visited,
+            &mut fields_a,
+        );
+        let mut fields_b = Vec::new();
+        let mut visited_b = path.to_vec();
+        collect_fields(
+            ctx,
+            &b.field.selection_set,
+            inner(b),
+            &mut visited_b,
+            &mut fields_b,
+        );
+        visited.extend(visited_b.drain(path.len()..));
+        for x in &fields_a {
+            for y in fields_b.iter().filter(|y| y.key == x.key) {
+                if let Some(reason) = self.conflict(x, y, exclusive, &visited) {
+                    return Some(format!("subfields {:?} conflict because {}", x.key, reason));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn same_arguments(a: &[(&str, Value)], b: &[(&str, Value)]) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|(name, value)| {
+            b.iter()
+                .any(|(other, other_value)| name == other && value == other_value)
+        })
+}
+
+/// Returns true if the types have different shapes or leaf types
+fn types_conflict(ctx: &Context, a: &Type, b: &Type) -> bool {
+    match (a, b) {
+        (Type::NonNullType(a), Type::NonNullType(b)) | (Type::ListType(a), Type::ListType(b)) => {
+            types_conflict(ctx, a, b)
+        }
+        (Type::NamedType(a), Type::NamedType(b)) => {
+            let is_leaf = |name: &str| {
+                matches!(
+                    ctx.get_type(name),
+                    Some(schema::TypeDefinition::Scalar(_)) | Some(schema::TypeDefinition::Enum(_))
+                )
+            };
+            (is_leaf(a) || is_leaf(b)) && a != b
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OverlappingFieldsCanBeMerged;
+    use crate::query::validation::tests::check;
+
+    #[test]
+    fn mergeable() {
+        assert!(check(
+            OverlappingFieldsCanBeMerged,
+            "{ dog { name name otherName: name ...F doesKnowCommand(dogCommand: SIT) } \
+               pet { ... on Dog { x: barkVolume } ... on Cat { x: meowVolume } } } \
+             fragment F on Dog { name doesKnowCommand(dogCommand: SIT) }"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn conflicts() {
+        assert_eq!(
+            check(
+                OverlappingFieldsCanBeMerged,
+                "{ dog { name: nickname name doesKnowCommand(dogCommand: SIT) ...F } }\n\
+                 fragment F on Dog { doesKnowCommand(dogCommand: HEEL) }"
+            ),
+            vec![
+                "1:9, 1:24: Fields \"name\" conflict because \"nickname\" and \"name\" are \
+                 different fields. Use different aliases on the fields to fetch both if this was \
+                 intentional.",
+                "1:29, 2:21: Fields \"doesKnowCommand\" conflict because they have differing \
+                 arguments. Use different aliases on the fields to fetch both if this was \
+                 intentional.",
+            ]
+        );
+        assert_eq!(
+            check(
+                OverlappingFieldsCanBeMerged,
+                "{ pet { ... on Dog { x: barks } ... on Cat { x: meowVolume } } \
+                   dog { owner: name } dog { owner: barks } }"
+            ),
+            vec![
+                "1:64, 1:84: Fields \"dog\" conflict because subfields \"owner\" conflict because \
+                 \"name\" and \"barks\" are different fields. Use different aliases on the fields \
+                 to fetch both if this was intentional.",
+                "1:22, 1:46: Fields \"x\" conflict because they return conflicting types \
+                 \"Boolean\" and \"Int\". Use different aliases on the fields to fetch both if \
+                 this was intentional.",
+            ]
+        );
+    }
+
+    #[test]
+    fn fragment_cycles() {
+        assert!(check(
+            OverlappingFieldsCanBeMerged,
+            "{ human { ...F } human { ...F } } fragment F on Human { relatives { ...F } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                OverlappingFieldsCanBeMerged,
+                "{ human { ...F } human { ...G } }\n\
+                 fragment F on Human { relatives { ...G x: name } }\n\
+                 fragment G on Human { relatives { ...F x: iq } }"
+            ),
+            vec![
+                "1:3, 1:18: Fields \"human\" conflict because subfields \"relatives\" conflict \
+                 because subfields \"x\" conflict because \"name\" and \"iq\" are different \
+                 fields. Use different aliases on the fields to fetch both if this was \
+                 intentional.",
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_fragments() {
+        let mut query = "{ human { ...F0 } }".to_string();
+        for idx in 0..30 {
+            query.push_str(&format!(
+                " fragment F{} on Human {{ relatives {{ ...F{} }} relatives {{ ...F{} }} }}",
+                idx,
+                idx + 1,
+                idx + 1
+            ));
+        }
+        query.push_str(" fragment F30 on Human { name }");
+        assert!(check(OverlappingFieldsCanBeMerged, &query).is_empty());
+    }
+}
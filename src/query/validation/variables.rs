@@ -0,0 +1,509 @@
+use crate::position::Pos;
+use crate::query::ast::*;
+use crate::query::fragments::used_fragments;
+use crate::schema::index::named_type;
+use crate::schema::Schema;
+use crate::schema::{self, DirectiveLocation, TypeDefinition};
+
+use super::walk::{walk, TypedVisitor};
+use super::{Context, Rule, ValidationError};
+
+/// Variables must have input types
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VariablesAreInputTypes;
+
+impl Rule for VariablesAreInputTypes {
+    fn name(&self) -> &'static str {
+        "VariablesAreInputTypes"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let schema = match ctx.schema() {
+            Some(schema) => schema,
+            None => return,
+        };
+        for op in ctx.document().operations() {
+            for var in op.variable_definitions() {
+                let name = named_type(&var.var_type);
+                if schema.get_type(name).is_some() && !schema.is_input(name) {
+                    errors.push(ValidationError {
+                        rule: "VariablesAreInputTypes",
+                        message: format!(
+                            "Variable \"${}\" cannot be non-input type \"{}\".",
+                            var.name, var.var_type
+                        ),
+                        positions: vec![var.position],
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Variables must be compatible with the positions they are used in
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VariablesInAllowedPosition;
+
+impl Rule for VariablesInAllowedPosition {
+    fn name(&self) -> &'static str {
+        "VariablesInAllowedPosition"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let schema = match ctx.schema() {
+            Some(schema) => schema,
+            None => return,
+        };
+        let usages = typed_usages(ctx);
+        let doc = ctx.document();
+        for (idx, def) in doc.definitions.iter().enumerate() {
+            let op = match def {
+                Definition::Operation(op) => op,
+                Definition::Fragment(_) => continue,
+            };
+            let fragments = used_fragments(doc, op).indices;
+            let op_usages = Some(&idx)
+                .into_iter()
+                .chain(&fragments)
+                .flat_map(|&idx| &usages[idx]);
+            for usage in op_usages {
+                let var = match op
+                    .variable_definitions()
+                    .iter()
+                    .find(|var| var.name == usage.name)
+                {
+                    Some(var) => var,
+                    None => continue,
+                };
+                if !allowed(schema, var, usage) {
+                    errors.push(ValidationError {
+                        rule: "VariablesInAllowedPosition",
+                        message: format!(
+                            "Variable \"${}\" of type \"{}\" used in position \
+                             expecting type \"{}\".",
+                            var.name, var.var_type, usage.expected
+                        ),
+                        positions: vec![var.position, usage.position],
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Variables of an operation must have unique names
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniqueVariableNames;
+
+impl Rule for UniqueVariableNames {
+    fn name(&self) -> &'static str {
+        "UniqueVariableNames"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        for op in ctx.document().operations() {
+            let vars = op.variable_definitions();
+            for (idx, var) in vars.iter().enumerate() {
+                if let Some(first) = vars[..idx].iter().find(|other| other.name == var.name) {
+                    errors.push(ValidationError {
+                        rule: "UniqueVariableNames",
+                        message: format!("There can be only one variable named \"${}\".", var.name),
+                        positions: vec![first.position, var.position],
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Variables used by an operation, directly or through fragments, must be
+/// defined by the operation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoUndefinedVariables;
+
+impl Rule for NoUndefinedVariables {
+    fn name(&self) -> &'static str {
+        "NoUndefinedVariables"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let usages = usages(ctx);
+        let doc = ctx.document();
+        for (idx, def) in doc.definitions.iter().enumerate() {
+            let op = match def {
+                Definition::Operation(op) => op,
+                Definition::Fragment(_) => continue,
+            };
+            let fragments = used_fragments(doc, op).indices;
+            let mut reported = Vec::new();
+            let op_usages = Some(&idx)
+                .into_iter()
+                .chain(&fragments)
+                .flat_map(|&idx| &usages[idx]);
+            for &(name, position) in op_usages {
+                let defined = op.variable_definitions().iter().any(|var| var.name == name);
+                if defined || reported.contains(&(name, position)) {
+                    continue;
+                }
+                reported.push((name, position));
+                let message = match op.name() {
+                    Some(op_name) => format!(
+                        "Variable \"${}\" is not defined by operation {:?}.",
+                        name, op_name
+                    ),
+                    None => format!("Variable \"${}\" is not defined.", name),
+                };
+                errors.push(ValidationError {
+                    rule: "NoUndefinedVariables",
+                    message,
+                    positions: vec![position, op.position()],
+                });
+            }
+        }
+    }
+}
+
+/// Variables defined by an operation must be used, directly or through
+/// fragments
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoUnusedVariables;
+
+impl Rule for NoUnusedVariables {
+    fn name(&self) -> &'static str {
+        "NoUnusedVariables"
+    }
+    fn validate<'b, 'a>(&self, ctx: &Context<'b, 'a>, errors: &mut Vec<ValidationError>) {
+        let usages = usages(ctx);
+        let doc = ctx.document();
+        for (idx, def) in doc.definitions.iter().enumerate() {
+            let op = match def {
+                Definition::Operation(op) => op,
+                Definition::Fragment(_) => continue,
+            };
+            let fragments = used_fragments(doc, op).indices;
+            let used = Some(&idx)
+                .into_iter()
+                .chain(&fragments)
+                .flat_map(|&idx| &usages[idx])
+                .map(|&(name, _)| name)
+                .collect::<Vec<_>>();
+            for var in op.variable_definitions() {
+                if used.contains(&var.name) {
+                    continue;
+                }
+                let message = match op.name() {
+                    Some(op_name) => format!(
+                        "Variable \"${}\" is never used in operation {:?}.",
+                        var.name, op_name
+                    ),
+                    None => format!("Variable \"${}\" is never used.", var.name),
+                };
+                errors.push(ValidationError {
+                    rule: "NoUnusedVariables",
+                    message,
+                    positions: vec![var.position],
+                });
+            }
+        }
+    }
+}
+
+/// Returns names of variables used by every definition of the document,
+/// with positions of the fields and directives they are used in
+fn usages<'b, 'a>(ctx: &Context<'b, 'a>) -> Vec<Vec<(&'a str, Pos)>> {
+    fn values<'a>(value: &Value<'a>, position: Pos, usages: &mut Vec<(&'a str, Pos)>) {
+        match value {
+            Value::Variable(name) => usages.push((name, position)),
+            Value::List(items) => {
+                for item in items {
+                    values(item, position, usages);
+                }
+            }
+            Value::Object(fields) => {
+                for value in fields.values() {
+                    values(value, position, usages);
+                }
+            }
+            _ => {}
+        }
+    }
+    struct Visitor<'a> {
+        usages: Vec<Vec<(&'a str, Pos)>>,
+    }
+    impl<'a> Visitor<'a> {
+        fn arguments(&mut self, args: &[(&'a str, Value<'a>)], position: Pos) {
+            let usages = self.usages.last_mut().expect("definition is entered");
+            for (_, value) in args {
+                values(value, position, usages);
+            }
+        }
+    }
+    impl<'b, 'a> TypedVisitor<'b, 'a> for Visitor<'a> {
+        fn enter_operation(&mut self, _: &'b OperationDefinition<'a>, _: Option<&'a str>) {
+            self.usages.push(Vec::new());
+        }
+        fn enter_fragment_definition(&mut self, _: &'b FragmentDefinition<'a>) {
+            self.usages.push(Vec::new());
+        }
+        fn enter_field(
+            &mut self,
+            field: &'b Field<'a>,
+            _: Option<&'a str>,
+            _: Option<&'b schema::Field<'a>>,
+        ) {
+            self.arguments(&field.arguments, field.position);
+        }
+        fn enter_directives(&mut self, directives: &'b [Directive<'a>], _: DirectiveLocation) {
+            for dir in directives {
+                self.arguments(&dir.arguments, dir.position);
+            }
+        }
+    }
+    let mut visitor = Visitor { usages: Vec::new() };
+    walk(ctx, &mut visitor);
+    visitor.usages
+}
+
+/// A variable used where a value of a known type is expected
+struct Usage<'b, 'a> {
+    name: &'a str,
+    expected: &'b Type<'a>,
+    /// The location has a default value
+    has_default: bool,
+    /// Position of the field or directive
+    position: Pos,
+}
+
+/// Returns variable usages of every definition of the document, in order
+fn typed_usages<'b, 'a>(ctx: &Context<'b, 'a>) -> Vec<Vec<Usage<'b, 'a>>> {
+    struct Visitor<'c, 'b, 'a> {
+        ctx: &'c Context<'b, 'a>,
+        usages: Vec<Vec<Usage<'b, 'a>>>,
+    }
+    impl<'c, 'b, 'a> Visitor<'c, 'b, 'a> {
+        fn arguments(
+            &mut self,
+            defs: &'b [schema::InputValue<'a>],
+            args: &'b [(&'a str, Value<'a>)],
+            position: Pos,
+        ) {
+            let schema = self
+                .ctx
+                .schema()
+                .expect("usages are collected with a schema");
+            let usages = self.usages.last_mut().expect("definition is entered");
+            for (name, value) in args {
+                if let Some(def) = defs.iter().find(|def| def.name == *name) {
+                    let has_default = def.default_value.is_some();
+                    collect(
+                        schema,
+                        value,
+                        &def.value_type,
+                        has_default,
+                        position,
+                        usages,
+                    );
+                }
+            }
+        }
+    }
+    impl<'c, 'b, 'a> TypedVisitor<'b, 'a> for Visitor<'c, 'b, 'a> {
+        fn enter_operation(&mut self, _: &'b OperationDefinition<'a>, _: Option<&'a str>) {
+            self.usages.push(Vec::new());
+        }
+        fn enter_fragment_definition(&mut self, _: &'b FragmentDefinition<'a>) {
+            self.usages.push(Vec::new());
+        }
+        fn enter_field(
+            &mut self,
+            field: &'b Field<'a>,
+            _: Option<&'a str>,
+            def: Option<&'b schema::Field<'a>>,
+        ) {
+            if let Some(def) = def {
+                self.arguments(&def.arguments, &field.arguments, field.position);
+            }
+        }
+        fn enter_directives(&mut self, directives: &'b [Directive<'a>], _: DirectiveLocation) {
+            for dir in directives {
+                if let Some(def) = self.ctx.get_directive(dir.name) {
+                    self.arguments(&def.arguments, &dir.arguments, dir.position);
+                }
+            }
+        }
+    }
+    let mut visitor = Visitor {
+        ctx,
+        usages: Vec::new(),
+    };
+    walk(ctx, &mut visitor);
+    visitor.usages
+}
+
+fn collect<'b, 'a>(
+    schema: &Schema<'b, 'a>,
+    value: &'b Value<'a>,
+    ty: &'b Type<'a>,
+    has_default: bool,
+    position: Pos,
+    usages: &mut Vec<Usage<'b, 'a>>,
+) {
+    match value {
+        Value::Variable(name) => usages.push(Usage {
+            name,
+            expected: ty,
+            has_default,
+            position,
+        }),
+        Value::List(items) => {
+            let nullable = match ty {
+                Type::NonNullType(inner) => inner,
+                ty => ty,
+            };
+            if let Type::ListType(inner) = nullable {
+                for item in items {
+                    collect(schema, item, inner, false, position, usages);
+                }
+            }
+        }
+        Value::Object(fields) => {
+            if let Some(TypeDefinition::InputObject(input)) = schema.get_type(named_type(ty)) {
+                for (name, value) in fields {
+                    if let Some(field) = input.fields.iter().find(|f| f.name == *name) {
+                        let has_default = field.default_value.is_some();
+                        collect(
+                            schema,
+                            value,
+                            &field.value_type,
+                            has_default,
+                            position,
+                            usages,
+                        );
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn allowed(schema: &Schema, var: &VariableDefinition, usage: &Usage) -> bool {
+    match (usage.expected, &var.var_type) {
+        (Type::NonNullType(expected), var_type) if !matches!(var_type, Type::NonNullType(_)) => {
+            let var_default = !matches!(var.default_value, None | Some(Value::Null));
+            (var_default || usage.has_default) && schema.is_subtype(var_type, expected)
+        }
+        (expected, var_type) => schema.is_subtype(var_type, expected),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NoUndefinedVariables, NoUnusedVariables, UniqueVariableNames};
+    use super::{VariablesAreInputTypes, VariablesInAllowedPosition};
+    use crate::query::validation::tests::{check, check_document};
+
+    #[test]
+    fn variables_are_input_types() {
+        assert!(check(
+            VariablesAreInputTypes,
+            "query($a: [String!], $b: ComplexInput, $c: DogCommand!) { dog { name } }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                VariablesAreInputTypes,
+                "query($a: Dog, $b: [[CatOrDog!]]!) { dog { name } }"
+            ),
+            vec![
+                r#"1:7: Variable "$a" cannot be non-input type "Dog"."#,
+                r#"1:16: Variable "$b" cannot be non-input type "[[CatOrDog!]]!"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn variables_in_allowed_position() {
+        assert!(check(
+            VariablesInAllowedPosition,
+            "query($i: Int!, $j: Int = 1, $b: Boolean, $l: [String!]) {
+                complicatedArgs {
+                    nonNullIntArgField(nonNullIntArg: $i)
+                    multipleReqs(req1: $j, req2: $i)
+                    stringListArgField(stringListArg: $l)
+                    complexArgField(complexArg: {requiredField: true, nonNullField: $b})
+                }
+                dog { isHouseTrained(atOtherHomes: $b) }
+            }"
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                VariablesInAllowedPosition,
+                "query Q($i: Int, $l: [String], $b: Boolean) {
+                    dog @include(if: $b) { ...F }
+                    complicatedArgs { stringListNonNullArgField(stringListNonNullArg: $l) }
+                }
+                fragment F on Dog { isAtLocation(x: 1, y: 2) name doesKnowCommand(dogCommand: $i) }"
+            ),
+            vec![
+                "1:32, 2:25: Variable \"$b\" of type \"Boolean\" used in position expecting type \
+                 \"Boolean!\".",
+                "1:18, 3:39: Variable \"$l\" of type \"[String]\" used in position expecting type \
+                 \"[String!]\".",
+                "1:9, 5:67: Variable \"$i\" of type \"Int\" used in position expecting type \
+                 \"DogCommand\".",
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_variable_names() {
+        assert!(check_document(
+            UniqueVariableNames,
+            "query A($x: Int) { a } query B($x: Int) { a }"
+        )
+        .is_empty());
+        assert_eq!(
+            check_document(
+                UniqueVariableNames,
+                "query($x: Int, $y: Int, $x: String) { a }"
+            ),
+            vec![r#"1:7, 1:25: There can be only one variable named "$x"."#]
+        );
+    }
+
+    #[test]
+    fn no_undefined_variables() {
+        assert!(check_document(
+            NoUndefinedVariables,
+            "query($a: Int, $b: Int) { f(x: [$a]) @skip(if: $b) { ...F } } fragment F on T { g(y: \
+             {z: $a}) }"
+        )
+        .is_empty());
+        assert_eq!(
+            check_document(
+                NoUndefinedVariables,
+                "query Q($a: Int) { f(x: $a, y: $b) { ...F } }\n\
+                 { ...F }\n\
+                 fragment F on T { g(y: $a) }"
+            ),
+            vec![
+                r#"1:20, 1:1: Variable "$b" is not defined by operation "Q"."#,
+                r#"3:19, 2:1: Variable "$a" is not defined."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn no_unused_variables() {
+        assert_eq!(
+            check_document(
+                NoUnusedVariables,
+                "query Q($a: Int, $b: Int) { ...F } query($c: Int) { f(x: $c) } fragment F on T { \
+                 g(y: $a) }"
+            ),
+            vec![r#"1:18: Variable "$b" is never used in operation "Q"."#]
+        );
+        assert_eq!(
+            check_document(NoUnusedVariables, "query($a: Int) { f }"),
+            vec![r#"1:7: Variable "$a" is never used."#]
+        );
+    }
+}
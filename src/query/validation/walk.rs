@@ -0,0 +1,105 @@
+//! Walking a document along with the schema types of its nodes
+use crate::query::ast::*;
+use crate::schema::index::named_type;
+use crate::schema::{self, DirectiveLocation};
+
+use super::Context;
+
+/// Visitor of document nodes with their parent types
+///
+/// Parent types are `None` when they are unknown, either because there is
+/// no schema, or because the document refers to something which doesn't
+/// exist. Fragment spreads aren't followed, fragment definitions are
+/// visited on their own.
+pub(crate) trait TypedVisitor<'b, 'a> {
+    fn enter_operation(&mut self, _op: &'b OperationDefinition<'a>, _root: Option<&'a str>) {}
+    fn enter_fragment_definition(&mut self, _frag: &'b FragmentDefinition<'a>) {}
+    fn enter_selection_set(&mut self, _set: &'b SelectionSet<'a>, _parent: Option<&'a str>) {}
+    fn enter_field(
+        &mut self,
+        _field: &'b Field<'a>,
+        _parent: Option<&'a str>,
+        _def: Option<&'b schema::Field<'a>>,
+    ) {
+    }
+    fn enter_inline_fragment(&mut self, _frag: &'b InlineFragment<'a>, _parent: Option<&'a str>) {}
+    fn enter_fragment_spread(&mut self, _spread: &'b FragmentSpread<'a>, _parent: Option<&'a str>) {
+    }
+    fn enter_directives(&mut self, _directives: &'b [Directive<'a>], _location: DirectiveLocation) {
+    }
+}
+
+pub(crate) fn walk<'b, 'a, V: TypedVisitor<'b, 'a>>(ctx: &Context<'b, 'a>, visitor: &mut V) {
+    let mut walker = Walker { ctx, visitor };
+    for def in &ctx.document().definitions {
+        match def {
+            Definition::Operation(op) => walker.operation(op),
+            Definition::Fragment(frag) => walker.fragment_definition(frag),
+        }
+    }
+}
+
+struct Walker<'c, 'b, 'a, V> {
+    ctx: &'c Context<'b, 'a>,
+    visitor: &'c mut V,
+}
+
+impl<'c, 'b, 'a, V: TypedVisitor<'b, 'a>> Walker<'c, 'b, 'a, V> {
+    /// Returns the type if it's a known type
+    fn known(&self, name: &'a str) -> Option<&'a str> {
+        self.ctx.get_type(name).map(|_| name)
+    }
+
+    fn operation(&mut self, op: &'b OperationDefinition<'a>) {
+        let kind = op.kind();
+        let root = self.ctx.root_type(kind);
+        self.visitor.enter_operation(op, root);
+        let location = match kind {
+            OperationKind::Query => DirectiveLocation::Query,
+            OperationKind::Mutation => DirectiveLocation::Mutation,
+            OperationKind::Subscription => DirectiveLocation::Subscription,
+        };
+        self.visitor.enter_directives(op.directives(), location);
+        self.selection_set(op.selection_set(), root);
+    }
+
+    fn fragment_definition(&mut self, frag: &'b FragmentDefinition<'a>) {
+        self.visitor.enter_fragment_definition(frag);
+        self.visitor
+            .enter_directives(&frag.directives, DirectiveLocation::FragmentDefinition);
+        let TypeCondition::On(name) = frag.type_condition;
+        let parent = self.known(name);
+        self.selection_set(&frag.selection_set, parent);
+    }
+
+    fn selection_set(&mut self, set: &'b SelectionSet<'a>, parent: Option<&'a str>) {
+        self.visitor.enter_selection_set(set, parent);
+        for item in &set.items {
+            match item {
+                Selection::Field(field) => {
+                    let def = parent.and_then(|parent| self.ctx.field(parent, field.name));
+                    self.visitor.enter_field(field, parent, def);
+                    self.visitor
+                        .enter_directives(&field.directives, DirectiveLocation::Field);
+                    let inner = def.and_then(|def| self.known(named_type(&def.field_type)));
+                    self.selection_set(&field.selection_set, inner);
+                }
+                Selection::InlineFragment(frag) => {
+                    self.visitor.enter_inline_fragment(frag, parent);
+                    self.visitor
+                        .enter_directives(&frag.directives, DirectiveLocation::InlineFragment);
+                    let inner = match frag.type_condition {
+                        Some(TypeCondition::On(name)) => self.known(name),
+                        None => parent,
+                    };
+                    self.selection_set(&frag.selection_set, inner);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.visitor.enter_fragment_spread(spread, parent);
+                    self.visitor
+                        .enter_directives(&spread.directives, DirectiveLocation::FragmentSpread);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Inlining variables into query documents
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value as Json};
+use thiserror::Error;
+
+use crate::json::ValueJsonError;
+use crate::query::ast::*;
+use crate::query::fragments::used_fragments;
+use crate::query::visit::{self, Fold};
+
+/// Values of variables as sent along with a query
+pub type Variables = Map<String, Json>;
+
+/// Error inlining variables
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum InlineError {
+    #[error(transparent)]
+    Operation(#[from] OperationError),
+    #[error("variable ${} is used but not defined", _0)]
+    UndefinedVariable(String),
+    #[error("value of non-null variable ${} is missing or null", _0)]
+    MissingVariable(String),
+    #[error("invalid value of variable ${}: {}", _0, _1)]
+    InvalidVariable(String, #[source] ValueJsonError),
+}
+
+/// Returns a document with the selected operation and the fragments it
+/// uses, where variables are replaced by literal values
+///
+/// The operation is selected by [`Document::operation`], and only its
+/// variable definitions are used. Variable definitions are removed. Values
+/// are taken from `variables`, or from the default value of the variable
+/// definition. When neither exists and the variable is nullable, arguments
+/// and object fields set to the variable are removed, as if they were not
+/// specified, and list items are replaced by `null`. Non-null variables
+/// must have a value which isn't `null`.
+///
+/// JSON strings always become string literals, as enum types can't be
+/// told from custom scalars without a schema.
+pub fn inline_variables<'a>(
+    doc: &Document<'a>,
+    operation: Option<&str>,
+    variables: &'a Variables,
+) -> Result<Document<'a>, InlineError> {
+    let op = doc.operation(operation)?;
+    let mut definitions = vec![Definition::Operation(op.clone())];
+    definitions.extend(
+        used_fragments(doc, op)
+            .indices
+            .iter()
+            .map(|&idx| doc.definitions[idx].clone()),
+    );
+    let mut inliner = Inliner {
+        definitions: op
+            .variable_definitions()
+            .iter()
+            .map(|var| (var.name, var))
+            .collect(),
+        variables,
+        error: None,
+    };
+    let doc = inliner.fold_document(Document { definitions });
+    match inliner.error {
+        Some(err) => Err(err),
+        None => Ok(doc),
+    }
+}
+
+struct Inliner<'v, 'a> {
+    definitions: HashMap<&'a str, &'v VariableDefinition<'a>>,
+    variables: &'a Variables,
+    error: Option<InlineError>,
+}
+
+impl<'v, 'a> Inliner<'v, 'a> {
+    /// Returns the value of the variable, `None` if it isn't provided
+    fn variable(&self, name: &str) -> Result<Option<Value<'a>>, InlineError> {
+        let def = self
+            .definitions
+            .get(name)
+            .ok_or_else(|| InlineError::UndefinedVariable(name.to_string()))?;
+        if let Some(json) = self.variables.get(name) {
+            if json.is_null() && matches!(def.var_type, Type::NonNullType(_)) {
+                return Err(InlineError::MissingVariable(name.to_string()));
+            }
+            return Value::from_json(json)
+                .map(Some)
+                .map_err(|e| InlineError::InvalidVariable(name.to_string(), e));
+        }
+        if let Some(ref default) = def.default_value {
+            return Ok(Some(default.clone()));
+        }
+        match def.var_type {
+            Type::NonNullType(_) => Err(InlineError::MissingVariable(name.to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Resolves variables in the value, `None` means it isn't provided
+    fn resolve(&self, value: Value<'a>) -> Result<Option<Value<'a>>, InlineError> {
+        let value = match value {
+            Value::Variable(name) => return self.variable(name),
+            Value::List(items) => Value::List(
+                items
+                    .into_iter()
+                    .map(|item| Ok(self.resolve(item)?.unwrap_or(Value::Null)))
+                    .collect::<Result<_, InlineError>>()?,
+            ),
+            Value::Object(fields) => {
+                let mut resolved = BTreeMap::new();
+                for (key, val) in fields {
+                    if let Some(val) = self.resolve(val)? {
+                        resolved.insert(key, val);
+                    }
+                }
+                Value::Object(resolved)
+            }
+            value => value,
+        };
+        Ok(Some(value))
+    }
+}
+
+impl<'v, 'a> Fold<'a> for Inliner<'v, 'a> {
+    fn fold_operation(&mut self, op: OperationDefinition<'a>) -> OperationDefinition<'a> {
+        let mut op = visit::fold_operation(self, op);
+        match op {
+            OperationDefinition::SelectionSet(_) => {}
+            OperationDefinition::Query(ref mut q) => q.variable_definitions.clear(),
+            OperationDefinition::Mutation(ref mut m) => m.variable_definitions.clear(),
+            OperationDefinition::Subscription(ref mut s) => s.variable_definitions.clear(),
+        }
+        op
+    }
+    fn fold_variable_definition(&mut self, var: VariableDefinition<'a>) -> VariableDefinition<'a> {
+        // default values are constant, and the definitions are removed anyway
+        var
+    }
+    fn fold_argument(&mut self, arg: (&'a str, Value<'a>)) -> Option<(&'a str, Value<'a>)> {
+        let (name, value) = arg;
+        match self.resolve(value) {
+            Ok(value) => value.map(|value| (name, value)),
+            Err(err) => {
+                self.error.get_or_insert(err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{inline_variables, InlineError};
+    use crate::query::{parse_query, OperationError};
+
+    fn inline(source: &str, vars: serde_json::Value) -> Result<String, InlineError> {
+        inline_operation(source, None, vars)
+    }
+
+    fn inline_operation(
+        source: &str,
+        operation: Option<&str>,
+        vars: serde_json::Value,
+    ) -> Result<String, InlineError> {
+        let doc = parse_query(source).unwrap();
+        inline_variables(&doc, operation, vars.as_object().unwrap()).map(|doc| doc.to_string())
+    }
+
+    #[test]
+    fn replaces_variables() {
+        assert_eq!(
+            inline(
+                "query Q($id: ID!, $f: Filter, $n: Int = 10) { \
+                 user(id: $id) @include(if: true) { friends(first: $n, filter: $f) } }",
+                json!({"id": "1", "f": {"tags": ["a"], "deep": {"x": 1.5}}}),
+            )
+            .unwrap(),
+            "query Q {\n  user(id: \"1\") @include(if: true) {\n    \
+             friends(first: 10, filter: {deep: {x: 1.5}, tags: [\"a\"]})\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn missing_nullable() {
+        assert_eq!(
+            inline(
+                "query($a: Int, $b: Int) @dir(a: $a) { \
+                 f(a: $a, b: 1, o: {a: $a, b: 2}, l: [$a, $b]) }",
+                json!({"b": null}),
+            )
+            .unwrap(),
+            "query @dir {\n  f(b: 1, o: {b: 2}, l: [null, null])\n}\n"
+        );
+    }
+
+    #[test]
+    fn fragments() {
+        assert_eq!(
+            inline(
+                "query($a: Int) { ...F } fragment F on T { f(a: $a) }",
+                json!({"a": 1}),
+            )
+            .unwrap(),
+            "query {\n  ...F\n}\n\nfragment F on T {\n  f(a: 1)\n}\n"
+        );
+    }
+
+    #[test]
+    fn operations() {
+        let source = "query A($n: Int = 10) { f(n: $n) ...F }\n\
+                      query B($n: Int! = 20) { f(n: $n) }\n\
+                      fragment F on T { g(n: $n) }";
+        assert_eq!(
+            inline_operation(source, Some("A"), json!({})).unwrap(),
+            "query A {\n  f(n: 10)\n  ...F\n}\n\nfragment F on T {\n  g(n: 10)\n}\n"
+        );
+        assert_eq!(
+            inline_operation(source, Some("B"), json!({})).unwrap(),
+            "query B {\n  f(n: 20)\n}\n"
+        );
+        assert_eq!(
+            inline_operation(source, Some("B"), json!({"n": null})),
+            Err(InlineError::MissingVariable("n".into()))
+        );
+        assert_eq!(
+            inline_operation(source, None, json!({})),
+            Err(InlineError::Operation(OperationError::MultipleOperations))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            inline("query($a: Int!) { f(a: $a) }", json!({})),
+            Err(InlineError::MissingVariable("a".into()))
+        );
+        assert_eq!(
+            inline("{ f(a: $a) }", json!({})),
+            Err(InlineError::UndefinedVariable("a".into()))
+        );
+        assert!(inline("query($a: Int) { f(a: $a) }", json!({"a": u64::MAX})).is_err());
+    }
+}
@@ -0,0 +1,1035 @@
+//! Traversal of the query language AST
+//!
+//! The [`Visitor`] trait has an `enter_*` and a `leave_*` hook for every kind
+//! of node. Hooks are called by the `walk_*` functions in document order and
+//! receive a [`Path`] pointing at the node being visited.
+//!
+//! [`VisitMut`] modifies nodes in place and [`Fold`] consumes a document and
+//! builds a new one, both are meant for rewriting queries before printing
+//! them with [`Document::format`](../struct.Document.html#method.format).
+//!
+//! ```rust
+//! # extern crate graphql_parser;
+//! use graphql_parser::query::{parse_query, Value};
+//! use graphql_parser::query::visit::{walk_document, Control, Path, Visitor};
+//!
+//! struct Variables<'a>(Vec<&'a str>);
+//!
+//! impl<'a> Visitor<'a> for Variables<'a> {
+//!     fn enter_value(&mut self, value: &'a Value<'a>, _: &Path<'a>) -> Control {
+//!         if let Value::Variable(name) = *value {
+//!             self.0.push(name);
+//!         }
+//!         Control::Continue
+//!     }
+//! }
+//!
+//! let ast = parse_query("query($id: ID) { user(id: $id) { name } }").unwrap();
+//! let mut vars = Variables(Vec::new());
+//! walk_document(&mut vars, &ast);
+//! assert_eq!(vars.0, vec!["id"]);
+//! ```
+use std::fmt;
+
+use crate::query::ast::*;
+
+/// Tells the walker what to do after an `enter_*` hook returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Visit the children of the node and then call the `leave_*` hook
+    Continue,
+    /// Skip the children of the node and its `leave_*` hook
+    Skip,
+}
+
+/// A single step from the document root towards a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    /// An operation, anonymous operations have no name
+    Operation(Option<&'a str>),
+    /// A fragment definition
+    Fragment(&'a str),
+    /// A variable definition of an operation
+    VariableDefinition(&'a str),
+    /// A field, identified by its response key (alias or name)
+    Field(&'a str),
+    /// An inline fragment with its optional type condition
+    InlineFragment(Option<&'a str>),
+    /// A fragment spread
+    FragmentSpread(&'a str),
+    /// A directive applied to the parent node
+    Directive(&'a str),
+    /// An argument of a field or a directive
+    Argument(&'a str),
+    /// An item of a list value
+    ListIndex(usize),
+    /// A field of an input object value
+    ObjectField(&'a str),
+}
+
+/// Path from the document root to the node being visited
+///
+/// The path passed to a hook always ends with the segment of the node
+/// itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Path<'a> {
+    segments: Vec<PathSegment<'a>>,
+}
+
+impl<'a> Path<'a> {
+    /// Creates an empty path pointing at the document root
+    pub fn new() -> Path<'a> {
+        Path::default()
+    }
+
+    /// Returns all segments starting from the root
+    pub fn segments(&self) -> &[PathSegment<'a>] {
+        &self.segments
+    }
+
+    /// Returns the last segment of the path
+    pub fn last(&self) -> Option<&PathSegment<'a>> {
+        self.segments.last()
+    }
+
+    /// Returns number of segments in the path
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` if the path points at the document root
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns response keys of the fields along the path
+    pub fn response_path(&self) -> Vec<&'a str> {
+        self.segments
+            .iter()
+            .filter_map(|seg| match *seg {
+                PathSegment::Field(key) => Some(key),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the segment of the operation or fragment the path starts from
+    pub fn definition(&self) -> Option<&PathSegment<'a>> {
+        self.segments.first()
+    }
+
+    /// Appends a segment to the path
+    pub fn push(&mut self, segment: PathSegment<'a>) {
+        self.segments.push(segment);
+    }
+
+    /// Removes the last segment of the path
+    pub fn pop(&mut self) -> Option<PathSegment<'a>> {
+        self.segments.pop()
+    }
+}
+
+impl<'a> fmt::Display for PathSegment<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathSegment::Operation(Some(name)) => write!(f, "operation {}", name),
+            PathSegment::Operation(None) => f.write_str("anonymous operation"),
+            PathSegment::Fragment(name) => write!(f, "fragment {}", name),
+            PathSegment::VariableDefinition(name) => write!(f, "${}", name),
+            PathSegment::Field(key) => f.write_str(key),
+            PathSegment::InlineFragment(Some(cond)) => write!(f, "... on {}", cond),
+            PathSegment::InlineFragment(None) => f.write_str("..."),
+            PathSegment::FragmentSpread(name) => write!(f, "...{}", name),
+            PathSegment::Directive(name) => write!(f, "@{}", name),
+            PathSegment::Argument(name) => write!(f, "({}:)", name),
+            PathSegment::ListIndex(idx) => write!(f, "[{}]", idx),
+            PathSegment::ObjectField(name) => write!(f, "{{{}:}}", name),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Path<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, seg) in self.segments.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" > ")?;
+            }
+            write!(f, "{}", seg)?;
+        }
+        Ok(())
+    }
+}
+
+/// A read-only visitor of the query AST
+///
+/// Every hook has a default implementation that does nothing, so
+/// implementors only override hooks for the nodes they are interested in.
+/// Returning [`Control::Skip`] from an `enter_*` hook skips the children of
+/// the node as well as the matching `leave_*` hook.
+#[allow(unused_variables)]
+pub trait Visitor<'a> {
+    fn enter_document(&mut self, doc: &'a Document<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_document(&mut self, doc: &'a Document<'a>) {}
+
+    fn enter_operation(&mut self, op: &'a OperationDefinition<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_operation(&mut self, op: &'a OperationDefinition<'a>, path: &Path<'a>) {}
+
+    fn enter_fragment_definition(
+        &mut self,
+        frag: &'a FragmentDefinition<'a>,
+        path: &Path<'a>,
+    ) -> Control {
+        Control::Continue
+    }
+    fn leave_fragment_definition(&mut self, frag: &'a FragmentDefinition<'a>, path: &Path<'a>) {}
+
+    fn enter_variable_definition(
+        &mut self,
+        var: &'a VariableDefinition<'a>,
+        path: &Path<'a>,
+    ) -> Control {
+        Control::Continue
+    }
+    fn leave_variable_definition(&mut self, var: &'a VariableDefinition<'a>, path: &Path<'a>) {}
+
+    fn enter_selection_set(&mut self, set: &'a SelectionSet<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_selection_set(&mut self, set: &'a SelectionSet<'a>, path: &Path<'a>) {}
+
+    fn enter_field(&mut self, field: &'a Field<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_field(&mut self, field: &'a Field<'a>, path: &Path<'a>) {}
+
+    fn enter_inline_fragment(&mut self, frag: &'a InlineFragment<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_inline_fragment(&mut self, frag: &'a InlineFragment<'a>, path: &Path<'a>) {}
+
+    fn enter_fragment_spread(
+        &mut self,
+        spread: &'a FragmentSpread<'a>,
+        path: &Path<'a>,
+    ) -> Control {
+        Control::Continue
+    }
+    fn leave_fragment_spread(&mut self, spread: &'a FragmentSpread<'a>, path: &Path<'a>) {}
+
+    fn enter_directive(&mut self, dir: &'a Directive<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_directive(&mut self, dir: &'a Directive<'a>, path: &Path<'a>) {}
+
+    fn enter_argument(&mut self, name: &'a str, value: &'a Value<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_argument(&mut self, name: &'a str, value: &'a Value<'a>, path: &Path<'a>) {}
+
+    fn enter_value(&mut self, value: &'a Value<'a>, path: &Path<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_value(&mut self, value: &'a Value<'a>, path: &Path<'a>) {}
+}
+
+/// Walks the whole document starting from an empty path
+pub fn walk_document<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, doc: &'a Document<'a>) {
+    if visitor.enter_document(doc) == Control::Skip {
+        return;
+    }
+    let mut path = Path::new();
+    for def in &doc.definitions {
+        walk_definition(visitor, def, &mut path);
+    }
+    visitor.leave_document(doc);
+}
+
+pub fn walk_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &'a Definition<'a>,
+    path: &mut Path<'a>,
+) {
+    match *def {
+        Definition::Operation(ref op) => walk_operation(visitor, op, path),
+        Definition::Fragment(ref frag) => walk_fragment_definition(visitor, frag, path),
+    }
+}
+
+pub fn walk_operation<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    op: &'a OperationDefinition<'a>,
+    path: &mut Path<'a>,
+) {
+    path.push(PathSegment::Operation(op.name()));
+    if visitor.enter_operation(op, path) == Control::Continue {
+        let (vars, dirs, set) = match *op {
+            OperationDefinition::SelectionSet(ref set) => (&[][..], &[][..], set),
+            OperationDefinition::Query(ref q) => (
+                &q.variable_definitions[..],
+                &q.directives[..],
+                &q.selection_set,
+            ),
+            OperationDefinition::Mutation(ref m) => (
+                &m.variable_definitions[..],
+                &m.directives[..],
+                &m.selection_set,
+            ),
+            OperationDefinition::Subscription(ref s) => (
+                &s.variable_definitions[..],
+                &s.directives[..],
+                &s.selection_set,
+            ),
+        };
+        for var in vars {
+            walk_variable_definition(visitor, var, path);
+        }
+        walk_directives(visitor, dirs, path);
+        walk_selection_set(visitor, set, path);
+        visitor.leave_operation(op, path);
+    }
+    path.pop();
+}
+
+pub fn walk_fragment_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    frag: &'a FragmentDefinition<'a>,
+    path: &mut Path<'a>,
+) {
+    path.push(PathSegment::Fragment(frag.name));
+    if visitor.enter_fragment_definition(frag, path) == Control::Continue {
+        walk_directives(visitor, &frag.directives, path);
+        walk_selection_set(visitor, &frag.selection_set, path);
+        visitor.leave_fragment_definition(frag, path);
+    }
+    path.pop();
+}
+
+pub fn walk_variable_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    var: &'a VariableDefinition<'a>,
+    path: &mut Path<'a>,
+) {
+    path.push(PathSegment::VariableDefinition(var.name));
+    if visitor.enter_variable_definition(var, path) == Control::Continue {
+        if let Some(ref value) = var.default_value {
+            walk_value(visitor, value, path);
+        }
+        visitor.leave_variable_definition(var, path);
+    }
+    path.pop();
+}
+
+/// Walks the selection set, the path is not extended for selection sets
+pub fn walk_selection_set<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    set: &'a SelectionSet<'a>,
+    path: &mut Path<'a>,
+) {
+    if visitor.enter_selection_set(set, path) == Control::Continue {
+        for item in &set.items {
+            walk_selection(visitor, item, path);
+        }
+        visitor.leave_selection_set(set, path);
+    }
+}
+
+pub fn walk_selection<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    selection: &'a Selection<'a>,
+    path: &mut Path<'a>,
+) {
+    match *selection {
+        Selection::Field(ref field) => walk_field(visitor, field, path),
+        Selection::InlineFragment(ref frag) => walk_inline_fragment(visitor, frag, path),
+        Selection::FragmentSpread(ref spread) => walk_fragment_spread(visitor, spread, path),
+    }
+}
+
+pub fn walk_field<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    field: &'a Field<'a>,
+    path: &mut Path<'a>,
+) {
+    path.push(PathSegment::Field(field.alias.unwrap_or(field.name)));
+    if visitor.enter_field(field, path) == Control::Continue {
+        walk_arguments(visitor, &field.arguments, path);
+        walk_directives(visitor, &field.directives, path);
+        walk_selection_set(visitor, &field.selection_set, path);
+        visitor.leave_field(field, path);
+    }
+    path.pop();
+}
+
+pub fn walk_inline_fragment<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    frag: &'a InlineFragment<'a>,
+    path: &mut Path<'a>,
+) {
+    let cond = frag.type_condition.as_ref().map(|c| match *c {
+        TypeCondition::On(name) => name,
+    });
+    path.push(PathSegment::InlineFragment(cond));
+    if visitor.enter_inline_fragment(frag, path) == Control::Continue {
+        walk_directives(visitor, &frag.directives, path);
+        walk_selection_set(visitor, &frag.selection_set, path);
+        visitor.leave_inline_fragment(frag, path);
+    }
+    path.pop();
+}
+
+/// Walks the fragment spread and its directives
+///
+/// The referenced fragment definition is not visited, it's visited once as
+/// a part of the document.
+pub fn walk_fragment_spread<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    spread: &'a FragmentSpread<'a>,
+    path: &mut Path<'a>,
+) {
+    path.push(PathSegment::FragmentSpread(spread.fragment_name));
+    if visitor.enter_fragment_spread(spread, path) == Control::Continue {
+        walk_directives(visitor, &spread.directives, path);
+        visitor.leave_fragment_spread(spread, path);
+    }
+    path.pop();
+}
+
+pub fn walk_directives<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    dirs: &'a [Directive<'a>],
+    path: &mut Path<'a>,
+) {
+    for dir in dirs {
+        walk_directive(visitor, dir, path);
+    }
+}
+
+pub fn walk_directive<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    dir: &'a Directive<'a>,
+    path: &mut Path<'a>,
+) {
+    path.push(PathSegment::Directive(dir.name));
+    if visitor.enter_directive(dir, path) == Control::Continue {
+        walk_arguments(visitor, &dir.arguments, path);
+        visitor.leave_directive(dir, path);
+    }
+    path.pop();
+}
+
+pub fn walk_arguments<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    args: &'a [(&'a str, Value<'a>)],
+    path: &mut Path<'a>,
+) {
+    for &(name, ref value) in args {
+        path.push(PathSegment::Argument(name));
+        if visitor.enter_argument(name, value, path) == Control::Continue {
+            walk_value(visitor, value, path);
+            visitor.leave_argument(name, value, path);
+        }
+        path.pop();
+    }
+}
+
+/// Walks the value and all nested list items and object fields
+pub fn walk_value<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    value: &'a Value<'a>,
+    path: &mut Path<'a>,
+) {
+    if visitor.enter_value(value, path) == Control::Continue {
+        match *value {
+            Value::List(ref items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(PathSegment::ListIndex(idx));
+                    walk_value(visitor, item, path);
+                    path.pop();
+                }
+            }
+            Value::Object(ref fields) => {
+                for (&name, item) in fields {
+                    path.push(PathSegment::ObjectField(name));
+                    walk_value(visitor, item, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+        visitor.leave_value(value, path);
+    }
+}
+
+/// A visitor that modifies the query AST in place
+///
+/// Each `visit_*_mut` method defaults to the matching `walk_*_mut` function
+/// which visits the children of the node. Override a method and call the
+/// `walk_*_mut` function from it to keep descending.
+pub trait VisitMut<'a> {
+    fn visit_document_mut(&mut self, doc: &mut Document<'a>) {
+        walk_document_mut(self, doc)
+    }
+    fn visit_definition_mut(&mut self, def: &mut Definition<'a>) {
+        walk_definition_mut(self, def)
+    }
+    fn visit_operation_mut(&mut self, op: &mut OperationDefinition<'a>) {
+        walk_operation_mut(self, op)
+    }
+    fn visit_fragment_definition_mut(&mut self, frag: &mut FragmentDefinition<'a>) {
+        walk_fragment_definition_mut(self, frag)
+    }
+    fn visit_variable_definition_mut(&mut self, var: &mut VariableDefinition<'a>) {
+        walk_variable_definition_mut(self, var)
+    }
+    fn visit_selection_set_mut(&mut self, set: &mut SelectionSet<'a>) {
+        walk_selection_set_mut(self, set)
+    }
+    fn visit_selection_mut(&mut self, selection: &mut Selection<'a>) {
+        walk_selection_mut(self, selection)
+    }
+    fn visit_field_mut(&mut self, field: &mut Field<'a>) {
+        walk_field_mut(self, field)
+    }
+    fn visit_inline_fragment_mut(&mut self, frag: &mut InlineFragment<'a>) {
+        walk_inline_fragment_mut(self, frag)
+    }
+    fn visit_fragment_spread_mut(&mut self, spread: &mut FragmentSpread<'a>) {
+        walk_fragment_spread_mut(self, spread)
+    }
+    fn visit_directive_mut(&mut self, dir: &mut Directive<'a>) {
+        walk_directive_mut(self, dir)
+    }
+    fn visit_argument_mut(&mut self, arg: &mut (&'a str, Value<'a>)) {
+        walk_argument_mut(self, arg)
+    }
+    fn visit_value_mut(&mut self, value: &mut Value<'a>) {
+        walk_value_mut(self, value)
+    }
+}
+
+pub fn walk_document_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, doc: &mut Document<'a>) {
+    for def in &mut doc.definitions {
+        visitor.visit_definition_mut(def);
+    }
+}
+
+pub fn walk_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &mut Definition<'a>,
+) {
+    match *def {
+        Definition::Operation(ref mut op) => visitor.visit_operation_mut(op),
+        Definition::Fragment(ref mut frag) => visitor.visit_fragment_definition_mut(frag),
+    }
+}
+
+pub fn walk_operation_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    op: &mut OperationDefinition<'a>,
+) {
+    let (vars, dirs, set) = match *op {
+        OperationDefinition::SelectionSet(ref mut set) => {
+            return visitor.visit_selection_set_mut(set);
+        }
+        OperationDefinition::Query(ref mut q) => (
+            &mut q.variable_definitions,
+            &mut q.directives,
+            &mut q.selection_set,
+        ),
+        OperationDefinition::Mutation(ref mut m) => (
+            &mut m.variable_definitions,
+            &mut m.directives,
+            &mut m.selection_set,
+        ),
+        OperationDefinition::Subscription(ref mut s) => (
+            &mut s.variable_definitions,
+            &mut s.directives,
+            &mut s.selection_set,
+        ),
+    };
+    for var in vars {
+        visitor.visit_variable_definition_mut(var);
+    }
+    for dir in dirs {
+        visitor.visit_directive_mut(dir);
+    }
+    visitor.visit_selection_set_mut(set);
+}
+
+pub fn walk_fragment_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    frag: &mut FragmentDefinition<'a>,
+) {
+    for dir in &mut frag.directives {
+        visitor.visit_directive_mut(dir);
+    }
+    visitor.visit_selection_set_mut(&mut frag.selection_set);
+}
+
+pub fn walk_variable_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    var: &mut VariableDefinition<'a>,
+) {
+    if let Some(ref mut value) = var.default_value {
+        visitor.visit_value_mut(value);
+    }
+}
+
+pub fn walk_selection_set_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    set: &mut SelectionSet<'a>,
+) {
+    for item in &mut set.items {
+        visitor.visit_selection_mut(item);
+    }
+}
+
+pub fn walk_selection_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    selection: &mut Selection<'a>,
+) {
+    match *selection {
+        Selection::Field(ref mut field) => visitor.visit_field_mut(field),
+        Selection::InlineFragment(ref mut frag) => visitor.visit_inline_fragment_mut(frag),
+        Selection::FragmentSpread(ref mut spread) => visitor.visit_fragment_spread_mut(spread),
+    }
+}
+
+pub fn walk_field_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, field: &mut Field<'a>) {
+    for arg in &mut field.arguments {
+        visitor.visit_argument_mut(arg);
+    }
+    for dir in &mut field.directives {
+        visitor.visit_directive_mut(dir);
+    }
+    visitor.visit_selection_set_mut(&mut field.selection_set);
+}
+
+pub fn walk_inline_fragment_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    frag: &mut InlineFragment<'a>,
+) {
+    for dir in &mut frag.directives {
+        visitor.visit_directive_mut(dir);
+    }
+    visitor.visit_selection_set_mut(&mut frag.selection_set);
+}
+
+pub fn walk_fragment_spread_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    spread: &mut FragmentSpread<'a>,
+) {
+    for dir in &mut spread.directives {
+        visitor.visit_directive_mut(dir);
+    }
+}
+
+pub fn walk_directive_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, dir: &mut Directive<'a>) {
+    for arg in &mut dir.arguments {
+        visitor.visit_argument_mut(arg);
+    }
+}
+
+pub fn walk_argument_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    arg: &mut (&'a str, Value<'a>),
+) {
+    visitor.visit_value_mut(&mut arg.1);
+}
+
+pub fn walk_value_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, value: &mut Value<'a>) {
+    match *value {
+        Value::List(ref mut items) => {
+            for item in items {
+                visitor.visit_value_mut(item);
+            }
+        }
+        Value::Object(ref mut fields) => {
+            for item in fields.values_mut() {
+                visitor.visit_value_mut(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A transformation that consumes the query AST and builds a new one
+///
+/// Each `fold_*` method defaults to the matching `fold_*` function which
+/// folds the children of the node. Methods for items of a list (definitions,
+/// selections, directives and arguments) return `None` to drop the item.
+pub trait Fold<'a> {
+    fn fold_document(&mut self, doc: Document<'a>) -> Document<'a> {
+        fold_document(self, doc)
+    }
+    fn fold_definition(&mut self, def: Definition<'a>) -> Option<Definition<'a>> {
+        fold_definition(self, def)
+    }
+    fn fold_operation(&mut self, op: OperationDefinition<'a>) -> OperationDefinition<'a> {
+        fold_operation(self, op)
+    }
+    fn fold_fragment_definition(&mut self, frag: FragmentDefinition<'a>) -> FragmentDefinition<'a> {
+        fold_fragment_definition(self, frag)
+    }
+    fn fold_variable_definition(&mut self, var: VariableDefinition<'a>) -> VariableDefinition<'a> {
+        fold_variable_definition(self, var)
+    }
+    fn fold_selection_set(&mut self, set: SelectionSet<'a>) -> SelectionSet<'a> {
+        fold_selection_set(self, set)
+    }
+    fn fold_selection(&mut self, selection: Selection<'a>) -> Option<Selection<'a>> {
+        fold_selection(self, selection)
+    }
+    fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+        fold_field(self, field)
+    }
+    fn fold_inline_fragment(&mut self, frag: InlineFragment<'a>) -> InlineFragment<'a> {
+        fold_inline_fragment(self, frag)
+    }
+    fn fold_fragment_spread(&mut self, spread: FragmentSpread<'a>) -> FragmentSpread<'a> {
+        fold_fragment_spread(self, spread)
+    }
+    fn fold_directive(&mut self, dir: Directive<'a>) -> Option<Directive<'a>> {
+        fold_directive(self, dir)
+    }
+    fn fold_argument(&mut self, arg: (&'a str, Value<'a>)) -> Option<(&'a str, Value<'a>)> {
+        fold_argument(self, arg)
+    }
+    fn fold_value(&mut self, value: Value<'a>) -> Value<'a> {
+        fold_value(self, value)
+    }
+}
+
+pub fn fold_document<'a, F: Fold<'a> + ?Sized>(folder: &mut F, doc: Document<'a>) -> Document<'a> {
+    Document {
+        definitions: doc
+            .definitions
+            .into_iter()
+            .filter_map(|def| folder.fold_definition(def))
+            .collect(),
+    }
+}
+
+pub fn fold_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    def: Definition<'a>,
+) -> Option<Definition<'a>> {
+    Some(match def {
+        Definition::Operation(op) => Definition::Operation(folder.fold_operation(op)),
+        Definition::Fragment(frag) => Definition::Fragment(folder.fold_fragment_definition(frag)),
+    })
+}
+
+fn fold_variable_definitions<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    vars: Vec<VariableDefinition<'a>>,
+) -> Vec<VariableDefinition<'a>> {
+    vars.into_iter()
+        .map(|var| folder.fold_variable_definition(var))
+        .collect()
+}
+
+fn fold_directives<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    dirs: Vec<Directive<'a>>,
+) -> Vec<Directive<'a>> {
+    dirs.into_iter()
+        .filter_map(|dir| folder.fold_directive(dir))
+        .collect()
+}
+
+fn fold_arguments<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    args: Vec<(&'a str, Value<'a>)>,
+) -> Vec<(&'a str, Value<'a>)> {
+    args.into_iter()
+        .filter_map(|arg| folder.fold_argument(arg))
+        .collect()
+}
+
+pub fn fold_operation<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    op: OperationDefinition<'a>,
+) -> OperationDefinition<'a> {
+    match op {
+        OperationDefinition::SelectionSet(set) => {
+            OperationDefinition::SelectionSet(folder.fold_selection_set(set))
+        }
+        OperationDefinition::Query(q) => OperationDefinition::Query(Query {
+            position: q.position,
+            name: q.name,
+            variable_definitions: fold_variable_definitions(folder, q.variable_definitions),
+            directives: fold_directives(folder, q.directives),
+            selection_set: folder.fold_selection_set(q.selection_set),
+        }),
+        OperationDefinition::Mutation(m) => OperationDefinition::Mutation(Mutation {
+            position: m.position,
+            name: m.name,
+            variable_definitions: fold_variable_definitions(folder, m.variable_definitions),
+            directives: fold_directives(folder, m.directives),
+            selection_set: folder.fold_selection_set(m.selection_set),
+        }),
+        OperationDefinition::Subscription(s) => OperationDefinition::Subscription(Subscription {
+            position: s.position,
+            name: s.name,
+            variable_definitions: fold_variable_definitions(folder, s.variable_definitions),
+            directives: fold_directives(folder, s.directives),
+            selection_set: folder.fold_selection_set(s.selection_set),
+        }),
+    }
+}
+
+pub fn fold_fragment_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    frag: FragmentDefinition<'a>,
+) -> FragmentDefinition<'a> {
+    FragmentDefinition {
+        position: frag.position,
+        name: frag.name,
+        type_condition: frag.type_condition,
+        directives: fold_directives(folder, frag.directives),
+        selection_set: folder.fold_selection_set(frag.selection_set),
+    }
+}
+
+pub fn fold_variable_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    var: VariableDefinition<'a>,
+) -> VariableDefinition<'a> {
+    VariableDefinition {
+        position: var.position,
+        name: var.name,
+        var_type: var.var_type,
+        default_value: var.default_value.map(|value| folder.fold_value(value)),
+    }
+}
+
+pub fn fold_selection_set<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    set: SelectionSet<'a>,
+) -> SelectionSet<'a> {
+    SelectionSet {
+        span: set.span,
+        items: set
+            .items
+            .into_iter()
+            .filter_map(|item| folder.fold_selection(item))
+            .collect(),
+    }
+}
+
+pub fn fold_selection<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    selection: Selection<'a>,
+) -> Option<Selection<'a>> {
+    Some(match selection {
+        Selection::Field(field) => Selection::Field(folder.fold_field(field)),
+        Selection::InlineFragment(frag) => {
+            Selection::InlineFragment(folder.fold_inline_fragment(frag))
+        }
+        Selection::FragmentSpread(spread) => {
+            Selection::FragmentSpread(folder.fold_fragment_spread(spread))
+        }
+    })
+}
+
+pub fn fold_field<'a, F: Fold<'a> + ?Sized>(folder: &mut F, field: Field<'a>) -> Field<'a> {
+    Field {
+        position: field.position,
+        alias: field.alias,
+        name: field.name,
+        arguments: fold_arguments(folder, field.arguments),
+        directives: fold_directives(folder, field.directives),
+        selection_set: folder.fold_selection_set(field.selection_set),
+    }
+}
+
+pub fn fold_inline_fragment<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    frag: InlineFragment<'a>,
+) -> InlineFragment<'a> {
+    InlineFragment {
+        position: frag.position,
+        type_condition: frag.type_condition,
+        directives: fold_directives(folder, frag.directives),
+        selection_set: folder.fold_selection_set(frag.selection_set),
+    }
+}
+
+pub fn fold_fragment_spread<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    spread: FragmentSpread<'a>,
+) -> FragmentSpread<'a> {
+    FragmentSpread {
+        position: spread.position,
+        fragment_name: spread.fragment_name,
+        directives: fold_directives(folder, spread.directives),
+    }
+}
+
+pub fn fold_directive<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    dir: Directive<'a>,
+) -> Option<Directive<'a>> {
+    Some(Directive {
+        position: dir.position,
+        name: dir.name,
+        arguments: fold_arguments(folder, dir.arguments),
+    })
+}
+
+pub fn fold_argument<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    (name, value): (&'a str, Value<'a>),
+) -> Option<(&'a str, Value<'a>)> {
+    Some((name, folder.fold_value(value)))
+}
+
+pub fn fold_value<'a, F: Fold<'a> + ?Sized>(folder: &mut F, value: Value<'a>) -> Value<'a> {
+    match value {
+        Value::List(items) => {
+            Value::List(items.into_iter().map(|v| folder.fold_value(v)).collect())
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, v)| (name, folder.fold_value(v)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_query;
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl<'a> Visitor<'a> for Trace {
+        fn enter_field(&mut self, field: &'a Field<'a>, path: &Path<'a>) -> Control {
+            self.0.push(format!("enter {}", path));
+            if field.name == "skipped" {
+                Control::Skip
+            } else {
+                Control::Continue
+            }
+        }
+        fn leave_field(&mut self, _: &'a Field<'a>, path: &Path<'a>) {
+            self.0.push(format!("leave {}", path));
+        }
+        fn enter_value(&mut self, value: &'a Value<'a>, path: &Path<'a>) -> Control {
+            if let Value::Variable(name) = *value {
+                self.0.push(format!("${} at {}", name, path));
+            }
+            Control::Continue
+        }
+    }
+
+    #[test]
+    fn enter_leave_order() {
+        let ast =
+            parse_query("query Q($x: Int) { a: user(ids: [1, $x]) { name } skipped { inner } }")
+                .unwrap();
+        let mut trace = Trace::default();
+        walk_document(&mut trace, &ast);
+        assert_eq!(
+            trace.0,
+            vec![
+                "enter operation Q > a",
+                "$x at operation Q > a > (ids:) > [1]",
+                "enter operation Q > a > name",
+                "leave operation Q > a > name",
+                "leave operation Q > a",
+                "enter operation Q > skipped",
+            ]
+        );
+    }
+
+    #[test]
+    fn response_path() {
+        struct Paths(Vec<Vec<String>>);
+        impl<'a> Visitor<'a> for Paths {
+            fn enter_field(&mut self, _: &'a Field<'a>, path: &Path<'a>) -> Control {
+                self.0
+                    .push(path.response_path().iter().map(|s| s.to_string()).collect());
+                Control::Continue
+            }
+        }
+        let ast = parse_query("{ a { ... on T { b } } } fragment F on T { c }").unwrap();
+        let mut paths = Paths(Vec::new());
+        walk_document(&mut paths, &ast);
+        assert_eq!(paths.0, vec![vec!["a"], vec!["a", "b"], vec!["c"]]);
+    }
+
+    struct RenameUsers;
+
+    impl<'a> VisitMut<'a> for RenameUsers {
+        fn visit_field_mut(&mut self, field: &mut Field<'a>) {
+            if field.name == "user" {
+                field.name = "member";
+            }
+            field
+                .selection_set
+                .items
+                .retain(|item| !matches!(*item, Selection::Field(ref f) if f.name == "secret"));
+            walk_field_mut(self, field);
+        }
+        fn visit_value_mut(&mut self, value: &mut Value<'a>) {
+            if let Value::Variable("id") = *value {
+                *value = Value::Int(7.into());
+            }
+            walk_value_mut(self, value);
+        }
+    }
+
+    #[test]
+    fn visit_mut_rewrites_in_place() {
+        let mut ast =
+            parse_query("{ user(ids: [$id]) { name secret friends { user { secret } } } }")
+                .unwrap();
+        RenameUsers.visit_document_mut(&mut ast);
+        assert_eq!(
+            ast.to_string(),
+            "{\n  member(ids: [7]) {\n    name\n    friends {\n      member\n    }\n  }\n}\n"
+        );
+    }
+
+    struct Deprecate;
+
+    impl<'a> Fold<'a> for Deprecate {
+        fn fold_selection(&mut self, selection: Selection<'a>) -> Option<Selection<'a>> {
+            match selection {
+                Selection::FragmentSpread(_) => None,
+                other => fold_selection(self, other),
+            }
+        }
+        fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+            let mut field = fold_field(self, field);
+            if field.name == "old" {
+                field.directives.push(Directive {
+                    position: field.position,
+                    name: "deprecated",
+                    arguments: Vec::new(),
+                });
+            }
+            field
+        }
+    }
+
+    #[test]
+    fn fold_drops_and_injects() {
+        let ast = parse_query("query Q { old ...Frag new { old } }").unwrap();
+        let ast = Deprecate.fold_document(ast);
+        assert_eq!(
+            ast.to_string(),
+            "query Q {\n  old @deprecated\n  new {\n    old @deprecated\n  }\n}\n"
+        );
+    }
+}
@@ -6,10 +6,12 @@ pub use crate::common::{Directive, Type, Value};
 use crate::position::Pos;
 
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document<'a>
 // where
 // T: Text<'a>,
 {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub definitions: Vec<Definition<'a>>,
 }
 
@@ -33,47 +35,88 @@ pub struct Document<'a>
 // }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Definition<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     SchemaDefinition(SchemaDefinition<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     TypeDefinition(TypeDefinition<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     TypeExtension(TypeExtension<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     DirectiveDefinition(DirectiveDefinition<'a>),
 }
 
+impl<'a> From<TypeDefinition<'a>> for Definition<'a> {
+    fn from(def: TypeDefinition<'a>) -> Definition<'a> {
+        Definition::TypeDefinition(def)
+    }
+}
+
+impl<'a> From<TypeExtension<'a>> for Definition<'a> {
+    fn from(ext: TypeExtension<'a>) -> Definition<'a> {
+        Definition::TypeExtension(ext)
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SchemaDefinition<'a> {
     pub position: Pos,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub query: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub mutation: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub subscription: Option<&'a str>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum TypeDefinition<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Scalar(ScalarType<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Object(ObjectType<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Interface(InterfaceType<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Union(UnionType<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Enum(EnumType<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     InputObject(InputObjectType<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum TypeExtension<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Scalar(ScalarTypeExtension<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Object(ObjectTypeExtension<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Interface(InterfaceTypeExtension<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Union(UnionTypeExtension<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Enum(EnumTypeExtension<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     InputObject(InputObjectTypeExtension<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScalarType<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
 }
 
@@ -92,9 +135,11 @@ impl<'a> ScalarType<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScalarTypeExtension<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
 }
 
@@ -112,12 +157,16 @@ impl<'a> ScalarTypeExtension<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectType<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub implements_interfaces: Vec<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub fields: Vec<Field<'a>>,
 }
 
@@ -138,11 +187,15 @@ impl<'a> ObjectType<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectTypeExtension<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub implements_interfaces: Vec<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub fields: Vec<Field<'a>>,
 }
 
@@ -162,32 +215,44 @@ impl<'a> ObjectTypeExtension<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub arguments: Vec<InputValue<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub field_type: Type<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InputValue<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub value_type: Type<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub default_value: Option<Value<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceType<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub implements_interfaces: Vec<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub fields: Vec<Field<'a>>,
 }
 
@@ -208,11 +273,15 @@ impl<'a> InterfaceType<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceTypeExtension<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub implements_interfaces: Vec<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub fields: Vec<Field<'a>>,
 }
 
@@ -232,11 +301,14 @@ impl<'a> InterfaceTypeExtension<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnionType<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub types: Vec<&'a str>,
 }
 
@@ -256,10 +328,13 @@ impl<'a> UnionType<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnionTypeExtension<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub types: Vec<&'a str>,
 }
 
@@ -278,11 +353,14 @@ impl<'a> UnionTypeExtension<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumType<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub values: Vec<EnumValue<'a>>,
 }
 
@@ -302,10 +380,12 @@ impl<'a> EnumType<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumValue<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
 }
 
@@ -324,10 +404,13 @@ impl<'a> EnumValue<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumTypeExtension<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub values: Vec<EnumValue<'a>>,
 }
 
@@ -346,11 +429,14 @@ impl<'a> EnumTypeExtension<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InputObjectType<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub fields: Vec<InputValue<'a>>,
 }
 
@@ -370,10 +456,13 @@ impl<'a> InputObjectType<'a>
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InputObjectTypeExtension<'a> {
     pub position: Pos,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub directives: Vec<Directive<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub fields: Vec<InputValue<'a>>,
 }
 
@@ -392,6 +481,8 @@ impl<'a> InputObjectTypeExtension<'a>
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
 pub enum DirectiveLocation {
     // executable
     Query,
@@ -418,10 +509,12 @@ pub enum DirectiveLocation {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DirectiveDefinition<'a> {
     pub position: Pos,
     pub description: Option<String>,
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub arguments: Vec<InputValue<'a>>,
     pub repeatable: bool,
     pub locations: Vec<DirectiveLocation>,
@@ -489,6 +582,7 @@ impl DirectiveLocation {
 }
 
 #[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[error("invalid directive location")]
 pub struct InvalidDirectiveLocation;
 
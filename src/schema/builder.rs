@@ -0,0 +1,689 @@
+//! Fluent API for constructing schema documents
+//!
+//! ```rust
+//! # extern crate graphql_parser;
+//! use graphql_parser::schema::builder::SchemaBuilder;
+//!
+//! let doc = SchemaBuilder::new()
+//!     .schema(|s| s.query("Query"))
+//!     .object("Query", |o| o
+//!         .field_with("user", "User", |f| f.arg("id", "ID!")))
+//!     .object("User", |o| o
+//!         .description("A registered user")
+//!         .field("id", "ID!")
+//!         .field("roles", "[Role!]!"))
+//!     .enum_type("Role", |e| e.value("ADMIN").value("USER"))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(doc.to_string(), r#"schema {
+//!   query: Query
+//! }
+//!
+//! type Query {
+//!   user(id: ID!): User
+//! }
+//!
+//! "A registered user"
+//! type User {
+//!   id: ID!
+//!   roles: [Role!]!
+//! }
+//!
+//! enum Role {
+//!   ADMIN
+//!   USER
+//! }
+//! "#);
+//! ```
+//!
+//! Type references are written in graphql syntax and parsed when added.
+//! Names and type references are checked when the document is built, all
+//! invalid ones are returned as [`BuildError`]s.
+use crate::position::Pos;
+use crate::query::builder::{check_name, check_type, DirectiveBuilder};
+use crate::schema::ast::*;
+
+pub use crate::query::builder::BuildError;
+
+// Methods shared by every builder of a node having a description and
+// directives, `$node` is the field holding the node being built
+macro_rules! impl_description_directives {
+    ($( $typ: ident => $node: ident, )+) => {
+        $(
+            impl<'a> $typ<'a> {
+                /// Sets the description
+                pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+                    self.$node.description = Some(description.into());
+                    self
+                }
+
+                /// Adds a directive
+                pub fn directive<F>(mut self, name: &'a str, build: F) -> Self
+                where
+                    F: FnOnce(DirectiveBuilder<'a>) -> DirectiveBuilder<'a>,
+                {
+                    let dir = build(DirectiveBuilder::new(name)).finish(&mut self.errors);
+                    self.$node.directives.push(dir);
+                    self
+                }
+            }
+        )+
+    };
+}
+
+/// Builds an argument or an input object field
+#[derive(Debug)]
+pub struct InputValueBuilder<'a> {
+    input: InputValue<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> InputValueBuilder<'a> {
+    fn new(name: &'a str, value_type: &'a str) -> InputValueBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        let value_type = check_type(value_type, &mut errors);
+        InputValueBuilder {
+            input: InputValue {
+                position: Pos::default(),
+                description: None,
+                name,
+                value_type,
+                default_value: None,
+                directives: Vec::new(),
+            },
+            errors,
+        }
+    }
+
+    /// Sets the default value
+    pub fn default<V: Into<Value<'a>>>(mut self, value: V) -> Self {
+        self.input.default_value = Some(value.into());
+        self
+    }
+}
+
+// Methods of the builders having a list of input values, `$list` is the
+// path to the list
+macro_rules! impl_input_values {
+    ($( $typ: ident => $method: ident, $method_with: ident, $( $list: ident ).+; )+) => {
+        $(
+            impl<'a> $typ<'a> {
+                /// Adds an input value with the type in graphql syntax
+                pub fn $method(self, name: &'a str, value_type: &'a str) -> Self {
+                    self.$method_with(name, value_type, |i| i)
+                }
+
+                /// Adds an input value with a default, description or directives
+                pub fn $method_with<F>(mut self, name: &'a str, value_type: &'a str, build: F)
+                    -> Self
+                where
+                    F: FnOnce(InputValueBuilder<'a>) -> InputValueBuilder<'a>,
+                {
+                    let input = build(InputValueBuilder::new(name, value_type));
+                    self.errors.extend(input.errors);
+                    self.$($list).+.push(input.input);
+                    self
+                }
+            }
+        )+
+    };
+}
+
+/// Builds a field of an object or an interface
+#[derive(Debug)]
+pub struct FieldBuilder<'a> {
+    field: Field<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> FieldBuilder<'a> {
+    fn new(name: &'a str, field_type: &'a str) -> FieldBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        let field_type = check_type(field_type, &mut errors);
+        FieldBuilder {
+            field: Field {
+                position: Pos::default(),
+                description: None,
+                name,
+                arguments: Vec::new(),
+                field_type,
+                directives: Vec::new(),
+            },
+            errors,
+        }
+    }
+}
+
+/// Builds an object or an interface type and their extensions
+#[derive(Debug)]
+pub struct ObjectBuilder<'a> {
+    object: ObjectType<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> ObjectBuilder<'a> {
+    fn new(name: &'a str) -> ObjectBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        ObjectBuilder {
+            object: ObjectType::new(name),
+            errors,
+        }
+    }
+
+    /// Adds an interface implemented by this type
+    pub fn implements(mut self, interface: &'a str) -> Self {
+        check_name(interface, &mut self.errors);
+        self.object.implements_interfaces.push(interface);
+        self
+    }
+
+    /// Adds a field without arguments
+    pub fn field(self, name: &'a str, field_type: &'a str) -> Self {
+        self.field_with(name, field_type, |f| f)
+    }
+
+    /// Adds a field with arguments, description or directives
+    pub fn field_with<F>(mut self, name: &'a str, field_type: &'a str, build: F) -> Self
+    where
+        F: FnOnce(FieldBuilder<'a>) -> FieldBuilder<'a>,
+    {
+        let field = build(FieldBuilder::new(name, field_type));
+        self.errors.extend(field.errors);
+        self.object.fields.push(field.field);
+        self
+    }
+}
+
+/// Builds an enum value
+#[derive(Debug)]
+pub struct EnumValueBuilder<'a> {
+    value: EnumValue<'a>,
+    errors: Vec<BuildError>,
+}
+
+/// Builds an enum type or an enum extension
+#[derive(Debug)]
+pub struct EnumBuilder<'a> {
+    enum_type: EnumType<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> EnumBuilder<'a> {
+    fn new(name: &'a str) -> EnumBuilder<'a> {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        EnumBuilder {
+            enum_type: EnumType::new(name),
+            errors,
+        }
+    }
+
+    /// Adds a value
+    pub fn value(self, name: &'a str) -> Self {
+        self.value_with(name, |v| v)
+    }
+
+    /// Adds a value with description or directives
+    pub fn value_with<F>(mut self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(EnumValueBuilder<'a>) -> EnumValueBuilder<'a>,
+    {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        let value = build(EnumValueBuilder {
+            value: EnumValue::new(name),
+            errors,
+        });
+        self.errors.extend(value.errors);
+        self.enum_type.values.push(value.value);
+        self
+    }
+}
+
+/// Builds an input object type or its extension
+#[derive(Debug)]
+pub struct InputObjectBuilder<'a> {
+    input: InputObjectType<'a>,
+    errors: Vec<BuildError>,
+}
+
+/// Builds a union type or its extension
+#[derive(Debug)]
+pub struct UnionBuilder<'a> {
+    union_type: UnionType<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> UnionBuilder<'a> {
+    /// Adds a member type
+    pub fn member(mut self, name: &'a str) -> Self {
+        check_name(name, &mut self.errors);
+        self.union_type.types.push(name);
+        self
+    }
+}
+
+/// Builds a scalar type or its extension
+#[derive(Debug)]
+pub struct ScalarBuilder<'a> {
+    scalar: ScalarType<'a>,
+    errors: Vec<BuildError>,
+}
+
+/// Builds a directive definition
+#[derive(Debug)]
+pub struct DirectiveDefinitionBuilder<'a> {
+    directive: DirectiveDefinition<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> DirectiveDefinitionBuilder<'a> {
+    /// Sets the description
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.directive.description = Some(description.into());
+        self
+    }
+
+    /// Allows the directive to be used more than once at a location
+    pub fn repeatable(mut self) -> Self {
+        self.directive.repeatable = true;
+        self
+    }
+
+    /// Adds a location where the directive may be used
+    pub fn location(mut self, location: DirectiveLocation) -> Self {
+        self.directive.locations.push(location);
+        self
+    }
+}
+
+/// Builds the `schema` definition
+#[derive(Debug)]
+pub struct SchemaDefinitionBuilder<'a> {
+    schema: SchemaDefinition<'a>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> SchemaDefinitionBuilder<'a> {
+    /// Sets the query root type
+    pub fn query(mut self, name: &'a str) -> Self {
+        check_name(name, &mut self.errors);
+        self.schema.query = Some(name);
+        self
+    }
+
+    /// Sets the mutation root type
+    pub fn mutation(mut self, name: &'a str) -> Self {
+        check_name(name, &mut self.errors);
+        self.schema.mutation = Some(name);
+        self
+    }
+
+    /// Sets the subscription root type
+    pub fn subscription(mut self, name: &'a str) -> Self {
+        check_name(name, &mut self.errors);
+        self.schema.subscription = Some(name);
+        self
+    }
+
+    /// Adds a directive
+    pub fn directive<F>(mut self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(DirectiveBuilder<'a>) -> DirectiveBuilder<'a>,
+    {
+        let dir = build(DirectiveBuilder::new(name)).finish(&mut self.errors);
+        self.schema.directives.push(dir);
+        self
+    }
+}
+
+impl_description_directives!(
+    InputValueBuilder => input,
+    FieldBuilder => field,
+    ObjectBuilder => object,
+    EnumValueBuilder => value,
+    EnumBuilder => enum_type,
+    InputObjectBuilder => input,
+    UnionBuilder => union_type,
+    ScalarBuilder => scalar,
+);
+
+impl_input_values!(
+    FieldBuilder => arg, arg_with, field.arguments;
+    InputObjectBuilder => field, field_with, input.fields;
+    DirectiveDefinitionBuilder => arg, arg_with, directive.arguments;
+);
+
+/// Builds a schema document
+#[derive(Debug, Default)]
+pub struct SchemaBuilder<'a> {
+    definitions: Vec<Definition<'a>>,
+    errors: Vec<BuildError>,
+}
+
+impl<'a> SchemaBuilder<'a> {
+    /// Starts an empty document
+    pub fn new() -> SchemaBuilder<'a> {
+        SchemaBuilder::default()
+    }
+
+    fn push<E>(mut self, errors: E, def: Definition<'a>) -> Self
+    where
+        E: IntoIterator<Item = BuildError>,
+    {
+        self.errors.extend(errors);
+        self.definitions.push(def);
+        self
+    }
+
+    /// Adds the `schema` definition
+    pub fn schema<F>(self, build: F) -> Self
+    where
+        F: FnOnce(SchemaDefinitionBuilder<'a>) -> SchemaDefinitionBuilder<'a>,
+    {
+        let b = build(SchemaDefinitionBuilder {
+            schema: SchemaDefinition::default(),
+            errors: Vec::new(),
+        });
+        self.push(b.errors, Definition::SchemaDefinition(b.schema))
+    }
+
+    /// Adds a scalar type
+    pub fn scalar<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(ScalarBuilder<'a>) -> ScalarBuilder<'a>,
+    {
+        let b = build(scalar_builder(name));
+        self.push(b.errors, TypeDefinition::Scalar(b.scalar).into())
+    }
+
+    /// Adds an object type
+    pub fn object<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(ObjectBuilder<'a>) -> ObjectBuilder<'a>,
+    {
+        let b = build(ObjectBuilder::new(name));
+        self.push(b.errors, TypeDefinition::Object(b.object).into())
+    }
+
+    /// Adds an interface type
+    pub fn interface<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(ObjectBuilder<'a>) -> ObjectBuilder<'a>,
+    {
+        let b = build(ObjectBuilder::new(name));
+        let o = b.object;
+        let iface = InterfaceType {
+            position: o.position,
+            description: o.description,
+            name: o.name,
+            implements_interfaces: o.implements_interfaces,
+            directives: o.directives,
+            fields: o.fields,
+        };
+        self.push(b.errors, TypeDefinition::Interface(iface).into())
+    }
+
+    /// Adds a union type
+    pub fn union<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(UnionBuilder<'a>) -> UnionBuilder<'a>,
+    {
+        let b = build(union_builder(name));
+        self.push(b.errors, TypeDefinition::Union(b.union_type).into())
+    }
+
+    /// Adds an enum type
+    pub fn enum_type<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(EnumBuilder<'a>) -> EnumBuilder<'a>,
+    {
+        let b = build(EnumBuilder::new(name));
+        self.push(b.errors, TypeDefinition::Enum(b.enum_type).into())
+    }
+
+    /// Adds an input object type
+    pub fn input<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(InputObjectBuilder<'a>) -> InputObjectBuilder<'a>,
+    {
+        let b = build(input_builder(name));
+        self.push(b.errors, TypeDefinition::InputObject(b.input).into())
+    }
+
+    /// Adds a directive definition
+    pub fn directive<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(DirectiveDefinitionBuilder<'a>) -> DirectiveDefinitionBuilder<'a>,
+    {
+        let mut errors = Vec::new();
+        check_name(name, &mut errors);
+        let b = build(DirectiveDefinitionBuilder {
+            directive: DirectiveDefinition::new(name),
+            errors,
+        });
+        self.push(b.errors, Definition::DirectiveDefinition(b.directive))
+    }
+
+    /// Adds an `extend scalar`, the description is ignored
+    pub fn extend_scalar<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(ScalarBuilder<'a>) -> ScalarBuilder<'a>,
+    {
+        let b = build(scalar_builder(name));
+        let ext = ScalarTypeExtension {
+            position: b.scalar.position,
+            name: b.scalar.name,
+            directives: b.scalar.directives,
+        };
+        self.push(b.errors, TypeExtension::Scalar(ext).into())
+    }
+
+    /// Adds an `extend type`, the description is ignored
+    pub fn extend_object<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(ObjectBuilder<'a>) -> ObjectBuilder<'a>,
+    {
+        let b = build(ObjectBuilder::new(name));
+        let o = b.object;
+        let ext = ObjectTypeExtension {
+            position: o.position,
+            name: o.name,
+            implements_interfaces: o.implements_interfaces,
+            directives: o.directives,
+            fields: o.fields,
+        };
+        self.push(b.errors, TypeExtension::Object(ext).into())
+    }
+
+    /// Adds an `extend interface`, the description is ignored
+    pub fn extend_interface<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(ObjectBuilder<'a>) -> ObjectBuilder<'a>,
+    {
+        let b = build(ObjectBuilder::new(name));
+        let o = b.object;
+        let ext = InterfaceTypeExtension {
+            position: o.position,
+            name: o.name,
+            implements_interfaces: o.implements_interfaces,
+            directives: o.directives,
+            fields: o.fields,
+        };
+        self.push(b.errors, TypeExtension::Interface(ext).into())
+    }
+
+    /// Adds an `extend union`, the description is ignored
+    pub fn extend_union<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(UnionBuilder<'a>) -> UnionBuilder<'a>,
+    {
+        let b = build(union_builder(name));
+        let u = b.union_type;
+        let ext = UnionTypeExtension {
+            position: u.position,
+            name: u.name,
+            directives: u.directives,
+            types: u.types,
+        };
+        self.push(b.errors, TypeExtension::Union(ext).into())
+    }
+
+    /// Adds an `extend enum`, the description is ignored
+    pub fn extend_enum<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(EnumBuilder<'a>) -> EnumBuilder<'a>,
+    {
+        let b = build(EnumBuilder::new(name));
+        let e = b.enum_type;
+        let ext = EnumTypeExtension {
+            position: e.position,
+            name: e.name,
+            directives: e.directives,
+            values: e.values,
+        };
+        self.push(b.errors, TypeExtension::Enum(ext).into())
+    }
+
+    /// Adds an `extend input`, the description is ignored
+    pub fn extend_input<F>(self, name: &'a str, build: F) -> Self
+    where
+        F: FnOnce(InputObjectBuilder<'a>) -> InputObjectBuilder<'a>,
+    {
+        let b = build(input_builder(name));
+        let i = b.input;
+        let ext = InputObjectTypeExtension {
+            position: i.position,
+            name: i.name,
+            directives: i.directives,
+            fields: i.fields,
+        };
+        self.push(b.errors, TypeExtension::InputObject(ext).into())
+    }
+
+    /// Returns the document or all invalid names and types encountered
+    pub fn build(self) -> Result<Document<'a>, Vec<BuildError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+        Ok(Document {
+            definitions: self.definitions,
+        })
+    }
+}
+
+fn scalar_builder(name: &str) -> ScalarBuilder<'_> {
+    let mut errors = Vec::new();
+    check_name(name, &mut errors);
+    ScalarBuilder {
+        scalar: ScalarType::new(name),
+        errors,
+    }
+}
+
+fn union_builder(name: &str) -> UnionBuilder<'_> {
+    let mut errors = Vec::new();
+    check_name(name, &mut errors);
+    UnionBuilder {
+        union_type: UnionType::new(name),
+        errors,
+    }
+}
+
+fn input_builder(name: &str) -> InputObjectBuilder<'_> {
+    let mut errors = Vec::new();
+    check_name(name, &mut errors);
+    InputObjectBuilder {
+        input: InputObjectType::new(name),
+        errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::parse_schema;
+
+    #[test]
+    fn all_definition_kinds() {
+        let doc = SchemaBuilder::new()
+            .scalar("Date", |s| s.description("ISO date"))
+            .interface("Node", |i| i.field("id", "ID!"))
+            .object("User", |o| {
+                o.implements("Node")
+                    .directive("key", |d| d.arg("fields", "id"))
+                    .field("id", "ID!")
+                    .field_with("friends", "[User!]", |f| {
+                        f.arg_with("first", "Int", |a| a.default(10))
+                            .directive("deprecated", |d| d)
+                    })
+            })
+            .union("Search", |u| u.member("User").member("Post"))
+            .input("Filter", |i| {
+                i.field_with("name", "String", |f| f.default("x"))
+            })
+            .enum_type("Role", |e| {
+                e.value_with("ADMIN", |v| v.description("All access"))
+                    .value("USER")
+            })
+            .directive("key", |d| {
+                d.arg("fields", "String!")
+                    .repeatable()
+                    .location(DirectiveLocation::Object)
+                    .location(DirectiveLocation::Interface)
+            })
+            .extend_object("User", |o| o.field("age", "Int"))
+            .extend_enum("Role", |e| e.value("GUEST"))
+            .extend_union("Search", |u| u.member("Comment"))
+            .extend_input("Filter", |i| i.field("age", "Int"))
+            .extend_interface("Node", |i| i.directive("dir", |d| d))
+            .extend_scalar("Date", |s| s.directive("dir", |d| d))
+            .build()
+            .unwrap();
+        let expected = parse_schema(
+            r#"
+            "ISO date"
+            scalar Date
+            interface Node { id: ID! }
+            type User implements Node @key(fields: "id") {
+                id: ID!
+                friends(first: Int = 10): [User!] @deprecated
+            }
+            union Search = User | Post
+            input Filter { name: String = "x" }
+            enum Role {
+                "All access"
+                ADMIN
+                USER
+            }
+            directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+            extend type User { age: Int }
+            extend enum Role { GUEST }
+            extend union Search = Comment
+            extend input Filter { age: Int }
+            extend interface Node @dir
+            extend scalar Date @dir
+            "#,
+        )
+        .unwrap();
+        assert_eq!(doc.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn invalid_names_and_types() {
+        let err = SchemaBuilder::new()
+            .object("User", |o| o.field("__ok", "ID!").field("bad-name", "ID"))
+            .build()
+            .unwrap_err();
+        assert_eq!(err, vec![BuildError::InvalidName("bad-name".into())]);
+
+        let err = SchemaBuilder::new()
+            .object("User", |o| o.field("id", "ID!!"))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err[..], [BuildError::InvalidType(ref typ, _)] if typ == "ID!!"));
+    }
+}
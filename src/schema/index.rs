@@ -0,0 +1,472 @@
+//! Lookups of schema definitions by name
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::schema::ast::*;
+use crate::schema::grammar::parse_schema;
+
+/// Built-in scalars, directives and introspection types
+///
+/// `__MetaFields` holds the fields which are implicitly defined on every
+/// composite type (`__typename`) or on the query type.
+const BUILTINS: &str = r#"
+scalar Int
+scalar Float
+scalar String
+scalar Boolean
+scalar ID
+
+directive @skip(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+directive @include(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+directive @deprecated(reason: String = "No longer supported")
+  on FIELD_DEFINITION | ARGUMENT_DEFINITION | INPUT_FIELD_DEFINITION | ENUM_VALUE
+directive @specifiedBy(url: String!) on SCALAR
+
+type __MetaFields {
+  __typename: String!
+  __schema: __Schema!
+  __type(name: String!): __Type
+}
+
+type __Schema {
+  description: String
+  types: [__Type!]!
+  queryType: __Type!
+  mutationType: __Type
+  subscriptionType: __Type
+  directives: [__Directive!]!
+}
+
+type __Type {
+  kind: __TypeKind!
+  name: String
+  description: String
+  specifiedByURL: String
+  fields(includeDeprecated: Boolean = false): [__Field!]
+  interfaces: [__Type!]
+  possibleTypes: [__Type!]
+  enumValues(includeDeprecated: Boolean = false): [__EnumValue!]
+  inputFields(includeDeprecated: Boolean = false): [__InputValue!]
+  ofType: __Type
+}
+
+enum __TypeKind {
+  SCALAR
+  OBJECT
+  INTERFACE
+  UNION
+  ENUM
+  INPUT_OBJECT
+  LIST
+  NON_NULL
+}
+
+type __Field {
+  name: String!
+  description: String
+  args(includeDeprecated: Boolean = false): [__InputValue!]!
+  type: __Type!
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __InputValue {
+  name: String!
+  description: String
+  type: __Type!
+  defaultValue: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __EnumValue {
+  name: String!
+  description: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __Directive {
+  name: String!
+  description: String
+  locations: [__DirectiveLocation!]!
+  args(includeDeprecated: Boolean = false): [__InputValue!]!
+  isRepeatable: Boolean!
+}
+
+enum __DirectiveLocation {
+  QUERY
+  MUTATION
+  SUBSCRIPTION
+  FIELD
+  FRAGMENT_DEFINITION
+  FRAGMENT_SPREAD
+  INLINE_FRAGMENT
+  VARIABLE_DEFINITION
+  SCHEMA
+  SCALAR
+  OBJECT
+  FIELD_DEFINITION
+  ARGUMENT_DEFINITION
+  INTERFACE
+  UNION
+  ENUM
+  ENUM_VALUE
+  INPUT_OBJECT
+  INPUT_FIELD_DEFINITION
+}
+"#;
+
+fn builtins() -> &'static Document<'static> {
+    static DOCUMENT: OnceLock<Document<'static>> = OnceLock::new();
+    DOCUMENT.get_or_init(|| parse_schema(BUILTINS).expect("built-in definitions are valid"))
+}
+
+/// Schema built from type system documents
+///
+/// Types and directives are indexed by name, so lookups don't scan the
+/// definitions. Built-in scalars, directives and introspection types are
+/// included, unless the documents define types or directives with the
+/// same names. Type extensions are ignored. When a name is defined several
+/// times, the first definition wins.
+///
+/// ```rust
+/// use graphql_parser::schema::{parse_schema, Schema};
+///
+/// let doc = parse_schema(r#"
+///     type Query { pets: [Pet] }
+///     interface Pet { name: String }
+///     type Dog implements Pet { name: String }
+/// "#).unwrap();
+/// let schema = Schema::new(&doc);
+/// assert_eq!(schema.query_type(), Some("Query"));
+/// assert_eq!(schema.possible_types("Pet"), vec!["Dog"]);
+/// assert!(schema.get_type("String").is_some());
+/// ```
+#[derive(Debug)]
+pub struct Schema<'b, 'a> {
+    types: HashMap<&'a str, &'b TypeDefinition<'a>>,
+    /// Names of the types, in order of definition
+    names: Vec<&'a str>,
+    directives: HashMap<&'a str, &'b DirectiveDefinition<'a>>,
+    /// Fields of `__MetaFields`
+    meta_fields: &'b [Field<'a>],
+    query: Option<&'a str>,
+    mutation: Option<&'a str>,
+    subscription: Option<&'a str>,
+}
+
+impl<'b, 'a> Schema<'b, 'a> {
+    /// Builds the schema from a single document
+    pub fn new(doc: &'b Document<'a>) -> Schema<'b, 'a> {
+        Schema::from_documents(Some(doc))
+    }
+
+    /// Builds the schema from several documents
+    ///
+    /// The first schema definition of the documents defines root types.
+    /// Without a schema definition, object types named `Query`, `Mutation`
+    /// and `Subscription` are used.
+    pub fn from_documents<I>(docs: I) -> Schema<'b, 'a>
+    where
+        I: IntoIterator<Item = &'b Document<'a>>,
+    {
+        let builtins: &'b Document<'a> = builtins();
+        let mut types = HashMap::new();
+        let mut names = Vec::new();
+        let mut directives = HashMap::new();
+        let mut meta_fields: &'b [Field<'a>] = &[];
+        let mut schema = None;
+        let definitions = docs
+            .into_iter()
+            .chain(Some(builtins))
+            .flat_map(|doc| &doc.definitions);
+        for def in definitions {
+            match def {
+                Definition::SchemaDefinition(def) => {
+                    schema.get_or_insert(def);
+                }
+                Definition::TypeDefinition(TypeDefinition::Object(obj))
+                    if obj.name == "__MetaFields" =>
+                {
+                    meta_fields = &obj.fields;
+                }
+                Definition::TypeDefinition(def) => {
+                    let name = type_name(def);
+                    if !types.contains_key(name) {
+                        types.insert(name, def);
+                        names.push(name);
+                    }
+                }
+                Definition::DirectiveDefinition(def) => {
+                    directives.entry(def.name).or_insert(def);
+                }
+                Definition::TypeExtension(_) => {}
+            }
+        }
+        let root = |name: &'a str| match types.get(name) {
+            Some(TypeDefinition::Object(_)) => Some(name),
+            _ => None,
+        };
+        let (query, mutation, subscription) = match schema {
+            Some(schema) => (schema.query, schema.mutation, schema.subscription),
+            None => (root("Query"), root("Mutation"), root("Subscription")),
+        };
+        Schema {
+            types,
+            names,
+            directives,
+            meta_fields,
+            query,
+            mutation,
+            subscription,
+        }
+    }
+
+    /// Returns the type definition, including built-in types
+    pub fn get_type(&self, name: &str) -> Option<&'b TypeDefinition<'a>> {
+        self.types.get(name).copied()
+    }
+
+    /// Returns the directive definition, including built-in directives
+    pub fn get_directive(&self, name: &str) -> Option<&'b DirectiveDefinition<'a>> {
+        self.directives.get(name).copied()
+    }
+
+    /// Iterates over type definitions in order of definition, built-in
+    /// types last
+    pub fn types(&self) -> impl Iterator<Item = &'b TypeDefinition<'a>> + '_ {
+        self.names.iter().map(move |name| self.types[name])
+    }
+
+    /// Returns the field of an object or interface type
+    ///
+    /// Introspection fields are returned for composite types, and for the
+    /// query type.
+    pub fn field(&self, type_name: &str, field_name: &str) -> Option<&'b Field<'a>> {
+        let meta = match field_name {
+            "__typename" => self.is_composite(type_name),
+            "__schema" | "__type" => self.query == Some(type_name),
+            _ => false,
+        };
+        let fields = if meta {
+            self.meta_fields
+        } else {
+            match self.get_type(type_name)? {
+                TypeDefinition::Object(obj) => &obj.fields,
+                TypeDefinition::Interface(iface) => &iface.fields,
+                _ => return None,
+            }
+        };
+        fields.iter().find(|field| field.name == field_name)
+    }
+
+    /// Returns true for object, interface and union types
+    pub fn is_composite(&self, name: &str) -> bool {
+        matches!(
+            self.get_type(name),
+            Some(TypeDefinition::Object(_))
+                | Some(TypeDefinition::Interface(_))
+                | Some(TypeDefinition::Union(_))
+        )
+    }
+
+    /// Returns true for interface and union types
+    pub fn is_abstract(&self, name: &str) -> bool {
+        matches!(
+            self.get_type(name),
+            Some(TypeDefinition::Interface(_)) | Some(TypeDefinition::Union(_))
+        )
+    }
+
+    /// Returns true for scalar and enum types
+    pub fn is_leaf(&self, name: &str) -> bool {
+        matches!(
+            self.get_type(name),
+            Some(TypeDefinition::Scalar(_)) | Some(TypeDefinition::Enum(_))
+        )
+    }
+
+    /// Returns true for scalar, enum and input object types
+    pub fn is_input(&self, name: &str) -> bool {
+        matches!(
+            self.get_type(name),
+            Some(TypeDefinition::Scalar(_))
+                | Some(TypeDefinition::Enum(_))
+                | Some(TypeDefinition::InputObject(_))
+        )
+    }
+
+    /// Returns names of object types which may be returned for the type
+    ///
+    /// Objects implementing an interface are in order of definition.
+    pub fn possible_types(&self, name: &str) -> Vec<&'a str> {
+        match self.get_type(name) {
+            Some(TypeDefinition::Object(obj)) => vec![obj.name],
+            Some(TypeDefinition::Union(union)) => union.types.clone(),
+            Some(TypeDefinition::Interface(_)) => self
+                .types()
+                .filter_map(|ty| match ty {
+                    TypeDefinition::Object(obj) if obj.implements_interfaces.contains(&name) => {
+                        Some(obj.name)
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns names of object and interface types implementing the
+    /// interface, in order of definition
+    pub fn implementations(&self, interface: &str) -> Vec<&'a str> {
+        self.types()
+            .filter_map(|ty| match ty {
+                TypeDefinition::Object(obj) if obj.implements_interfaces.contains(&interface) => {
+                    Some(obj.name)
+                }
+                TypeDefinition::Interface(iface)
+                    if iface.implements_interfaces.contains(&interface) =>
+                {
+                    Some(iface.name)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns true if values of type `sub` are valid where `sup` is
+    /// expected
+    ///
+    /// A non-null type is a subtype of its nullable type, and an object or
+    /// interface type is a subtype of the interfaces it implements and of
+    /// the unions it's a member of.
+    pub fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        match (sub, sup) {
+            (Type::NonNullType(sub), Type::NonNullType(sup)) => self.is_subtype(sub, sup),
+            (Type::NonNullType(sub), sup) => self.is_subtype(sub, sup),
+            (_, Type::NonNullType(_)) => false,
+            (Type::ListType(sub), Type::ListType(sup)) => self.is_subtype(sub, sup),
+            (Type::ListType(_), _) | (_, Type::ListType(_)) => false,
+            (Type::NamedType(sub), Type::NamedType(sup)) => {
+                if sub == sup {
+                    return true;
+                }
+                let implements = match self.get_type(sub) {
+                    Some(TypeDefinition::Object(obj)) => &obj.implements_interfaces,
+                    Some(TypeDefinition::Interface(iface)) => &iface.implements_interfaces,
+                    _ => return false,
+                };
+                match self.get_type(sup) {
+                    Some(TypeDefinition::Union(union)) => union.types.contains(sub),
+                    Some(TypeDefinition::Interface(_)) => implements.contains(sup),
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Returns the name of the query root type
+    pub fn query_type(&self) -> Option<&'a str> {
+        self.query
+    }
+
+    /// Returns the name of the mutation root type
+    pub fn mutation_type(&self) -> Option<&'a str> {
+        self.mutation
+    }
+
+    /// Returns the name of the subscription root type
+    pub fn subscription_type(&self) -> Option<&'a str> {
+        self.subscription
+    }
+}
+
+pub(crate) fn type_name<'a>(def: &TypeDefinition<'a>) -> &'a str {
+    match def {
+        TypeDefinition::Scalar(t) => t.name,
+        TypeDefinition::Object(t) => t.name,
+        TypeDefinition::Interface(t) => t.name,
+        TypeDefinition::Union(t) => t.name,
+        TypeDefinition::Enum(t) => t.name,
+        TypeDefinition::InputObject(t) => t.name,
+    }
+}
+
+pub(crate) fn type_directives<'b, 'a>(def: &'b TypeDefinition<'a>) -> &'b [Directive<'a>] {
+    match def {
+        TypeDefinition::Scalar(t) => &t.directives,
+        TypeDefinition::Object(t) => &t.directives,
+        TypeDefinition::Interface(t) => &t.directives,
+        TypeDefinition::Union(t) => &t.directives,
+        TypeDefinition::Enum(t) => &t.directives,
+        TypeDefinition::InputObject(t) => &t.directives,
+    }
+}
+
+/// Returns the innermost named type
+pub(crate) fn named_type<'a>(ty: &Type<'a>) -> &'a str {
+    match ty {
+        Type::NamedType(name) => name,
+        Type::ListType(ty) | Type::NonNullType(ty) => named_type(ty),
+    }
+}
+
+/// Returns true if the type is a list, possibly non-null
+pub(crate) fn is_list(ty: &Type) -> bool {
+    match ty {
+        Type::NamedType(_) => false,
+        Type::ListType(_) => true,
+        Type::NonNullType(ty) => is_list(ty),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Schema;
+    use crate::schema::{parse_schema, Type};
+
+    #[test]
+    fn lookups() {
+        let doc = parse_schema(
+            "type Query { node: Node } interface Node { id: ID }\n\
+             type B implements Node { id: ID } type A implements Node { id: ID }\n\
+             union U = A | B",
+        )
+        .unwrap();
+        let schema = Schema::new(&doc);
+        assert_eq!(schema.query_type(), Some("Query"));
+        assert_eq!(schema.mutation_type(), None);
+        assert_eq!(schema.possible_types("Node"), vec!["B", "A"]);
+        assert_eq!(schema.possible_types("U"), vec!["A", "B"]);
+        assert!(schema.is_leaf("ID") && schema.is_input("Boolean"));
+        assert!(schema.field("U", "__typename").is_some());
+        assert!(schema.field("Query", "__schema").is_some());
+        assert!(schema.field("A", "__schema").is_none());
+        assert!(schema.get_type("__MetaFields").is_none());
+        assert!(schema.get_directive("skip").is_some());
+    }
+
+    #[test]
+    fn documents() {
+        let types = parse_schema(
+            "interface Node { id: ID } interface Named implements Node { id: ID }
+             type A implements Named & Node { id: ID } union U = A",
+        )
+        .unwrap();
+        let root = parse_schema("schema { query: Root } type Root { node: Node }").unwrap();
+        let schema = Schema::from_documents(vec![&types, &root]);
+        assert_eq!(schema.query_type(), Some("Root"));
+        assert_eq!(schema.implementations("Node"), vec!["Named", "A"]);
+        assert_eq!(schema.types().next().map(super::type_name), Some("Node"));
+        let named = |name| Type::NamedType(name);
+        let non_null = |ty| Type::NonNullType(Box::new(ty));
+        let list = |ty| Type::ListType(Box::new(ty));
+        assert!(schema.is_subtype(&non_null(named("A")), &named("Node")));
+        assert!(schema.is_subtype(&list(named("A")), &list(named("U"))));
+        assert!(schema.is_subtype(&named("Named"), &named("Node")));
+        assert!(!schema.is_subtype(&named("Node"), &named("A")));
+        assert!(!schema.is_subtype(&named("A"), &non_null(named("A"))));
+        assert!(!schema.is_subtype(&list(named("A")), &named("A")));
+    }
+}
@@ -0,0 +1,479 @@
+//! Conversion of schema documents from and to graphql-js AST JSON
+use serde_json::{json, Value as Json};
+
+use crate::json::{self, node, JsonError, Node};
+use crate::schema::ast::*;
+
+/// Converts a schema document into graphql-js AST JSON
+pub fn to_graphql_js_json(doc: &Document) -> Json {
+    json!({
+        "kind": "Document",
+        "definitions": doc.definitions.iter().map(definition).collect::<Vec<_>>(),
+    })
+}
+
+/// Reads a schema document from graphql-js AST JSON
+///
+/// Names in the returned document borrow from `json`. Schema extensions
+/// have no counterpart in this crate's AST and are reported as errors.
+pub fn from_graphql_js_json(json: &Json) -> Result<Document<'_>, JsonError> {
+    let doc = Node::expect(json, "Document")?;
+    Ok(Document {
+        definitions: doc
+            .list("definitions")?
+            .iter()
+            .map(definition_from)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn definition(def: &Definition) -> Json {
+    match def {
+        Definition::SchemaDefinition(schema) => {
+            let operations = [
+                ("query", schema.query),
+                ("mutation", schema.mutation),
+                ("subscription", schema.subscription),
+            ];
+            json!({
+                "kind": "SchemaDefinition",
+                "directives": json::directives(&schema.directives),
+                "operationTypes": operations
+                    .iter()
+                    .filter_map(|&(operation, name)| name.map(|name| json!({
+                        "kind": "OperationTypeDefinition",
+                        "operation": operation,
+                        "type": json::named_type(name),
+                    })))
+                    .collect::<Vec<_>>(),
+                "loc": json::loc(schema.position),
+            })
+        }
+        Definition::TypeDefinition(def) => type_definition(def),
+        Definition::TypeExtension(ext) => type_extension(ext),
+        Definition::DirectiveDefinition(def) => node(json!({
+            "kind": "DirectiveDefinition",
+            "description": json::description(&def.description),
+            "name": json::name(def.name),
+            "arguments": input_values(&def.arguments),
+            "repeatable": def.repeatable,
+            "locations": def
+                .locations
+                .iter()
+                .map(|loc| json::name(loc.as_str()))
+                .collect::<Vec<_>>(),
+            "loc": json::loc(def.position),
+        })),
+    }
+}
+
+fn type_definition(def: &TypeDefinition) -> Json {
+    node(match def {
+        TypeDefinition::Scalar(scalar) => json!({
+            "kind": "ScalarTypeDefinition",
+            "description": json::description(&scalar.description),
+            "name": json::name(scalar.name),
+            "directives": json::directives(&scalar.directives),
+            "loc": json::loc(scalar.position),
+        }),
+        TypeDefinition::Object(obj) => json!({
+            "kind": "ObjectTypeDefinition",
+            "description": json::description(&obj.description),
+            "name": json::name(obj.name),
+            "interfaces": named_types(&obj.implements_interfaces),
+            "directives": json::directives(&obj.directives),
+            "fields": fields(&obj.fields),
+            "loc": json::loc(obj.position),
+        }),
+        TypeDefinition::Interface(iface) => json!({
+            "kind": "InterfaceTypeDefinition",
+            "description": json::description(&iface.description),
+            "name": json::name(iface.name),
+            "interfaces": named_types(&iface.implements_interfaces),
+            "directives": json::directives(&iface.directives),
+            "fields": fields(&iface.fields),
+            "loc": json::loc(iface.position),
+        }),
+        TypeDefinition::Union(union) => json!({
+            "kind": "UnionTypeDefinition",
+            "description": json::description(&union.description),
+            "name": json::name(union.name),
+            "directives": json::directives(&union.directives),
+            "types": named_types(&union.types),
+            "loc": json::loc(union.position),
+        }),
+        TypeDefinition::Enum(enm) => json!({
+            "kind": "EnumTypeDefinition",
+            "description": json::description(&enm.description),
+            "name": json::name(enm.name),
+            "directives": json::directives(&enm.directives),
+            "values": enum_values(&enm.values),
+            "loc": json::loc(enm.position),
+        }),
+        TypeDefinition::InputObject(input) => json!({
+            "kind": "InputObjectTypeDefinition",
+            "description": json::description(&input.description),
+            "name": json::name(input.name),
+            "directives": json::directives(&input.directives),
+            "fields": input_values(&input.fields),
+            "loc": json::loc(input.position),
+        }),
+    })
+}
+
+fn type_extension(ext: &TypeExtension) -> Json {
+    match ext {
+        TypeExtension::Scalar(scalar) => json!({
+            "kind": "ScalarTypeExtension",
+            "name": json::name(scalar.name),
+            "directives": json::directives(&scalar.directives),
+            "loc": json::loc(scalar.position),
+        }),
+        TypeExtension::Object(obj) => json!({
+            "kind": "ObjectTypeExtension",
+            "name": json::name(obj.name),
+            "interfaces": named_types(&obj.implements_interfaces),
+            "directives": json::directives(&obj.directives),
+            "fields": fields(&obj.fields),
+            "loc": json::loc(obj.position),
+        }),
+        TypeExtension::Interface(iface) => json!({
+            "kind": "InterfaceTypeExtension",
+            "name": json::name(iface.name),
+            "interfaces": named_types(&iface.implements_interfaces),
+            "directives": json::directives(&iface.directives),
+            "fields": fields(&iface.fields),
+            "loc": json::loc(iface.position),
+        }),
+        TypeExtension::Union(union) => json!({
+            "kind": "UnionTypeExtension",
+            "name": json::name(union.name),
+            "directives": json::directives(&union.directives),
+            "types": named_types(&union.types),
+            "loc": json::loc(union.position),
+        }),
+        TypeExtension::Enum(enm) => json!({
+            "kind": "EnumTypeExtension",
+            "name": json::name(enm.name),
+            "directives": json::directives(&enm.directives),
+            "values": enum_values(&enm.values),
+            "loc": json::loc(enm.position),
+        }),
+        TypeExtension::InputObject(input) => json!({
+            "kind": "InputObjectTypeExtension",
+            "name": json::name(input.name),
+            "directives": json::directives(&input.directives),
+            "fields": input_values(&input.fields),
+            "loc": json::loc(input.position),
+        }),
+    }
+}
+
+fn named_types(names: &[&str]) -> Json {
+    names.iter().map(|name| json::named_type(name)).collect()
+}
+
+fn fields(fields: &[Field]) -> Json {
+    fields
+        .iter()
+        .map(|field| {
+            node(json!({
+                "kind": "FieldDefinition",
+                "description": json::description(&field.description),
+                "name": json::name(field.name),
+                "arguments": input_values(&field.arguments),
+                "type": json::var_type(&field.field_type),
+                "directives": json::directives(&field.directives),
+                "loc": json::loc(field.position),
+            }))
+        })
+        .collect()
+}
+
+fn input_values(values: &[InputValue]) -> Json {
+    values
+        .iter()
+        .map(|val| {
+            node(json!({
+                "kind": "InputValueDefinition",
+                "description": json::description(&val.description),
+                "name": json::name(val.name),
+                "type": json::var_type(&val.value_type),
+                "defaultValue": val.default_value.as_ref().map(json::value),
+                "directives": json::directives(&val.directives),
+                "loc": json::loc(val.position),
+            }))
+        })
+        .collect()
+}
+
+fn enum_values(values: &[EnumValue]) -> Json {
+    values
+        .iter()
+        .map(|val| {
+            node(json!({
+                "kind": "EnumValueDefinition",
+                "description": json::description(&val.description),
+                "name": json::name(val.name),
+                "directives": json::directives(&val.directives),
+                "loc": json::loc(val.position),
+            }))
+        })
+        .collect()
+}
+
+fn definition_from(json: &Json) -> Result<Definition<'_>, JsonError> {
+    let def = Node::new(json, "TypeSystemDefinition")?;
+    let position = def.position();
+    let definition = match def.kind() {
+        "SchemaDefinition" => {
+            let mut schema = SchemaDefinition {
+                position,
+                directives: def.directives()?,
+                ..SchemaDefinition::default()
+            };
+            for json in def.list("operationTypes")? {
+                let op = Node::expect(json, "OperationTypeDefinition")?;
+                let name = Some(op.named_type("type")?);
+                match op.str("operation")? {
+                    "query" => schema.query = name,
+                    "mutation" => schema.mutation = name,
+                    "subscription" => schema.subscription = name,
+                    _ => return Err(op.invalid("operation")),
+                }
+            }
+            Definition::SchemaDefinition(schema)
+        }
+        "DirectiveDefinition" => Definition::DirectiveDefinition(DirectiveDefinition {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            arguments: input_values_from(def, "arguments")?,
+            repeatable: def.bool("repeatable")?,
+            locations: def
+                .list("locations")?
+                .iter()
+                .map(|json| {
+                    Node::expect(json, "Name")?
+                        .str("value")?
+                        .parse()
+                        .map_err(|_| def.invalid("locations"))
+                })
+                .collect::<Result<_, _>>()?,
+        }),
+        "ScalarTypeDefinition" => TypeDefinition::Scalar(ScalarType {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            directives: def.directives()?,
+        })
+        .into(),
+        "ObjectTypeDefinition" => TypeDefinition::Object(ObjectType {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            implements_interfaces: def.named_types("interfaces")?,
+            directives: def.directives()?,
+            fields: fields_from(def)?,
+        })
+        .into(),
+        "InterfaceTypeDefinition" => TypeDefinition::Interface(InterfaceType {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            implements_interfaces: def.named_types("interfaces")?,
+            directives: def.directives()?,
+            fields: fields_from(def)?,
+        })
+        .into(),
+        "UnionTypeDefinition" => TypeDefinition::Union(UnionType {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            directives: def.directives()?,
+            types: def.named_types("types")?,
+        })
+        .into(),
+        "EnumTypeDefinition" => TypeDefinition::Enum(EnumType {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            directives: def.directives()?,
+            values: enum_values_from(def)?,
+        })
+        .into(),
+        "InputObjectTypeDefinition" => TypeDefinition::InputObject(InputObjectType {
+            position,
+            description: def.description()?,
+            name: def.name("name")?,
+            directives: def.directives()?,
+            fields: input_values_from(def, "fields")?,
+        })
+        .into(),
+        "ScalarTypeExtension" => TypeExtension::Scalar(ScalarTypeExtension {
+            position,
+            name: def.name("name")?,
+            directives: def.directives()?,
+        })
+        .into(),
+        "ObjectTypeExtension" => TypeExtension::Object(ObjectTypeExtension {
+            position,
+            name: def.name("name")?,
+            implements_interfaces: def.named_types("interfaces")?,
+            directives: def.directives()?,
+            fields: fields_from(def)?,
+        })
+        .into(),
+        "InterfaceTypeExtension" => TypeExtension::Interface(InterfaceTypeExtension {
+            position,
+            name: def.name("name")?,
+            implements_interfaces: def.named_types("interfaces")?,
+            directives: def.directives()?,
+            fields: fields_from(def)?,
+        })
+        .into(),
+        "UnionTypeExtension" => TypeExtension::Union(UnionTypeExtension {
+            position,
+            name: def.name("name")?,
+            directives: def.directives()?,
+            types: def.named_types("types")?,
+        })
+        .into(),
+        "EnumTypeExtension" => TypeExtension::Enum(EnumTypeExtension {
+            position,
+            name: def.name("name")?,
+            directives: def.directives()?,
+            values: enum_values_from(def)?,
+        })
+        .into(),
+        "InputObjectTypeExtension" => TypeExtension::InputObject(InputObjectTypeExtension {
+            position,
+            name: def.name("name")?,
+            directives: def.directives()?,
+            fields: input_values_from(def, "fields")?,
+        })
+        .into(),
+        _ => return Err(def.unexpected("TypeSystemDefinition")),
+    };
+    Ok(definition)
+}
+
+fn fields_from<'a>(def: Node<'a>) -> Result<Vec<Field<'a>>, JsonError> {
+    def.list("fields")?
+        .iter()
+        .map(|json| {
+            let field = Node::expect(json, "FieldDefinition")?;
+            Ok(Field {
+                position: field.position(),
+                description: field.description()?,
+                name: field.name("name")?,
+                arguments: input_values_from(field, "arguments")?,
+                field_type: field.var_type("type")?,
+                directives: field.directives()?,
+            })
+        })
+        .collect()
+}
+
+fn input_values_from<'a>(
+    def: Node<'a>,
+    list: &'static str,
+) -> Result<Vec<InputValue<'a>>, JsonError> {
+    def.list(list)?
+        .iter()
+        .map(|json| {
+            let val = Node::expect(json, "InputValueDefinition")?;
+            Ok(InputValue {
+                position: val.position(),
+                description: val.description()?,
+                name: val.name("name")?,
+                value_type: val.var_type("type")?,
+                default_value: val.default_value()?,
+                directives: val.directives()?,
+            })
+        })
+        .collect()
+}
+
+fn enum_values_from<'a>(def: Node<'a>) -> Result<Vec<EnumValue<'a>>, JsonError> {
+    def.list("values")?
+        .iter()
+        .map(|json| {
+            let val = Node::expect(json, "EnumValueDefinition")?;
+            Ok(EnumValue {
+                position: val.position(),
+                description: val.description()?,
+                name: val.name("name")?,
+                directives: val.directives()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{from_graphql_js_json, to_graphql_js_json, JsonError};
+    use crate::schema::parse_schema;
+
+    #[test]
+    fn roundtrip() {
+        let doc = parse_schema(
+            r#"
+            schema @dir { query: Query mutation: Mutation }
+            "The query"
+            type Query implements Node @key(fields: "id") {
+                "Arguments"
+                user(id: ID!, first: Int = 10): [User!]! @deprecated
+            }
+            scalar Date
+            interface Node implements Entity { id: ID! }
+            union Result = A | B
+            enum Color { RED "green" GREEN }
+            input Filter { tags: [String] = ["a"] }
+            directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+            extend type Query { ping: Boolean }
+            extend scalar Date @dir
+            extend interface Node @dir
+            extend union Result = C
+            extend enum Color { BLUE }
+            extend input Filter { limit: Int }
+            "#,
+        )
+        .unwrap();
+        let json = to_graphql_js_json(&doc);
+        assert_eq!(from_graphql_js_json(&json).unwrap(), doc);
+    }
+
+    #[test]
+    fn layout() {
+        let doc = parse_schema("\"Desc\" type Query { a(b: Int): String }").unwrap();
+        let json = to_graphql_js_json(&doc);
+        let def = &json["definitions"][0];
+        assert_eq!(def["kind"], "ObjectTypeDefinition");
+        assert_eq!(
+            def["description"],
+            json!({"kind": "StringValue", "value": "Desc", "block": false})
+        );
+        let field = &def["fields"][0];
+        assert_eq!(field["kind"], "FieldDefinition");
+        assert_eq!(field["arguments"][0]["kind"], "InputValueDefinition");
+        assert_eq!(
+            field["type"],
+            json!({"kind": "NamedType", "name": {"kind": "Name", "value": "String"}})
+        );
+    }
+
+    #[test]
+    fn schema_extension_unsupported() {
+        let json = json!({"kind": "Document", "definitions": [{"kind": "SchemaExtension"}]});
+        assert_eq!(
+            from_graphql_js_json(&json).unwrap_err(),
+            JsonError::UnexpectedKind {
+                expected: "TypeSystemDefinition",
+                found: "SchemaExtension".into(),
+            }
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Merging of type extensions into type definitions
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::position::{Pos, Positions};
+use crate::schema::ast::*;
+use crate::schema::index::type_name;
+
+/// Error applying a type extension
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    #[error("{}extension of unknown type {:?}", Positions(&[*position]), name)]
+    UnknownType { name: String, position: Pos },
+    #[error("{}{} extension of {} {:?}", Positions(&[*position]), extension, kind, name)]
+    KindMismatch {
+        name: String,
+        /// Kind of the definition, e.g. `object`
+        kind: &'static str,
+        /// Kind of the extension
+        extension: &'static str,
+        position: Pos,
+    },
+    #[error("{}duplicate {} {:?} of {:?}", Positions(&[*position]), member, name, type_name)]
+    DuplicateMember {
+        type_name: String,
+        /// Kind of the member, e.g. `field` or `enum value`
+        member: &'static str,
+        name: String,
+        position: Pos,
+    },
+}
+
+/// Applies type extensions of the document to the type definitions
+///
+/// Fields, enum values, union members, interfaces and directives of each
+/// extension are appended to the definition of the extended type, which
+/// may be anywhere in the document. The returned document has no type
+/// extensions, other definitions keep their order.
+///
+/// All errors are returned when extensions can't be applied.
+///
+/// ```rust
+/// use graphql_parser::schema::{merge_extensions, parse_schema};
+///
+/// let doc = parse_schema(r#"
+///     type Query { a: Int }
+///     extend type Query { b: Int }
+/// "#).unwrap();
+/// let merged = merge_extensions(doc).unwrap();
+/// assert_eq!(merged.to_string(), "type Query {\n  a: Int\n  b: Int\n}\n");
+/// ```
+pub fn merge_extensions(doc: Document) -> Result<Document, Vec<MergeError>> {
+    let mut definitions = Vec::new();
+    let mut extensions = Vec::new();
+    for def in doc.definitions {
+        match def {
+            Definition::TypeExtension(ext) => extensions.push(ext),
+            def => definitions.push(def),
+        }
+    }
+    let mut types = HashMap::new();
+    for (idx, def) in definitions.iter().enumerate() {
+        if let Definition::TypeDefinition(def) = def {
+            types.entry(type_name(def)).or_insert(idx);
+        }
+    }
+    let mut errors = Vec::new();
+    for ext in extensions {
+        let (name, position) = (extension_name(&ext), extension_position(&ext));
+        let def = match types.get(name).map(|&idx| &mut definitions[idx]) {
+            Some(Definition::TypeDefinition(def)) => def,
+            _ => {
+                errors.push(MergeError::UnknownType {
+                    name: name.to_string(),
+                    position,
+                });
+                continue;
+            }
+        };
+        let mut merger = Merger {
+            errors: &mut errors,
+            type_name: name,
+            position,
+        };
+        match (def, ext) {
+            (TypeDefinition::Scalar(def), TypeExtension::Scalar(ext)) => {
+                def.directives.extend(ext.directives);
+            }
+            (TypeDefinition::Object(def), TypeExtension::Object(ext)) => {
+                merger.names(
+                    "interface",
+                    &mut def.implements_interfaces,
+                    ext.implements_interfaces,
+                );
+                merger.members("field", &mut def.fields, ext.fields, |f| {
+                    (f.name, f.position)
+                });
+                def.directives.extend(ext.directives);
+            }
+            (TypeDefinition::Interface(def), TypeExtension::Interface(ext)) => {
+                merger.names(
+                    "interface",
+                    &mut def.implements_interfaces,
+                    ext.implements_interfaces,
+                );
+                merger.members("field", &mut def.fields, ext.fields, |f| {
+                    (f.name, f.position)
+                });
+                def.directives.extend(ext.directives);
+            }
+            (TypeDefinition::Union(def), TypeExtension::Union(ext)) => {
+                merger.names("union member", &mut def.types, ext.types);
+                def.directives.extend(ext.directives);
+            }
+            (TypeDefinition::Enum(def), TypeExtension::Enum(ext)) => {
+                merger.members("enum value", &mut def.values, ext.values, |v| {
+                    (v.name, v.position)
+                });
+                def.directives.extend(ext.directives);
+            }
+            (TypeDefinition::InputObject(def), TypeExtension::InputObject(ext)) => {
+                merger.members("input field", &mut def.fields, ext.fields, |f| {
+                    (f.name, f.position)
+                });
+                def.directives.extend(ext.directives);
+            }
+            (def, ext) => errors.push(MergeError::KindMismatch {
+                name: name.to_string(),
+                kind: definition_kind(def),
+                extension: extension_kind(&ext),
+                position,
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(Document { definitions })
+    } else {
+        Err(errors)
+    }
+}
+
+struct Merger<'e, 'n> {
+    errors: &'e mut Vec<MergeError>,
+    type_name: &'n str,
+    /// Position of the extension
+    position: Pos,
+}
+
+impl<'e, 'n> Merger<'e, 'n> {
+    fn duplicate(&mut self, member: &'static str, name: &str, position: Pos) {
+        self.errors.push(MergeError::DuplicateMember {
+            type_name: self.type_name.to_string(),
+            member,
+            name: name.to_string(),
+            position,
+        });
+    }
+
+    fn members<'a, T, F>(&mut self, member: &'static str, items: &mut Vec<T>, added: Vec<T>, key: F)
+    where
+        F: Fn(&T) -> (&'a str, Pos),
+    {
+        for item in added {
+            let (name, position) = key(&item);
+            if items.iter().any(|other| key(other).0 == name) {
+                self.duplicate(member, name, position);
+            } else {
+                items.push(item);
+            }
+        }
+    }
+
+    /// Merges members without positions, errors are reported at the
+    /// position of the extension
+    fn names<'a>(&mut self, member: &'static str, names: &mut Vec<&'a str>, added: Vec<&'a str>) {
+        for name in added {
+            if names.contains(&name) {
+                let position = self.position;
+                self.duplicate(member, name, position);
+            } else {
+                names.push(name);
+            }
+        }
+    }
+}
+
+fn definition_kind(def: &TypeDefinition) -> &'static str {
+    match def {
+        TypeDefinition::Scalar(_) => "scalar",
+        TypeDefinition::Object(_) => "object",
+        TypeDefinition::Interface(_) => "interface",
+        TypeDefinition::Union(_) => "union",
+        TypeDefinition::Enum(_) => "enum",
+        TypeDefinition::InputObject(_) => "input object",
+    }
+}
+
+fn extension_kind(ext: &TypeExtension) -> &'static str {
+    match ext {
+        TypeExtension::Scalar(_) => "scalar",
+        TypeExtension::Object(_) => "object",
+        TypeExtension::Interface(_) => "interface",
+        TypeExtension::Union(_) => "union",
+        TypeExtension::Enum(_) => "enum",
+        TypeExtension::InputObject(_) => "input object",
+    }
+}
+
+fn extension_name<'a>(ext: &TypeExtension<'a>) -> &'a str {
+    match ext {
+        TypeExtension::Scalar(e) => e.name,
+        TypeExtension::Object(e) => e.name,
+        TypeExtension::Interface(e) => e.name,
+        TypeExtension::Union(e) => e.name,
+        TypeExtension::Enum(e) => e.name,
+        TypeExtension::InputObject(e) => e.name,
+    }
+}
+
+fn extension_position(ext: &TypeExtension) -> Pos {
+    match ext {
+        TypeExtension::Scalar(e) => e.position,
+        TypeExtension::Object(e) => e.position,
+        TypeExtension::Interface(e) => e.position,
+        TypeExtension::Union(e) => e.position,
+        TypeExtension::Enum(e) => e.position,
+        TypeExtension::InputObject(e) => e.position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_extensions;
+    use crate::schema::parse_schema;
+
+    #[test]
+    fn merges() {
+        let doc = parse_schema(
+            r#"
+            extend type Query implements Node @tag { b: Int }
+            type Query { a: Int }
+            interface Node { id: ID }
+            extend interface Node @key { name: String }
+            union U = Query
+            extend union U = Other
+            type Other { a: Int }
+            enum E { A }
+            extend enum E { B }
+            input In { a: Int }
+            extend input In { b: Int }
+            scalar S
+            extend scalar S @specifiedBy(url: "x")
+            "#,
+        )
+        .unwrap();
+        let expected = parse_schema(
+            r#"
+            type Query implements Node @tag { a: Int b: Int }
+            interface Node @key { id: ID name: String }
+            union U = Query | Other
+            type Other { a: Int }
+            enum E { A B }
+            input In { a: Int b: Int }
+            scalar S @specifiedBy(url: "x")
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            merge_extensions(doc).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let doc = parse_schema(
+            "type T implements I { a: Int }\n\
+             extend type Unknown { a: Int }\n\
+             extend enum T { A }\n\
+             extend type T implements I { b: Int a: String }\n\
+             enum E { A } extend enum E { B A }",
+        )
+        .unwrap();
+        let errors = merge_extensions(doc)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                r#"2:8: extension of unknown type "Unknown""#,
+                r#"3:8: enum extension of object "T""#,
+                r#"4:8: duplicate interface "I" of "T""#,
+                r#"4:37: duplicate field "a" of "T""#,
+                r#"5:32: duplicate enum value "A" of "E""#,
+            ]
+        );
+    }
+}
@@ -1,10 +1,23 @@
 //! Schema definition language AST and utility
 //!
 mod ast;
+pub mod builder;
 mod grammar;
+pub(crate) mod index;
+mod merge;
+mod semantic;
+mod validation;
 mod error;
 mod format;
+#[cfg(feature = "serde_json")]
+mod json;
+pub mod visit;
 
 pub use self::ast::*;
 pub use self::error::ParseError;
 pub use self::grammar::parse_schema;
+pub use self::index::Schema;
+pub use self::merge::{merge_extensions, MergeError};
+pub use self::validation::{validate, ValidationError};
+#[cfg(feature = "serde_json")]
+pub use self::json::{from_graphql_js_json, to_graphql_js_json};
@@ -0,0 +1,31 @@
+//! Semantic comparison of schema documents, see `crate::semantic`
+use crate::schema::ast::*;
+
+impl_semantic_struct! {
+    Document { definitions }
+    SchemaDefinition { directives, query, mutation, subscription }
+    ScalarType { description, name, directives }
+    ScalarTypeExtension { name, directives }
+    ObjectType { description, name, implements_interfaces, directives, fields }
+    ObjectTypeExtension { name, implements_interfaces, directives, fields }
+    Field { description, name, arguments, field_type, directives }
+    InputValue { description, name, value_type, default_value, directives }
+    InterfaceType { description, name, implements_interfaces, directives, fields }
+    InterfaceTypeExtension { name, implements_interfaces, directives, fields }
+    UnionType { description, name, directives, types }
+    UnionTypeExtension { name, directives, types }
+    EnumType { description, name, directives, values }
+    EnumValue { description, name, directives }
+    EnumTypeExtension { name, directives, values }
+    InputObjectType { description, name, directives, fields }
+    InputObjectTypeExtension { name, directives, fields }
+    DirectiveDefinition { description, name, arguments, repeatable, locations }
+}
+
+impl_semantic_enum! {
+    Definition { SchemaDefinition, TypeDefinition, TypeExtension, DirectiveDefinition }
+    TypeDefinition { Scalar, Object, Interface, Union, Enum, InputObject }
+    TypeExtension { Scalar, Object, Interface, Union, Enum, InputObject }
+}
+
+impl_semantic_exact!(Vec<DirectiveLocation>);
@@ -0,0 +1,841 @@
+//! Validation of type system documents
+//!
+//! Implements the type validation rules of the "Type System" section of
+//! the GraphQL specification:
+//!
+//! ```rust
+//! use graphql_parser::schema::{parse_schema, validate};
+//!
+//! let schema = parse_schema(r#"
+//!     type Query { pet: Pet }
+//!     interface Pet { name: String! }
+//!     type Dog implements Pet { name: Int }
+//! "#).unwrap();
+//! let errors = validate(&schema).unwrap_err();
+//! assert_eq!(errors[0].to_string(),
+//!     "4:31: Interface field Pet.name expects type String! but Dog.name is type Int.");
+//! ```
+//!
+//! Type extensions are ignored, apply them first with
+//! [`merge_extensions`](super::merge_extensions).
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::position::{Pos, Positions};
+use crate::query::validation::value_errors;
+use crate::schema::ast::*;
+use crate::schema::index::{named_type, type_name, Schema};
+
+/// A type system validation error
+///
+/// Messages follow the ones of the reference implementation.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{}{}", Positions(positions), message)]
+pub struct ValidationError {
+    pub message: String,
+    /// Positions of the definitions involved, empty when the error is
+    /// about something missing from the document
+    pub positions: Vec<Pos>,
+}
+
+/// Validates the type system document
+///
+/// All errors found are returned, in the order of the definitions.
+pub fn validate(doc: &Document) -> Result<(), Vec<ValidationError>> {
+    let mut checker = Checker {
+        schema: Schema::new(doc),
+        errors: Vec::new(),
+    };
+    checker.document(doc);
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+struct Checker<'b, 'a> {
+    schema: Schema<'b, 'a>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'b, 'a> Checker<'b, 'a> {
+    fn error(&mut self, positions: Vec<Pos>, message: String) {
+        self.errors.push(ValidationError { message, positions });
+    }
+
+    fn document(&mut self, doc: &'b Document<'a>) {
+        let mut schema = None;
+        let mut types = HashMap::new();
+        let mut directives = HashMap::new();
+        for def in &doc.definitions {
+            match def {
+                Definition::SchemaDefinition(def) => {
+                    if schema.is_some() {
+                        self.error(
+                            vec![def.position],
+                            "Must provide only one schema definition.".into(),
+                        );
+                        continue;
+                    }
+                    schema = Some(def);
+                    self.directives(&def.directives, DirectiveLocation::Schema);
+                }
+                Definition::TypeDefinition(def) => {
+                    let (name, position) = (type_name(def), type_position(def));
+                    if let Some(&first) = types.get(name) {
+                        self.error(
+                            vec![first, position],
+                            format!("There can be only one type named {:?}.", name),
+                        );
+                        continue;
+                    }
+                    types.insert(name, position);
+                    self.type_definition(def);
+                }
+                Definition::DirectiveDefinition(def) => {
+                    if let Some(&first) = directives.get(def.name) {
+                        self.error(
+                            vec![first, def.position],
+                            format!("There can be only one directive named \"@{}\".", def.name),
+                        );
+                        continue;
+                    }
+                    directives.insert(def.name, def.position);
+                    self.name(def.name, def.position);
+                    self.arguments(&format!("@{}", def.name), &def.arguments);
+                }
+                Definition::TypeExtension(_) => {}
+            }
+        }
+        self.root_types(schema);
+        self.input_cycles(doc);
+    }
+
+    fn root_types(&mut self, schema: Option<&'b SchemaDefinition<'a>>) {
+        let (roots, positions, provided) = match schema {
+            Some(def) => (
+                [def.query, def.mutation, def.subscription],
+                vec![def.position],
+                "",
+            ),
+            None => (
+                [Some("Query"), Some("Mutation"), Some("Subscription")],
+                Vec::new(),
+                " if provided",
+            ),
+        };
+        let kinds = ["Query", "Mutation", "Subscription"];
+        for (kind, root) in kinds.iter().zip(&roots) {
+            let name = match root {
+                Some(name) => name,
+                None if *kind == "Query" => {
+                    let message = "Query root type must be provided.".into();
+                    self.error(positions.clone(), message);
+                    continue;
+                }
+                None => continue,
+            };
+            match self.schema.get_type(name) {
+                Some(TypeDefinition::Object(_)) => {}
+                None if schema.is_some() => {
+                    self.error(positions.clone(), format!("Unknown type {:?}.", name))
+                }
+                None if *kind == "Query" => {
+                    let message = "Query root type must be provided.".into();
+                    self.error(positions.clone(), message);
+                }
+                None => {}
+                Some(def) => {
+                    let message = format!(
+                        "{} root type must be Object type{}, it cannot be {}.",
+                        kind, provided, name
+                    );
+                    let mut positions = positions.clone();
+                    positions.push(type_position(def));
+                    self.error(positions, message);
+                }
+            }
+        }
+    }
+
+    fn type_definition(&mut self, def: &'b TypeDefinition<'a>) {
+        self.name(type_name(def), type_position(def));
+        match def {
+            TypeDefinition::Scalar(scalar) => {
+                self.directives(&scalar.directives, DirectiveLocation::Scalar);
+            }
+            TypeDefinition::Object(obj) => {
+                self.directives(&obj.directives, DirectiveLocation::Object);
+                self.fields(obj.name, obj.position, &obj.fields);
+                self.interfaces(
+                    obj.name,
+                    obj.position,
+                    &obj.implements_interfaces,
+                    &obj.fields,
+                );
+            }
+            TypeDefinition::Interface(iface) => {
+                self.directives(&iface.directives, DirectiveLocation::Interface);
+                self.fields(iface.name, iface.position, &iface.fields);
+                self.interfaces(
+                    iface.name,
+                    iface.position,
+                    &iface.implements_interfaces,
+                    &iface.fields,
+                );
+            }
+            TypeDefinition::Union(union) => {
+                self.directives(&union.directives, DirectiveLocation::Union);
+                self.union(union);
+            }
+            TypeDefinition::Enum(enum_type) => {
+                self.directives(&enum_type.directives, DirectiveLocation::Enum);
+                self.enum_values(enum_type);
+            }
+            TypeDefinition::InputObject(input) => {
+                self.directives(&input.directives, DirectiveLocation::InputObject);
+                self.input_fields(input);
+            }
+        }
+    }
+
+    fn name(&mut self, name: &str, position: Pos) {
+        if name.starts_with("__") {
+            self.error(
+                vec![position],
+                format!(
+                    "Name {:?} must not begin with \"__\", which is reserved by \
+                     GraphQL introspection.",
+                    name
+                ),
+            );
+        }
+    }
+
+    /// Checks that the named type of `ty` exists and is an input or output
+    /// type, `subject` describes what has the type
+    fn field_type(&mut self, subject: &str, ty: &Type, input: bool, position: Pos) {
+        let name = named_type(ty);
+        let valid = match self.schema.get_type(name) {
+            None => {
+                self.error(vec![position], format!("Unknown type {:?}.", name));
+                return;
+            }
+            Some(TypeDefinition::InputObject(_)) => input,
+            Some(TypeDefinition::Object(_))
+            | Some(TypeDefinition::Interface(_))
+            | Some(TypeDefinition::Union(_)) => !input,
+            Some(TypeDefinition::Scalar(_)) | Some(TypeDefinition::Enum(_)) => true,
+        };
+        if !valid {
+            let kind = if input { "Input" } else { "Output" };
+            self.error(
+                vec![position],
+                format!(
+                    "The type of {} must be {} Type but got: {}.",
+                    subject, kind, ty
+                ),
+            );
+        }
+    }
+
+    fn fields(&mut self, type_name: &str, position: Pos, fields: &'b [Field<'a>]) {
+        if fields.is_empty() {
+            self.error(
+                vec![position],
+                format!("Type {} must define one or more fields.", type_name),
+            );
+        }
+        for (idx, field) in fields.iter().enumerate() {
+            let subject = format!("{}.{}", type_name, field.name);
+            if let Some(first) = fields[..idx].iter().find(|f| f.name == field.name) {
+                self.error(
+                    vec![first.position, field.position],
+                    format!("Field {:?} can only be defined once.", subject),
+                );
+                continue;
+            }
+            self.name(field.name, field.position);
+            self.field_type(&subject, &field.field_type, false, field.position);
+            self.arguments(&subject, &field.arguments);
+            self.directives(&field.directives, DirectiveLocation::FieldDefinition);
+        }
+    }
+
+    /// Checks arguments of a field (`owner` is `Type.field`) or a
+    /// directive (`owner` is `@directive`)
+    fn arguments(&mut self, owner: &str, arguments: &'b [InputValue<'a>]) {
+        for (idx, arg) in arguments.iter().enumerate() {
+            let subject = format!("{}({}:)", owner, arg.name);
+            if let Some(first) = arguments[..idx].iter().find(|a| a.name == arg.name) {
+                self.error(
+                    vec![first.position, arg.position],
+                    format!("Argument {:?} can only be defined once.", subject),
+                );
+                continue;
+            }
+            self.name(arg.name, arg.position);
+            self.field_type(&subject, &arg.value_type, true, arg.position);
+            self.default_value(&format!("Argument {:?}", subject), arg);
+            self.directives(&arg.directives, DirectiveLocation::ArgumentDefinition);
+        }
+    }
+
+    /// Checks that the default value is valid for the type of the argument
+    /// or input field, `subject` describes it
+    fn default_value(&mut self, subject: &str, input: &InputValue) {
+        if let Some(ref value) = input.default_value {
+            let mut messages = Vec::new();
+            value_errors(&self.schema, value, &input.value_type, &mut messages);
+            for message in messages {
+                self.error(
+                    vec![input.position],
+                    format!("{} has invalid default value: {}", subject, message),
+                );
+            }
+        }
+    }
+
+    fn interfaces(
+        &mut self,
+        type_name: &str,
+        position: Pos,
+        implements: &[&'a str],
+        fields: &'b [Field<'a>],
+    ) {
+        for (idx, &name) in implements.iter().enumerate() {
+            if implements[..idx].contains(&name) {
+                self.error(
+                    vec![position],
+                    format!("Type {} can only implement {} once.", type_name, name),
+                );
+                continue;
+            }
+            if name == type_name {
+                self.error(
+                    vec![position],
+                    format!(
+                        "Type {} cannot implement itself because it would create a \
+                         circular reference.",
+                        type_name
+                    ),
+                );
+                continue;
+            }
+            let iface = match self.schema.get_type(name) {
+                Some(TypeDefinition::Interface(iface)) => iface,
+                Some(_) => {
+                    self.error(
+                        vec![position],
+                        format!(
+                            "Type {} must only implement Interface types, it cannot \
+                             implement {}.",
+                            type_name, name
+                        ),
+                    );
+                    continue;
+                }
+                None => {
+                    self.error(vec![position], format!("Unknown type {:?}.", name));
+                    continue;
+                }
+            };
+            for &transitive in &iface.implements_interfaces {
+                if transitive == type_name {
+                    self.error(
+                        vec![position],
+                        format!(
+                            "Type {} cannot implement {} because it would create a circular \
+                             reference.",
+                            type_name, name
+                        ),
+                    );
+                } else if !implements.contains(&transitive) {
+                    self.error(
+                        vec![position],
+                        format!(
+                            "Type {} must implement {} because it is implemented by {}.",
+                            type_name, transitive, name
+                        ),
+                    );
+                }
+            }
+            self.interface_fields(type_name, position, fields, iface);
+        }
+    }
+
+    fn interface_fields(
+        &mut self,
+        type_name: &str,
+        position: Pos,
+        fields: &[Field],
+        iface: &InterfaceType,
+    ) {
+        for iface_field in &iface.fields {
+            let iface_subject = format!("{}.{}", iface.name, iface_field.name);
+            let field = match fields.iter().find(|f| f.name == iface_field.name) {
+                Some(field) => field,
+                None => {
+                    self.error(
+                        vec![position, iface_field.position],
+                        format!(
+                            "Interface field {} expected but {} does not provide it.",
+                            iface_subject, type_name
+                        ),
+                    );
+                    continue;
+                }
+            };
+            let subject = format!("{}.{}", type_name, field.name);
+            if !self
+                .schema
+                .is_subtype(&field.field_type, &iface_field.field_type)
+            {
+                self.error(
+                    vec![field.position],
+                    format!(
+                        "Interface field {} expects type {} but {} is type {}.",
+                        iface_subject, iface_field.field_type, subject, field.field_type
+                    ),
+                );
+            }
+            for iface_arg in &iface_field.arguments {
+                match field.arguments.iter().find(|a| a.name == iface_arg.name) {
+                    None => self.error(
+                        vec![field.position],
+                        format!(
+                            "Interface field argument {}({}:) expected but {} does not \
+                             provide it.",
+                            iface_subject, iface_arg.name, subject
+                        ),
+                    ),
+                    Some(arg) if arg.value_type != iface_arg.value_type => self.error(
+                        vec![arg.position],
+                        format!(
+                            "Interface field argument {}({}:) expects type {} but \
+                             {}({}:) is type {}.",
+                            iface_subject,
+                            iface_arg.name,
+                            iface_arg.value_type,
+                            subject,
+                            arg.name,
+                            arg.value_type
+                        ),
+                    ),
+                    Some(_) => {}
+                }
+            }
+            for arg in &field.arguments {
+                let required =
+                    matches!(arg.value_type, Type::NonNullType(_)) && arg.default_value.is_none();
+                if required && !iface_field.arguments.iter().any(|a| a.name == arg.name) {
+                    self.error(
+                        vec![arg.position],
+                        format!(
+                            "Object field {} includes required argument {} that is missing \
+                             from the Interface field {}.",
+                            subject, arg.name, iface_subject
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn union(&mut self, union: &UnionType) {
+        if union.types.is_empty() {
+            self.error(
+                vec![union.position],
+                format!(
+                    "Union type {} must define one or more member types.",
+                    union.name
+                ),
+            );
+        }
+        for (idx, &member) in union.types.iter().enumerate() {
+            if union.types[..idx].contains(&member) {
+                self.error(
+                    vec![union.position],
+                    format!(
+                        "Union type {} can only include type {} once.",
+                        union.name, member
+                    ),
+                );
+                continue;
+            }
+            match self.schema.get_type(member) {
+                Some(TypeDefinition::Object(_)) => {}
+                Some(_) => self.error(
+                    vec![union.position],
+                    format!(
+                        "Union type {} can only include Object types, it cannot include {}.",
+                        union.name, member
+                    ),
+                ),
+                None => self.error(vec![union.position], format!("Unknown type {:?}.", member)),
+            }
+        }
+    }
+
+    fn enum_values(&mut self, enum_type: &'b EnumType<'a>) {
+        if enum_type.values.is_empty() {
+            self.error(
+                vec![enum_type.position],
+                format!(
+                    "Enum type {} must define one or more values.",
+                    enum_type.name
+                ),
+            );
+        }
+        for (idx, value) in enum_type.values.iter().enumerate() {
+            if let Some(first) = enum_type.values[..idx]
+                .iter()
+                .find(|v| v.name == value.name)
+            {
+                self.error(
+                    vec![first.position, value.position],
+                    format!(
+                        "Enum value \"{}.{}\" can only be defined once.",
+                        enum_type.name, value.name
+                    ),
+                );
+                continue;
+            }
+            self.name(value.name, value.position);
+            if let "true" | "false" | "null" = value.name {
+                self.error(
+                    vec![value.position],
+                    format!(
+                        "Enum type {} cannot include value: {}.",
+                        enum_type.name, value.name
+                    ),
+                );
+            }
+            self.directives(&value.directives, DirectiveLocation::EnumValue);
+        }
+    }
+
+    fn input_fields(&mut self, input: &'b InputObjectType<'a>) {
+        if input.fields.is_empty() {
+            self.error(
+                vec![input.position],
+                format!(
+                    "Input Object type {} must define one or more fields.",
+                    input.name
+                ),
+            );
+        }
+        for (idx, field) in input.fields.iter().enumerate() {
+            let subject = format!("{}.{}", input.name, field.name);
+            if let Some(first) = input.fields[..idx].iter().find(|f| f.name == field.name) {
+                self.error(
+                    vec![first.position, field.position],
+                    format!("Field {:?} can only be defined once.", subject),
+                );
+                continue;
+            }
+            self.name(field.name, field.position);
+            self.field_type(&subject, &field.value_type, true, field.position);
+            self.default_value(&format!("Input field {:?}", subject), field);
+            self.directives(&field.directives, DirectiveLocation::InputFieldDefinition);
+        }
+    }
+
+    /// Checks that input objects don't reference themselves through
+    /// non-null fields, such values can't be written
+    fn input_cycles(&mut self, doc: &'b Document<'a>) {
+        struct Detector<'c, 'b, 'a> {
+            schema: &'c Schema<'b, 'a>,
+            errors: &'c mut Vec<ValidationError>,
+            visited: HashSet<&'a str>,
+            /// Fields followed from the input object the search started with
+            path: Vec<&'b InputValue<'a>>,
+            /// Index in `path` where each input object on the path is entered
+            entered: HashMap<&'a str, usize>,
+        }
+        impl<'c, 'b, 'a> Detector<'c, 'b, 'a> {
+            fn detect(&mut self, input: &'b InputObjectType<'a>) {
+                self.visited.insert(input.name);
+                self.entered.insert(input.name, self.path.len());
+                for field in &input.fields {
+                    let name = match field.value_type {
+                        Type::NonNullType(ref inner) => match **inner {
+                            Type::NamedType(name) => name,
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    let target = match self.schema.get_type(name) {
+                        Some(TypeDefinition::InputObject(target)) => target,
+                        _ => continue,
+                    };
+                    if let Some(&start) = self.entered.get(name) {
+                        let cycle = self.path[start..].iter().chain(Some(&field));
+                        let (names, positions): (Vec<_>, Vec<_>) =
+                            cycle.map(|field| (field.name, field.position)).unzip();
+                        self.errors.push(ValidationError {
+                            message: format!(
+                                "Cannot reference Input Object {:?} within itself through \
+                                 a series of non-null fields: \"{}\".",
+                                name,
+                                names.join(".")
+                            ),
+                            positions,
+                        });
+                    } else if !self.visited.contains(name) {
+                        self.path.push(field);
+                        self.detect(target);
+                        self.path.pop();
+                    }
+                }
+                self.entered.remove(input.name);
+            }
+        }
+        let mut detector = Detector {
+            schema: &self.schema,
+            errors: &mut self.errors,
+            visited: HashSet::new(),
+            path: Vec::new(),
+            entered: HashMap::new(),
+        };
+        for def in &doc.definitions {
+            if let Definition::TypeDefinition(TypeDefinition::InputObject(input)) = def {
+                if !detector.visited.contains(input.name) {
+                    detector.detect(input);
+                }
+            }
+        }
+    }
+
+    /// Checks directives applied to a type system definition
+    fn directives(&mut self, directives: &[Directive], location: DirectiveLocation) {
+        for (idx, dir) in directives.iter().enumerate() {
+            let def = match self.schema.get_directive(dir.name) {
+                Some(def) => def,
+                None => {
+                    self.error(
+                        vec![dir.position],
+                        format!("Unknown directive \"@{}\".", dir.name),
+                    );
+                    continue;
+                }
+            };
+            if !def.locations.contains(&location) {
+                self.error(
+                    vec![dir.position],
+                    format!(
+                        "Directive \"@{}\" may not be used on {}.",
+                        dir.name,
+                        location.as_str()
+                    ),
+                );
+            }
+            self.directive_arguments(dir, def);
+            if def.repeatable {
+                continue;
+            }
+            if let Some(first) = directives[..idx].iter().find(|d| d.name == dir.name) {
+                self.error(
+                    vec![first.position, dir.position],
+                    format!(
+                        "The directive \"@{}\" can only be used once at this location.",
+                        dir.name
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Checks arguments of a directive applied to a definition
+    fn directive_arguments(&mut self, dir: &Directive, def: &DirectiveDefinition) {
+        for (name, value) in &dir.arguments {
+            let arg = match def.arguments.iter().find(|arg| arg.name == *name) {
+                Some(arg) => arg,
+                None => {
+                    self.error(
+                        vec![dir.position],
+                        format!(
+                            "Unknown argument {:?} on directive \"@{}\".",
+                            name, dir.name
+                        ),
+                    );
+                    continue;
+                }
+            };
+            let mut messages = Vec::new();
+            value_errors(&self.schema, value, &arg.value_type, &mut messages);
+            for message in messages {
+                self.error(vec![dir.position], message);
+            }
+        }
+        for arg in &def.arguments {
+            let required =
+                matches!(arg.value_type, Type::NonNullType(_)) && arg.default_value.is_none();
+            if required && !dir.arguments.iter().any(|(name, _)| *name == arg.name) {
+                self.error(
+                    vec![dir.position],
+                    format!(
+                        "Directive \"@{}\" argument {:?} of type \"{}\" is required, but it \
+                         was not provided.",
+                        dir.name, arg.name, arg.value_type
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn type_position(def: &TypeDefinition) -> Pos {
+    match def {
+        TypeDefinition::Scalar(t) => t.position,
+        TypeDefinition::Object(t) => t.position,
+        TypeDefinition::Interface(t) => t.position,
+        TypeDefinition::Union(t) => t.position,
+        TypeDefinition::Enum(t) => t.position,
+        TypeDefinition::InputObject(t) => t.position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+    use crate::schema::parse_schema;
+
+    fn errors(schema: &str) -> Vec<String> {
+        let doc = parse_schema(schema).unwrap();
+        match validate(&doc) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            errors(
+                r#"
+                schema { query: Root }
+                directive @tag(name: String!) repeatable on OBJECT | FIELD_DEFINITION
+                type Root @tag(name: "a") @tag(name: "b") { node(id: ID!): Node pets: [Pet!] }
+                interface Node { id: ID! }
+                interface Pet implements Node { id: ID! name(full: Boolean): String }
+                type Dog implements Pet & Node {
+                    id: ID!
+                    name(full: Boolean, short: Boolean): String!
+                    friends: [Dog]
+                    old: Int @deprecated
+                }
+                union DogOrRoot = Dog | Root
+                enum Color { RED GREEN }
+                input Filter { color: Color = RED, next: Filter, list: [Filter!]! }
+                scalar Date @specifiedBy(url: "https://example.com")
+            "#
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn definitions() {
+        assert_eq!(
+            errors(
+                "type Query { a: Int }\n\
+                 type Query { b: Int }\n\
+                 type __Meta { a: Int }\n\
+                 enum E { A true A }\n\
+                 union U = Query | E | Query\n\
+                 input In { a: Query b: In! c: [Int] = [1, \"2\"] }\n\
+                 type Empty\n\
+                 directive @d(x: Int, x: Int) on FIELD\n\
+                 directive @d on FIELD\n\
+                 type D { a(x: Int = \"s\", e: E = B): Int }"
+            ),
+            vec![
+                r#"1:1, 2:1: There can be only one type named "Query"."#,
+                "3:1: Name \"__Meta\" must not begin with \"__\", which is reserved by GraphQL \
+                 introspection.",
+                r#"4:12: Enum type E cannot include value: true."#,
+                r#"4:10, 4:17: Enum value "E.A" can only be defined once."#,
+                r#"5:1: Union type U can only include Object types, it cannot include E."#,
+                r#"5:1: Union type U can only include type Query once."#,
+                r#"6:12: The type of In.a must be Input Type but got: Query."#,
+                "6:28: Input field \"In.c\" has invalid default value: Expected value of type \
+                 \"Int\", found \"2\".",
+                r#"7:1: Type Empty must define one or more fields."#,
+                r#"8:14, 8:22: Argument "@d(x:)" can only be defined once."#,
+                r#"8:1, 9:1: There can be only one directive named "@d"."#,
+                "10:12: Argument \"D.a(x:)\" has invalid default value: Expected value of type \
+                 \"Int\", found \"s\".",
+                "10:26: Argument \"D.a(e:)\" has invalid default value: Value \"B\" does not exist \
+                 in \"E\" enum.",
+                "6:21: Cannot reference Input Object \"In\" within itself through a series of \
+                 non-null fields: \"b\".",
+            ]
+        );
+    }
+
+    #[test]
+    fn interfaces() {
+        assert_eq!(
+            errors(
+                "type Query { a(x: Int): I }\n\
+                 interface I implements J { a(x: Int, y: Int): [Int] b: I }\n\
+                 interface J { b: J }\n\
+                 type T implements I & Query & I {\n\
+                 a(x: String, z: Int!): [Int!]!\n\
+                 c: Unknown\n\
+                 }\n\
+                 type U implements I & J { a(x: Int, y: Int): Int b: U }\n\
+                 interface K implements L { a: Int }\n\
+                 interface L implements K { a: Int }"
+            ),
+            vec![
+                r#"6:1: Unknown type "Unknown"."#,
+                r#"4:1: Type T must implement J because it is implemented by I."#,
+                "5:3: Interface field argument I.a(x:) expects type Int but T.a(x:) is type \
+                 String.",
+                r#"5:1: Interface field argument I.a(y:) expected but T.a does not provide it."#,
+                "5:14: Object field T.a includes required argument z that is missing from the \
+                 Interface field I.a.",
+                r#"4:1, 2:53: Interface field I.b expected but T does not provide it."#,
+                r#"4:1: Type T must only implement Interface types, it cannot implement Query."#,
+                r#"4:1: Type T can only implement I once."#,
+                r#"8:27: Interface field I.a expects type [Int] but U.a is type Int."#,
+                r#"9:1: Type K cannot implement L because it would create a circular reference."#,
+                r#"10:1: Type L cannot implement K because it would create a circular reference."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn root_types_and_directives() {
+        assert_eq!(
+            errors(
+                "schema @deprecated { query: Q mutation: M }\n\
+                 schema { query: Q }\n\
+                 scalar Q @skip(if: true) @specifiedBy(url: \"a\") @specifiedBy(url: \"b\")\n\
+                 directive @d(x: Int!) on OBJECT\n\
+                 type O @d { a: Int @deprecated(reason: 5) b: Int @deprecated(foo: 1) }"
+            ),
+            vec![
+                r#"1:8: Directive "@deprecated" may not be used on SCHEMA."#,
+                r#"2:1: Must provide only one schema definition."#,
+                r#"3:10: Directive "@skip" may not be used on SCALAR."#,
+                "3:26, 3:49: The directive \"@specifiedBy\" can only be used once at this \
+                 location.",
+                "5:8: Directive \"@d\" argument \"x\" of type \"Int!\" is required, but it was not \
+                 provided.",
+                r#"5:20: Expected value of type "String", found 5."#,
+                r#"5:50: Unknown argument "foo" on directive "@deprecated"."#,
+                r#"1:1, 3:1: Query root type must be Object type, it cannot be Q."#,
+                r#"1:1: Unknown type "M"."#,
+            ]
+        );
+        assert_eq!(
+            errors("type T { a: Int }"),
+            vec!["Query root type must be provided."]
+        );
+    }
+}
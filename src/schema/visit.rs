@@ -0,0 +1,1165 @@
+//! Traversal of the schema definition language AST
+//!
+//! This module mirrors [`query::visit`](../../query/visit/index.html):
+//! [`Visitor`] has `enter_*` and `leave_*` hooks for every definition,
+//! extension and member kind, [`VisitMut`] modifies nodes in place and
+//! [`Fold`] consumes a document and builds a new one.
+//!
+//! ```rust
+//! # extern crate graphql_parser;
+//! use graphql_parser::schema::{parse_schema, Field};
+//! use graphql_parser::schema::visit::{walk_document, Control, Visitor};
+//!
+//! struct Deprecated<'a>(Vec<&'a str>);
+//!
+//! impl<'a> Visitor<'a> for Deprecated<'a> {
+//!     fn enter_field(&mut self, field: &'a Field<'a>) -> Control {
+//!         if field.directives.iter().any(|d| d.name == "deprecated") {
+//!             self.0.push(field.name);
+//!         }
+//!         Control::Continue
+//!     }
+//! }
+//!
+//! let ast = parse_schema("type User { id: ID, login: String @deprecated }").unwrap();
+//! let mut found = Deprecated(Vec::new());
+//! walk_document(&mut found, &ast);
+//! assert_eq!(found.0, vec!["login"]);
+//! ```
+use crate::schema::ast::*;
+
+pub use crate::query::visit::Control;
+
+/// A read-only visitor of the schema AST
+///
+/// Every hook has a default implementation that does nothing. Returning
+/// [`Control::Skip`] from an `enter_*` hook skips the children of the node
+/// as well as the matching `leave_*` hook.
+#[allow(unused_variables)]
+pub trait Visitor<'a> {
+    fn enter_document(&mut self, doc: &'a Document<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_document(&mut self, doc: &'a Document<'a>) {}
+
+    fn enter_schema_definition(&mut self, def: &'a SchemaDefinition<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_schema_definition(&mut self, def: &'a SchemaDefinition<'a>) {}
+
+    fn enter_scalar_type(&mut self, ty: &'a ScalarType<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_scalar_type(&mut self, ty: &'a ScalarType<'a>) {}
+
+    fn enter_object_type(&mut self, ty: &'a ObjectType<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_object_type(&mut self, ty: &'a ObjectType<'a>) {}
+
+    fn enter_interface_type(&mut self, ty: &'a InterfaceType<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_interface_type(&mut self, ty: &'a InterfaceType<'a>) {}
+
+    fn enter_union_type(&mut self, ty: &'a UnionType<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_union_type(&mut self, ty: &'a UnionType<'a>) {}
+
+    fn enter_enum_type(&mut self, ty: &'a EnumType<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_enum_type(&mut self, ty: &'a EnumType<'a>) {}
+
+    fn enter_input_object_type(&mut self, ty: &'a InputObjectType<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_input_object_type(&mut self, ty: &'a InputObjectType<'a>) {}
+
+    fn enter_scalar_type_extension(&mut self, ext: &'a ScalarTypeExtension<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_scalar_type_extension(&mut self, ext: &'a ScalarTypeExtension<'a>) {}
+
+    fn enter_object_type_extension(&mut self, ext: &'a ObjectTypeExtension<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_object_type_extension(&mut self, ext: &'a ObjectTypeExtension<'a>) {}
+
+    fn enter_interface_type_extension(&mut self, ext: &'a InterfaceTypeExtension<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_interface_type_extension(&mut self, ext: &'a InterfaceTypeExtension<'a>) {}
+
+    fn enter_union_type_extension(&mut self, ext: &'a UnionTypeExtension<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_union_type_extension(&mut self, ext: &'a UnionTypeExtension<'a>) {}
+
+    fn enter_enum_type_extension(&mut self, ext: &'a EnumTypeExtension<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_enum_type_extension(&mut self, ext: &'a EnumTypeExtension<'a>) {}
+
+    fn enter_input_object_type_extension(
+        &mut self,
+        ext: &'a InputObjectTypeExtension<'a>,
+    ) -> Control {
+        Control::Continue
+    }
+    fn leave_input_object_type_extension(&mut self, ext: &'a InputObjectTypeExtension<'a>) {}
+
+    fn enter_directive_definition(&mut self, def: &'a DirectiveDefinition<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_directive_definition(&mut self, def: &'a DirectiveDefinition<'a>) {}
+
+    fn enter_field(&mut self, field: &'a Field<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_field(&mut self, field: &'a Field<'a>) {}
+
+    fn enter_input_value(&mut self, input: &'a InputValue<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_input_value(&mut self, input: &'a InputValue<'a>) {}
+
+    fn enter_enum_value(&mut self, value: &'a EnumValue<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_enum_value(&mut self, value: &'a EnumValue<'a>) {}
+
+    fn enter_directive(&mut self, dir: &'a Directive<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_directive(&mut self, dir: &'a Directive<'a>) {}
+
+    fn enter_value(&mut self, value: &'a Value<'a>) -> Control {
+        Control::Continue
+    }
+    fn leave_value(&mut self, value: &'a Value<'a>) {}
+}
+
+pub fn walk_document<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, doc: &'a Document<'a>) {
+    if visitor.enter_document(doc) == Control::Continue {
+        for def in &doc.definitions {
+            walk_definition(visitor, def);
+        }
+        visitor.leave_document(doc);
+    }
+}
+
+pub fn walk_definition<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, def: &'a Definition<'a>) {
+    match *def {
+        Definition::SchemaDefinition(ref s) => walk_schema_definition(visitor, s),
+        Definition::TypeDefinition(ref t) => walk_type_definition(visitor, t),
+        Definition::TypeExtension(ref e) => walk_type_extension(visitor, e),
+        Definition::DirectiveDefinition(ref d) => walk_directive_definition(visitor, d),
+    }
+}
+
+pub fn walk_schema_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &'a SchemaDefinition<'a>,
+) {
+    if visitor.enter_schema_definition(def) == Control::Continue {
+        walk_directives(visitor, &def.directives);
+        visitor.leave_schema_definition(def);
+    }
+}
+
+pub fn walk_type_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &'a TypeDefinition<'a>,
+) {
+    match *def {
+        TypeDefinition::Scalar(ref ty) => {
+            if visitor.enter_scalar_type(ty) == Control::Continue {
+                walk_directives(visitor, &ty.directives);
+                visitor.leave_scalar_type(ty);
+            }
+        }
+        TypeDefinition::Object(ref ty) => {
+            if visitor.enter_object_type(ty) == Control::Continue {
+                walk_directives(visitor, &ty.directives);
+                walk_fields(visitor, &ty.fields);
+                visitor.leave_object_type(ty);
+            }
+        }
+        TypeDefinition::Interface(ref ty) => {
+            if visitor.enter_interface_type(ty) == Control::Continue {
+                walk_directives(visitor, &ty.directives);
+                walk_fields(visitor, &ty.fields);
+                visitor.leave_interface_type(ty);
+            }
+        }
+        TypeDefinition::Union(ref ty) => {
+            if visitor.enter_union_type(ty) == Control::Continue {
+                walk_directives(visitor, &ty.directives);
+                visitor.leave_union_type(ty);
+            }
+        }
+        TypeDefinition::Enum(ref ty) => {
+            if visitor.enter_enum_type(ty) == Control::Continue {
+                walk_directives(visitor, &ty.directives);
+                walk_enum_values(visitor, &ty.values);
+                visitor.leave_enum_type(ty);
+            }
+        }
+        TypeDefinition::InputObject(ref ty) => {
+            if visitor.enter_input_object_type(ty) == Control::Continue {
+                walk_directives(visitor, &ty.directives);
+                walk_input_values(visitor, &ty.fields);
+                visitor.leave_input_object_type(ty);
+            }
+        }
+    }
+}
+
+pub fn walk_type_extension<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &'a TypeExtension<'a>,
+) {
+    match *ext {
+        TypeExtension::Scalar(ref ext) => {
+            if visitor.enter_scalar_type_extension(ext) == Control::Continue {
+                walk_directives(visitor, &ext.directives);
+                visitor.leave_scalar_type_extension(ext);
+            }
+        }
+        TypeExtension::Object(ref ext) => {
+            if visitor.enter_object_type_extension(ext) == Control::Continue {
+                walk_directives(visitor, &ext.directives);
+                walk_fields(visitor, &ext.fields);
+                visitor.leave_object_type_extension(ext);
+            }
+        }
+        TypeExtension::Interface(ref ext) => {
+            if visitor.enter_interface_type_extension(ext) == Control::Continue {
+                walk_directives(visitor, &ext.directives);
+                walk_fields(visitor, &ext.fields);
+                visitor.leave_interface_type_extension(ext);
+            }
+        }
+        TypeExtension::Union(ref ext) => {
+            if visitor.enter_union_type_extension(ext) == Control::Continue {
+                walk_directives(visitor, &ext.directives);
+                visitor.leave_union_type_extension(ext);
+            }
+        }
+        TypeExtension::Enum(ref ext) => {
+            if visitor.enter_enum_type_extension(ext) == Control::Continue {
+                walk_directives(visitor, &ext.directives);
+                walk_enum_values(visitor, &ext.values);
+                visitor.leave_enum_type_extension(ext);
+            }
+        }
+        TypeExtension::InputObject(ref ext) => {
+            if visitor.enter_input_object_type_extension(ext) == Control::Continue {
+                walk_directives(visitor, &ext.directives);
+                walk_input_values(visitor, &ext.fields);
+                visitor.leave_input_object_type_extension(ext);
+            }
+        }
+    }
+}
+
+pub fn walk_directive_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &'a DirectiveDefinition<'a>,
+) {
+    if visitor.enter_directive_definition(def) == Control::Continue {
+        walk_input_values(visitor, &def.arguments);
+        visitor.leave_directive_definition(def);
+    }
+}
+
+pub fn walk_fields<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, fields: &'a [Field<'a>]) {
+    for field in fields {
+        walk_field(visitor, field);
+    }
+}
+
+pub fn walk_field<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, field: &'a Field<'a>) {
+    if visitor.enter_field(field) == Control::Continue {
+        walk_input_values(visitor, &field.arguments);
+        walk_directives(visitor, &field.directives);
+        visitor.leave_field(field);
+    }
+}
+
+pub fn walk_input_values<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    inputs: &'a [InputValue<'a>],
+) {
+    for input in inputs {
+        walk_input_value(visitor, input);
+    }
+}
+
+pub fn walk_input_value<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, input: &'a InputValue<'a>) {
+    if visitor.enter_input_value(input) == Control::Continue {
+        if let Some(ref value) = input.default_value {
+            walk_value(visitor, value);
+        }
+        walk_directives(visitor, &input.directives);
+        visitor.leave_input_value(input);
+    }
+}
+
+pub fn walk_enum_values<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, values: &'a [EnumValue<'a>]) {
+    for value in values {
+        if visitor.enter_enum_value(value) == Control::Continue {
+            walk_directives(visitor, &value.directives);
+            visitor.leave_enum_value(value);
+        }
+    }
+}
+
+pub fn walk_directives<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, dirs: &'a [Directive<'a>]) {
+    for dir in dirs {
+        if visitor.enter_directive(dir) == Control::Continue {
+            for (_, value) in &dir.arguments {
+                walk_value(visitor, value);
+            }
+            visitor.leave_directive(dir);
+        }
+    }
+}
+
+/// Walks the value and all nested list items and object fields
+pub fn walk_value<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, value: &'a Value<'a>) {
+    if visitor.enter_value(value) == Control::Continue {
+        match *value {
+            Value::List(ref items) => {
+                for item in items {
+                    walk_value(visitor, item);
+                }
+            }
+            Value::Object(ref fields) => {
+                for item in fields.values() {
+                    walk_value(visitor, item);
+                }
+            }
+            _ => {}
+        }
+        visitor.leave_value(value);
+    }
+}
+
+/// A visitor that modifies the schema AST in place
+///
+/// Each `visit_*_mut` method defaults to the matching `walk_*_mut` function
+/// which visits the children of the node.
+pub trait VisitMut<'a> {
+    fn visit_document_mut(&mut self, doc: &mut Document<'a>) {
+        walk_document_mut(self, doc)
+    }
+    fn visit_definition_mut(&mut self, def: &mut Definition<'a>) {
+        walk_definition_mut(self, def)
+    }
+    fn visit_schema_definition_mut(&mut self, def: &mut SchemaDefinition<'a>) {
+        walk_schema_definition_mut(self, def)
+    }
+    fn visit_type_definition_mut(&mut self, def: &mut TypeDefinition<'a>) {
+        walk_type_definition_mut(self, def)
+    }
+    fn visit_scalar_type_mut(&mut self, ty: &mut ScalarType<'a>) {
+        walk_scalar_type_mut(self, ty)
+    }
+    fn visit_object_type_mut(&mut self, ty: &mut ObjectType<'a>) {
+        walk_object_type_mut(self, ty)
+    }
+    fn visit_interface_type_mut(&mut self, ty: &mut InterfaceType<'a>) {
+        walk_interface_type_mut(self, ty)
+    }
+    fn visit_union_type_mut(&mut self, ty: &mut UnionType<'a>) {
+        walk_union_type_mut(self, ty)
+    }
+    fn visit_enum_type_mut(&mut self, ty: &mut EnumType<'a>) {
+        walk_enum_type_mut(self, ty)
+    }
+    fn visit_input_object_type_mut(&mut self, ty: &mut InputObjectType<'a>) {
+        walk_input_object_type_mut(self, ty)
+    }
+    fn visit_type_extension_mut(&mut self, ext: &mut TypeExtension<'a>) {
+        walk_type_extension_mut(self, ext)
+    }
+    fn visit_scalar_type_extension_mut(&mut self, ext: &mut ScalarTypeExtension<'a>) {
+        walk_scalar_type_extension_mut(self, ext)
+    }
+    fn visit_object_type_extension_mut(&mut self, ext: &mut ObjectTypeExtension<'a>) {
+        walk_object_type_extension_mut(self, ext)
+    }
+    fn visit_interface_type_extension_mut(&mut self, ext: &mut InterfaceTypeExtension<'a>) {
+        walk_interface_type_extension_mut(self, ext)
+    }
+    fn visit_union_type_extension_mut(&mut self, ext: &mut UnionTypeExtension<'a>) {
+        walk_union_type_extension_mut(self, ext)
+    }
+    fn visit_enum_type_extension_mut(&mut self, ext: &mut EnumTypeExtension<'a>) {
+        walk_enum_type_extension_mut(self, ext)
+    }
+    fn visit_input_object_type_extension_mut(&mut self, ext: &mut InputObjectTypeExtension<'a>) {
+        walk_input_object_type_extension_mut(self, ext)
+    }
+    fn visit_directive_definition_mut(&mut self, def: &mut DirectiveDefinition<'a>) {
+        walk_directive_definition_mut(self, def)
+    }
+    fn visit_field_mut(&mut self, field: &mut Field<'a>) {
+        walk_field_mut(self, field)
+    }
+    fn visit_input_value_mut(&mut self, input: &mut InputValue<'a>) {
+        walk_input_value_mut(self, input)
+    }
+    fn visit_enum_value_mut(&mut self, value: &mut EnumValue<'a>) {
+        walk_enum_value_mut(self, value)
+    }
+    fn visit_directive_mut(&mut self, dir: &mut Directive<'a>) {
+        walk_directive_mut(self, dir)
+    }
+    fn visit_value_mut(&mut self, value: &mut Value<'a>) {
+        walk_value_mut(self, value)
+    }
+}
+
+pub fn walk_document_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, doc: &mut Document<'a>) {
+    for def in &mut doc.definitions {
+        visitor.visit_definition_mut(def);
+    }
+}
+
+pub fn walk_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &mut Definition<'a>,
+) {
+    match *def {
+        Definition::SchemaDefinition(ref mut s) => visitor.visit_schema_definition_mut(s),
+        Definition::TypeDefinition(ref mut t) => visitor.visit_type_definition_mut(t),
+        Definition::TypeExtension(ref mut e) => visitor.visit_type_extension_mut(e),
+        Definition::DirectiveDefinition(ref mut d) => visitor.visit_directive_definition_mut(d),
+    }
+}
+
+pub fn walk_schema_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &mut SchemaDefinition<'a>,
+) {
+    walk_directives_mut(visitor, &mut def.directives);
+}
+
+pub fn walk_type_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &mut TypeDefinition<'a>,
+) {
+    match *def {
+        TypeDefinition::Scalar(ref mut ty) => visitor.visit_scalar_type_mut(ty),
+        TypeDefinition::Object(ref mut ty) => visitor.visit_object_type_mut(ty),
+        TypeDefinition::Interface(ref mut ty) => visitor.visit_interface_type_mut(ty),
+        TypeDefinition::Union(ref mut ty) => visitor.visit_union_type_mut(ty),
+        TypeDefinition::Enum(ref mut ty) => visitor.visit_enum_type_mut(ty),
+        TypeDefinition::InputObject(ref mut ty) => visitor.visit_input_object_type_mut(ty),
+    }
+}
+
+pub fn walk_scalar_type_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ty: &mut ScalarType<'a>,
+) {
+    walk_directives_mut(visitor, &mut ty.directives);
+}
+
+pub fn walk_object_type_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ty: &mut ObjectType<'a>,
+) {
+    walk_directives_mut(visitor, &mut ty.directives);
+    walk_fields_mut(visitor, &mut ty.fields);
+}
+
+pub fn walk_interface_type_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ty: &mut InterfaceType<'a>,
+) {
+    walk_directives_mut(visitor, &mut ty.directives);
+    walk_fields_mut(visitor, &mut ty.fields);
+}
+
+pub fn walk_union_type_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, ty: &mut UnionType<'a>) {
+    walk_directives_mut(visitor, &mut ty.directives);
+}
+
+pub fn walk_enum_type_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, ty: &mut EnumType<'a>) {
+    walk_directives_mut(visitor, &mut ty.directives);
+    walk_enum_values_mut(visitor, &mut ty.values);
+}
+
+pub fn walk_input_object_type_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ty: &mut InputObjectType<'a>,
+) {
+    walk_directives_mut(visitor, &mut ty.directives);
+    walk_input_values_mut(visitor, &mut ty.fields);
+}
+
+pub fn walk_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut TypeExtension<'a>,
+) {
+    match *ext {
+        TypeExtension::Scalar(ref mut ext) => visitor.visit_scalar_type_extension_mut(ext),
+        TypeExtension::Object(ref mut ext) => visitor.visit_object_type_extension_mut(ext),
+        TypeExtension::Interface(ref mut ext) => visitor.visit_interface_type_extension_mut(ext),
+        TypeExtension::Union(ref mut ext) => visitor.visit_union_type_extension_mut(ext),
+        TypeExtension::Enum(ref mut ext) => visitor.visit_enum_type_extension_mut(ext),
+        TypeExtension::InputObject(ref mut ext) => {
+            visitor.visit_input_object_type_extension_mut(ext)
+        }
+    }
+}
+
+pub fn walk_scalar_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut ScalarTypeExtension<'a>,
+) {
+    walk_directives_mut(visitor, &mut ext.directives);
+}
+
+pub fn walk_object_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut ObjectTypeExtension<'a>,
+) {
+    walk_directives_mut(visitor, &mut ext.directives);
+    walk_fields_mut(visitor, &mut ext.fields);
+}
+
+pub fn walk_interface_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut InterfaceTypeExtension<'a>,
+) {
+    walk_directives_mut(visitor, &mut ext.directives);
+    walk_fields_mut(visitor, &mut ext.fields);
+}
+
+pub fn walk_union_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut UnionTypeExtension<'a>,
+) {
+    walk_directives_mut(visitor, &mut ext.directives);
+}
+
+pub fn walk_enum_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut EnumTypeExtension<'a>,
+) {
+    walk_directives_mut(visitor, &mut ext.directives);
+    walk_enum_values_mut(visitor, &mut ext.values);
+}
+
+pub fn walk_input_object_type_extension_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    ext: &mut InputObjectTypeExtension<'a>,
+) {
+    walk_directives_mut(visitor, &mut ext.directives);
+    walk_input_values_mut(visitor, &mut ext.fields);
+}
+
+pub fn walk_directive_definition_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    def: &mut DirectiveDefinition<'a>,
+) {
+    walk_input_values_mut(visitor, &mut def.arguments);
+}
+
+pub fn walk_fields_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, fields: &mut [Field<'a>]) {
+    for field in fields {
+        visitor.visit_field_mut(field);
+    }
+}
+
+pub fn walk_field_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, field: &mut Field<'a>) {
+    walk_input_values_mut(visitor, &mut field.arguments);
+    walk_directives_mut(visitor, &mut field.directives);
+}
+
+pub fn walk_input_values_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    inputs: &mut [InputValue<'a>],
+) {
+    for input in inputs {
+        visitor.visit_input_value_mut(input);
+    }
+}
+
+pub fn walk_input_value_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    input: &mut InputValue<'a>,
+) {
+    if let Some(ref mut value) = input.default_value {
+        visitor.visit_value_mut(value);
+    }
+    walk_directives_mut(visitor, &mut input.directives);
+}
+
+pub fn walk_enum_values_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    values: &mut [EnumValue<'a>],
+) {
+    for value in values {
+        visitor.visit_enum_value_mut(value);
+    }
+}
+
+pub fn walk_enum_value_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    value: &mut EnumValue<'a>,
+) {
+    walk_directives_mut(visitor, &mut value.directives);
+}
+
+pub fn walk_directives_mut<'a, V: VisitMut<'a> + ?Sized>(
+    visitor: &mut V,
+    dirs: &mut [Directive<'a>],
+) {
+    for dir in dirs {
+        visitor.visit_directive_mut(dir);
+    }
+}
+
+pub fn walk_directive_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, dir: &mut Directive<'a>) {
+    for (_, value) in &mut dir.arguments {
+        visitor.visit_value_mut(value);
+    }
+}
+
+pub fn walk_value_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, value: &mut Value<'a>) {
+    match *value {
+        Value::List(ref mut items) => {
+            for item in items {
+                visitor.visit_value_mut(item);
+            }
+        }
+        Value::Object(ref mut fields) => {
+            for item in fields.values_mut() {
+                visitor.visit_value_mut(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A transformation that consumes the schema AST and builds a new one
+///
+/// Each `fold_*` method defaults to the matching `fold_*` function which
+/// folds the children of the node. Methods for items of a list (definitions,
+/// fields, input values, enum values and directives) return `None` to drop
+/// the item.
+pub trait Fold<'a> {
+    fn fold_document(&mut self, doc: Document<'a>) -> Document<'a> {
+        fold_document(self, doc)
+    }
+    fn fold_definition(&mut self, def: Definition<'a>) -> Option<Definition<'a>> {
+        fold_definition(self, def)
+    }
+    fn fold_schema_definition(&mut self, def: SchemaDefinition<'a>) -> SchemaDefinition<'a> {
+        fold_schema_definition(self, def)
+    }
+    fn fold_type_definition(&mut self, def: TypeDefinition<'a>) -> TypeDefinition<'a> {
+        fold_type_definition(self, def)
+    }
+    fn fold_scalar_type(&mut self, ty: ScalarType<'a>) -> ScalarType<'a> {
+        fold_scalar_type(self, ty)
+    }
+    fn fold_object_type(&mut self, ty: ObjectType<'a>) -> ObjectType<'a> {
+        fold_object_type(self, ty)
+    }
+    fn fold_interface_type(&mut self, ty: InterfaceType<'a>) -> InterfaceType<'a> {
+        fold_interface_type(self, ty)
+    }
+    fn fold_union_type(&mut self, ty: UnionType<'a>) -> UnionType<'a> {
+        fold_union_type(self, ty)
+    }
+    fn fold_enum_type(&mut self, ty: EnumType<'a>) -> EnumType<'a> {
+        fold_enum_type(self, ty)
+    }
+    fn fold_input_object_type(&mut self, ty: InputObjectType<'a>) -> InputObjectType<'a> {
+        fold_input_object_type(self, ty)
+    }
+    fn fold_type_extension(&mut self, ext: TypeExtension<'a>) -> TypeExtension<'a> {
+        fold_type_extension(self, ext)
+    }
+    fn fold_scalar_type_extension(
+        &mut self,
+        ext: ScalarTypeExtension<'a>,
+    ) -> ScalarTypeExtension<'a> {
+        fold_scalar_type_extension(self, ext)
+    }
+    fn fold_object_type_extension(
+        &mut self,
+        ext: ObjectTypeExtension<'a>,
+    ) -> ObjectTypeExtension<'a> {
+        fold_object_type_extension(self, ext)
+    }
+    fn fold_interface_type_extension(
+        &mut self,
+        ext: InterfaceTypeExtension<'a>,
+    ) -> InterfaceTypeExtension<'a> {
+        fold_interface_type_extension(self, ext)
+    }
+    fn fold_union_type_extension(&mut self, ext: UnionTypeExtension<'a>) -> UnionTypeExtension<'a> {
+        fold_union_type_extension(self, ext)
+    }
+    fn fold_enum_type_extension(&mut self, ext: EnumTypeExtension<'a>) -> EnumTypeExtension<'a> {
+        fold_enum_type_extension(self, ext)
+    }
+    fn fold_input_object_type_extension(
+        &mut self,
+        ext: InputObjectTypeExtension<'a>,
+    ) -> InputObjectTypeExtension<'a> {
+        fold_input_object_type_extension(self, ext)
+    }
+    fn fold_directive_definition(
+        &mut self,
+        def: DirectiveDefinition<'a>,
+    ) -> DirectiveDefinition<'a> {
+        fold_directive_definition(self, def)
+    }
+    fn fold_field(&mut self, field: Field<'a>) -> Option<Field<'a>> {
+        fold_field(self, field)
+    }
+    fn fold_input_value(&mut self, input: InputValue<'a>) -> Option<InputValue<'a>> {
+        fold_input_value(self, input)
+    }
+    fn fold_enum_value(&mut self, value: EnumValue<'a>) -> Option<EnumValue<'a>> {
+        fold_enum_value(self, value)
+    }
+    fn fold_directive(&mut self, dir: Directive<'a>) -> Option<Directive<'a>> {
+        fold_directive(self, dir)
+    }
+    fn fold_value(&mut self, value: Value<'a>) -> Value<'a> {
+        fold_value(self, value)
+    }
+}
+
+pub fn fold_document<'a, F: Fold<'a> + ?Sized>(folder: &mut F, doc: Document<'a>) -> Document<'a> {
+    Document {
+        definitions: doc
+            .definitions
+            .into_iter()
+            .filter_map(|def| folder.fold_definition(def))
+            .collect(),
+    }
+}
+
+pub fn fold_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    def: Definition<'a>,
+) -> Option<Definition<'a>> {
+    Some(match def {
+        Definition::SchemaDefinition(s) => {
+            Definition::SchemaDefinition(folder.fold_schema_definition(s))
+        }
+        Definition::TypeDefinition(t) => Definition::TypeDefinition(folder.fold_type_definition(t)),
+        Definition::TypeExtension(e) => Definition::TypeExtension(folder.fold_type_extension(e)),
+        Definition::DirectiveDefinition(d) => {
+            Definition::DirectiveDefinition(folder.fold_directive_definition(d))
+        }
+    })
+}
+
+fn fold_directives<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    dirs: Vec<Directive<'a>>,
+) -> Vec<Directive<'a>> {
+    dirs.into_iter()
+        .filter_map(|dir| folder.fold_directive(dir))
+        .collect()
+}
+
+fn fold_fields<'a, F: Fold<'a> + ?Sized>(folder: &mut F, fields: Vec<Field<'a>>) -> Vec<Field<'a>> {
+    fields
+        .into_iter()
+        .filter_map(|field| folder.fold_field(field))
+        .collect()
+}
+
+fn fold_input_values<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    inputs: Vec<InputValue<'a>>,
+) -> Vec<InputValue<'a>> {
+    inputs
+        .into_iter()
+        .filter_map(|input| folder.fold_input_value(input))
+        .collect()
+}
+
+fn fold_enum_values<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    values: Vec<EnumValue<'a>>,
+) -> Vec<EnumValue<'a>> {
+    values
+        .into_iter()
+        .filter_map(|value| folder.fold_enum_value(value))
+        .collect()
+}
+
+pub fn fold_schema_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    def: SchemaDefinition<'a>,
+) -> SchemaDefinition<'a> {
+    SchemaDefinition {
+        directives: fold_directives(folder, def.directives),
+        ..def
+    }
+}
+
+pub fn fold_type_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    def: TypeDefinition<'a>,
+) -> TypeDefinition<'a> {
+    match def {
+        TypeDefinition::Scalar(ty) => TypeDefinition::Scalar(folder.fold_scalar_type(ty)),
+        TypeDefinition::Object(ty) => TypeDefinition::Object(folder.fold_object_type(ty)),
+        TypeDefinition::Interface(ty) => TypeDefinition::Interface(folder.fold_interface_type(ty)),
+        TypeDefinition::Union(ty) => TypeDefinition::Union(folder.fold_union_type(ty)),
+        TypeDefinition::Enum(ty) => TypeDefinition::Enum(folder.fold_enum_type(ty)),
+        TypeDefinition::InputObject(ty) => {
+            TypeDefinition::InputObject(folder.fold_input_object_type(ty))
+        }
+    }
+}
+
+pub fn fold_scalar_type<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ty: ScalarType<'a>,
+) -> ScalarType<'a> {
+    ScalarType {
+        directives: fold_directives(folder, ty.directives),
+        ..ty
+    }
+}
+
+pub fn fold_object_type<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ty: ObjectType<'a>,
+) -> ObjectType<'a> {
+    ObjectType {
+        directives: fold_directives(folder, ty.directives),
+        fields: fold_fields(folder, ty.fields),
+        ..ty
+    }
+}
+
+pub fn fold_interface_type<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ty: InterfaceType<'a>,
+) -> InterfaceType<'a> {
+    InterfaceType {
+        directives: fold_directives(folder, ty.directives),
+        fields: fold_fields(folder, ty.fields),
+        ..ty
+    }
+}
+
+pub fn fold_union_type<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ty: UnionType<'a>,
+) -> UnionType<'a> {
+    UnionType {
+        directives: fold_directives(folder, ty.directives),
+        ..ty
+    }
+}
+
+pub fn fold_enum_type<'a, F: Fold<'a> + ?Sized>(folder: &mut F, ty: EnumType<'a>) -> EnumType<'a> {
+    EnumType {
+        directives: fold_directives(folder, ty.directives),
+        values: fold_enum_values(folder, ty.values),
+        ..ty
+    }
+}
+
+pub fn fold_input_object_type<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ty: InputObjectType<'a>,
+) -> InputObjectType<'a> {
+    InputObjectType {
+        directives: fold_directives(folder, ty.directives),
+        fields: fold_input_values(folder, ty.fields),
+        ..ty
+    }
+}
+
+pub fn fold_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: TypeExtension<'a>,
+) -> TypeExtension<'a> {
+    match ext {
+        TypeExtension::Scalar(ext) => TypeExtension::Scalar(folder.fold_scalar_type_extension(ext)),
+        TypeExtension::Object(ext) => TypeExtension::Object(folder.fold_object_type_extension(ext)),
+        TypeExtension::Interface(ext) => {
+            TypeExtension::Interface(folder.fold_interface_type_extension(ext))
+        }
+        TypeExtension::Union(ext) => TypeExtension::Union(folder.fold_union_type_extension(ext)),
+        TypeExtension::Enum(ext) => TypeExtension::Enum(folder.fold_enum_type_extension(ext)),
+        TypeExtension::InputObject(ext) => {
+            TypeExtension::InputObject(folder.fold_input_object_type_extension(ext))
+        }
+    }
+}
+
+pub fn fold_scalar_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: ScalarTypeExtension<'a>,
+) -> ScalarTypeExtension<'a> {
+    ScalarTypeExtension {
+        directives: fold_directives(folder, ext.directives),
+        ..ext
+    }
+}
+
+pub fn fold_object_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: ObjectTypeExtension<'a>,
+) -> ObjectTypeExtension<'a> {
+    ObjectTypeExtension {
+        directives: fold_directives(folder, ext.directives),
+        fields: fold_fields(folder, ext.fields),
+        ..ext
+    }
+}
+
+pub fn fold_interface_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: InterfaceTypeExtension<'a>,
+) -> InterfaceTypeExtension<'a> {
+    InterfaceTypeExtension {
+        directives: fold_directives(folder, ext.directives),
+        fields: fold_fields(folder, ext.fields),
+        ..ext
+    }
+}
+
+pub fn fold_union_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: UnionTypeExtension<'a>,
+) -> UnionTypeExtension<'a> {
+    UnionTypeExtension {
+        directives: fold_directives(folder, ext.directives),
+        ..ext
+    }
+}
+
+pub fn fold_enum_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: EnumTypeExtension<'a>,
+) -> EnumTypeExtension<'a> {
+    EnumTypeExtension {
+        directives: fold_directives(folder, ext.directives),
+        values: fold_enum_values(folder, ext.values),
+        ..ext
+    }
+}
+
+pub fn fold_input_object_type_extension<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    ext: InputObjectTypeExtension<'a>,
+) -> InputObjectTypeExtension<'a> {
+    InputObjectTypeExtension {
+        directives: fold_directives(folder, ext.directives),
+        fields: fold_input_values(folder, ext.fields),
+        ..ext
+    }
+}
+
+pub fn fold_directive_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    def: DirectiveDefinition<'a>,
+) -> DirectiveDefinition<'a> {
+    DirectiveDefinition {
+        arguments: fold_input_values(folder, def.arguments),
+        ..def
+    }
+}
+
+pub fn fold_field<'a, F: Fold<'a> + ?Sized>(folder: &mut F, field: Field<'a>) -> Option<Field<'a>> {
+    Some(Field {
+        arguments: fold_input_values(folder, field.arguments),
+        directives: fold_directives(folder, field.directives),
+        ..field
+    })
+}
+
+pub fn fold_input_value<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    input: InputValue<'a>,
+) -> Option<InputValue<'a>> {
+    Some(InputValue {
+        default_value: input.default_value.map(|value| folder.fold_value(value)),
+        directives: fold_directives(folder, input.directives),
+        ..input
+    })
+}
+
+pub fn fold_enum_value<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    value: EnumValue<'a>,
+) -> Option<EnumValue<'a>> {
+    Some(EnumValue {
+        directives: fold_directives(folder, value.directives),
+        ..value
+    })
+}
+
+pub fn fold_directive<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    dir: Directive<'a>,
+) -> Option<Directive<'a>> {
+    Some(Directive {
+        position: dir.position,
+        name: dir.name,
+        arguments: dir
+            .arguments
+            .into_iter()
+            .map(|(name, value)| (name, folder.fold_value(value)))
+            .collect(),
+    })
+}
+
+pub fn fold_value<'a, F: Fold<'a> + ?Sized>(folder: &mut F, value: Value<'a>) -> Value<'a> {
+    match value {
+        Value::List(items) => {
+            Value::List(items.into_iter().map(|v| folder.fold_value(v)).collect())
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, v)| (name, folder.fold_value(v)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::parse_schema;
+
+    const SCHEMA: &str = "\
+        type Query { user(id: ID = 1 @x): User }
+        extend type Query { me: User @internal }
+        enum Role { ADMIN @internal USER }
+        directive @internal on FIELD_DEFINITION | ENUM_VALUE
+    ";
+
+    #[derive(Default)]
+    struct Counter {
+        names: Vec<String>,
+    }
+
+    impl<'a> Visitor<'a> for Counter {
+        fn enter_object_type(&mut self, ty: &'a ObjectType<'a>) -> Control {
+            self.names.push(format!("type {}", ty.name));
+            Control::Continue
+        }
+        fn enter_object_type_extension(&mut self, ext: &'a ObjectTypeExtension<'a>) -> Control {
+            self.names.push(format!("extend {}", ext.name));
+            Control::Skip
+        }
+        fn enter_field(&mut self, field: &'a Field<'a>) -> Control {
+            self.names.push(format!("field {}", field.name));
+            Control::Continue
+        }
+        fn enter_directive(&mut self, dir: &'a Directive<'a>) -> Control {
+            self.names.push(format!("@{}", dir.name));
+            Control::Continue
+        }
+        fn enter_enum_value(&mut self, value: &'a EnumValue<'a>) -> Control {
+            self.names.push(format!("value {}", value.name));
+            Control::Continue
+        }
+    }
+
+    #[test]
+    fn visit_all_kinds() {
+        let ast = parse_schema(SCHEMA).unwrap();
+        let mut counter = Counter::default();
+        walk_document(&mut counter, &ast);
+        assert_eq!(
+            counter.names,
+            vec![
+                "type Query",
+                "field user",
+                "@x",
+                "extend Query",
+                "value ADMIN",
+                "@internal",
+                "value USER",
+            ]
+        );
+    }
+
+    struct StripInternal;
+
+    impl<'a> Fold<'a> for StripInternal {
+        fn fold_field(&mut self, field: Field<'a>) -> Option<Field<'a>> {
+            if field.directives.iter().any(|d| d.name == "internal") {
+                None
+            } else {
+                fold_field(self, field)
+            }
+        }
+        fn fold_enum_value(&mut self, value: EnumValue<'a>) -> Option<EnumValue<'a>> {
+            if value.directives.iter().any(|d| d.name == "internal") {
+                None
+            } else {
+                fold_enum_value(self, value)
+            }
+        }
+        fn fold_definition(&mut self, def: Definition<'a>) -> Option<Definition<'a>> {
+            match def {
+                Definition::DirectiveDefinition(ref d) if d.name == "internal" => None,
+                def => fold_definition(self, def),
+            }
+        }
+    }
+
+    #[test]
+    fn fold_strips_members() {
+        let ast = StripInternal.fold_document(parse_schema(SCHEMA).unwrap());
+        assert_eq!(
+            ast.to_string(),
+            "type Query {\n  user(id: ID = 1 @x): User\n}\n\n\
+             extend type Query\n\n\
+             enum Role {\n  USER\n}\n"
+        );
+    }
+
+    struct Rename;
+
+    impl<'a> VisitMut<'a> for Rename {
+        fn visit_object_type_mut(&mut self, ty: &mut ObjectType<'a>) {
+            ty.name = "Root";
+            walk_object_type_mut(self, ty);
+        }
+        fn visit_input_value_mut(&mut self, input: &mut InputValue<'a>) {
+            input.name = "userId";
+            walk_input_value_mut(self, input);
+        }
+        fn visit_value_mut(&mut self, value: &mut Value<'a>) {
+            *value = Value::Null;
+        }
+    }
+
+    #[test]
+    fn visit_mut_in_place() {
+        let mut ast = parse_schema("type Query { user(id: ID = 1): User }").unwrap();
+        Rename.visit_document_mut(&mut ast);
+        assert_eq!(
+            ast.to_string(),
+            "type Root {\n  user(userId: ID = null): User\n}\n"
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Position-insensitive comparison and hashing of AST nodes
+//!
+//! The derived `PartialEq` of the AST compares positions too, so the same
+//! document parsed from differently formatted sources is unequal. The traits
+//! here compare and hash documents ignoring positions:
+//!
+//! ```rust
+//! # extern crate graphql_parser;
+//! use graphql_parser::parse_query;
+//! use graphql_parser::semantic::{SemanticEq, Semantics};
+//!
+//! let a = parse_query("{ user(id: 1, name: \"x\") { id } }").unwrap();
+//! let b = parse_query("{\n  user(name: \"x\", id: 1) {\n    id\n  }\n}").unwrap();
+//! assert!(!a.semantic_eq(&b));
+//! assert!(a.semantic_eq_with(&b, Semantics::default().ignore_argument_order(true)));
+//! ```
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Deref;
+
+use crate::common::{Directive, Number, Type, Value};
+
+/// Options of semantic comparison
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Semantics {
+    ignore_argument_order: bool,
+}
+
+impl Semantics {
+    /// Compare arguments of fields and directives regardless of their order
+    ///
+    /// Fields of object values are always compared regardless of order.
+    pub fn ignore_argument_order(&mut self, value: bool) -> &mut Self {
+        self.ignore_argument_order = value;
+        self
+    }
+}
+
+/// Equality ignoring positions of nodes
+pub trait SemanticEq {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool;
+
+    fn semantic_eq(&self, other: &Self) -> bool {
+        self.semantic_eq_with(other, &Semantics::default())
+    }
+}
+
+/// Hashing consistent with `SemanticEq`
+pub trait SemanticHash {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H);
+
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        self.semantic_hash_with(&Semantics::default(), state)
+    }
+}
+
+/// A wrapper implementing `Eq` and `Hash` by semantic comparison
+///
+/// Useful to deduplicate documents in a `HashSet` or `HashMap`.
+#[derive(Debug, Clone)]
+pub struct SemanticKey<T> {
+    node: T,
+    semantics: Semantics,
+}
+
+impl<T> SemanticKey<T> {
+    pub fn new(node: T) -> SemanticKey<T> {
+        SemanticKey::with_semantics(node, Semantics::default())
+    }
+
+    pub fn with_semantics(node: T, semantics: Semantics) -> SemanticKey<T> {
+        SemanticKey { node, semantics }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.node
+    }
+}
+
+impl<T> Deref for SemanticKey<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+impl<T: SemanticEq> PartialEq for SemanticKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node.semantic_eq_with(&other.node, &self.semantics)
+    }
+}
+
+impl<T: SemanticEq> Eq for SemanticKey<T> {}
+
+impl<T: SemanticHash> Hash for SemanticKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.semantic_hash_with(&self.semantics, state)
+    }
+}
+
+/// Implements the traits for structs, comparing only listed fields
+macro_rules! impl_semantic_struct {
+    ($($name:ident { $($field:ident),* })*) => {$(
+        impl<'a> $crate::semantic::SemanticEq for $name<'a> {
+            fn semantic_eq_with(&self, other: &Self,
+                semantics: &$crate::semantic::Semantics)
+                -> bool
+            {
+                true $(&& self.$field.semantic_eq_with(&other.$field, semantics))*
+            }
+        }
+
+        impl<'a> $crate::semantic::SemanticHash for $name<'a> {
+            fn semantic_hash_with<H: ::std::hash::Hasher>(&self,
+                semantics: &$crate::semantic::Semantics, state: &mut H)
+            {
+                $(self.$field.semantic_hash_with(semantics, state);)*
+            }
+        }
+
+        impl_semantic_list!($name<'a>);
+    )*};
+}
+
+/// Implements the traits for enums with single-field variants
+macro_rules! impl_semantic_enum {
+    ($($name:ident { $($variant:ident),* })*) => {$(
+        impl<'a> $crate::semantic::SemanticEq for $name<'a> {
+            fn semantic_eq_with(&self, other: &Self,
+                semantics: &$crate::semantic::Semantics)
+                -> bool
+            {
+                match (self, other) {
+                    $(($name::$variant(a), $name::$variant(b)) => {
+                        a.semantic_eq_with(b, semantics)
+                    })*
+                    _ => false,
+                }
+            }
+        }
+
+        impl<'a> $crate::semantic::SemanticHash for $name<'a> {
+            fn semantic_hash_with<H: ::std::hash::Hasher>(&self,
+                semantics: &$crate::semantic::Semantics, state: &mut H)
+            {
+                ::std::hash::Hash::hash(&::std::mem::discriminant(self), state);
+                match self {
+                    $($name::$variant(a) => a.semantic_hash_with(semantics, state),)*
+                }
+            }
+        }
+
+        impl_semantic_list!($name<'a>);
+    )*};
+}
+
+/// Implements the traits for `Vec` of a node, comparing items in order
+///
+/// There is no blanket implementation for `Vec<T>` so that argument lists
+/// can be compared differently.
+macro_rules! impl_semantic_list {
+    ($typ:ty) => {
+        impl<'a> $crate::semantic::SemanticEq for Vec<$typ> {
+            fn semantic_eq_with(
+                &self,
+                other: &Self,
+                semantics: &$crate::semantic::Semantics,
+            ) -> bool {
+                self.len() == other.len()
+                    && self
+                        .iter()
+                        .zip(other)
+                        .all(|(a, b)| a.semantic_eq_with(b, semantics))
+            }
+        }
+
+        impl<'a> $crate::semantic::SemanticHash for Vec<$typ> {
+            fn semantic_hash_with<H: ::std::hash::Hasher>(
+                &self,
+                semantics: &$crate::semantic::Semantics,
+                state: &mut H,
+            ) {
+                ::std::hash::Hash::hash(&self.len(), state);
+                for item in self {
+                    item.semantic_hash_with(semantics, state);
+                }
+            }
+        }
+    };
+}
+
+/// Implements the traits for types without positions using `Eq` and `Hash`
+macro_rules! impl_semantic_exact {
+    ($($typ:ty),*) => {$(
+        impl $crate::semantic::SemanticEq for $typ {
+            fn semantic_eq_with(&self, other: &Self, _: &$crate::semantic::Semantics) -> bool {
+                self == other
+            }
+        }
+
+        impl $crate::semantic::SemanticHash for $typ {
+            fn semantic_hash_with<H: ::std::hash::Hasher>(&self,
+                _: &$crate::semantic::Semantics, state: &mut H)
+            {
+                ::std::hash::Hash::hash(self, state)
+            }
+        }
+    )*};
+}
+
+impl_semantic_exact!(str, String, bool, Vec<&'_ str>);
+
+impl<T: SemanticEq + ?Sized> SemanticEq for &T {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool {
+        (**self).semantic_eq_with(*other, semantics)
+    }
+}
+
+impl<T: SemanticHash + ?Sized> SemanticHash for &T {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H) {
+        (**self).semantic_hash_with(semantics, state)
+    }
+}
+
+impl<T: SemanticEq> SemanticEq for Box<T> {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool {
+        (**self).semantic_eq_with(other, semantics)
+    }
+}
+
+impl<T: SemanticHash> SemanticHash for Box<T> {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H) {
+        (**self).semantic_hash_with(semantics, state)
+    }
+}
+
+impl<T: SemanticEq> SemanticEq for Option<T> {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.semantic_eq_with(b, semantics),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: SemanticHash> SemanticHash for Option<T> {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        if let Some(value) = self {
+            value.semantic_hash_with(semantics, state);
+        }
+    }
+}
+
+/// Floats are equal if they are equal numbers or both NaN
+fn float_eq(a: f64, b: f64) -> bool {
+    a == b || a.is_nan() && b.is_nan()
+}
+
+fn float_hash<H: Hasher>(value: f64, state: &mut H) {
+    if value == 0.0 {
+        0.0f64.to_bits().hash(state)
+    } else if value.is_nan() {
+        f64::NAN.to_bits().hash(state)
+    } else {
+        value.to_bits().hash(state)
+    }
+}
+
+impl SemanticEq for Number {
+    fn semantic_eq_with(&self, other: &Self, _: &Semantics) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl SemanticHash for Number {
+    fn semantic_hash_with<H: Hasher>(&self, _: &Semantics, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<'a> SemanticEq for Value<'a> {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool {
+        match (self, other) {
+            (Value::Variable(a), Value::Variable(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a.0 == b.0,
+            (Value::Float(a), Value::Float(b)) => float_eq(*a, *b),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Null, Value::Null) => true,
+            (Value::Enum(a), Value::Enum(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a.semantic_eq_with(b, semantics),
+            (Value::Object(a), Value::Object(b)) => a.semantic_eq_with(b, semantics),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> SemanticHash for Value<'a> {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Value::Variable(name) | Value::Enum(name) => name.hash(state),
+            Value::Int(num) => num.0.hash(state),
+            Value::Float(val) => float_hash(*val, state),
+            Value::String(val) => val.hash(state),
+            Value::Boolean(val) => val.hash(state),
+            Value::Null => {}
+            Value::List(items) => items.semantic_hash_with(semantics, state),
+            Value::Object(fields) => fields.semantic_hash_with(semantics, state),
+        }
+    }
+}
+
+impl_semantic_list!(Value<'a>);
+
+impl<'a> SemanticEq for BTreeMap<&'a str, Value<'a>> {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other)
+                .all(|((ka, va), (kb, vb))| ka == kb && va.semantic_eq_with(vb, semantics))
+    }
+}
+
+impl<'a> SemanticHash for BTreeMap<&'a str, Value<'a>> {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H) {
+        self.len().hash(state);
+        for (key, value) in self {
+            key.hash(state);
+            value.semantic_hash_with(semantics, state);
+        }
+    }
+}
+
+/// Returns arguments in the order they should be compared in
+fn arguments<'b, 'a>(
+    args: &'b [(&'a str, Value<'a>)],
+    semantics: &Semantics,
+) -> Vec<&'b (&'a str, Value<'a>)> {
+    let mut args = args.iter().collect::<Vec<_>>();
+    if semantics.ignore_argument_order {
+        args.sort_by_key(|(name, _)| *name);
+    }
+    args
+}
+
+impl<'a> SemanticEq for Vec<(&'a str, Value<'a>)> {
+    fn semantic_eq_with(&self, other: &Self, semantics: &Semantics) -> bool {
+        self.len() == other.len()
+            && arguments(self, semantics)
+                .into_iter()
+                .zip(arguments(other, semantics))
+                .all(|((ka, va), (kb, vb))| ka == kb && va.semantic_eq_with(vb, semantics))
+    }
+}
+
+impl<'a> SemanticHash for Vec<(&'a str, Value<'a>)> {
+    fn semantic_hash_with<H: Hasher>(&self, semantics: &Semantics, state: &mut H) {
+        self.len().hash(state);
+        for (name, value) in arguments(self, semantics) {
+            name.hash(state);
+            value.semantic_hash_with(semantics, state);
+        }
+    }
+}
+
+impl_semantic_struct! {
+    Directive { name, arguments }
+}
+
+impl_semantic_enum! {
+    Type { NamedType, ListType, NonNullType }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::Hasher;
+
+    use super::{SemanticEq, SemanticHash, SemanticKey, Semantics};
+    use crate::common::Value;
+    use crate::{parse_query, parse_schema};
+
+    fn hash<T: SemanticHash>(node: &T, semantics: &Semantics) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        node.semantic_hash_with(semantics, &mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn ignores_positions() {
+        let a = parse_query("query Q($a: Int = 1) { a(x: [1.5, {b: null}]) ...F }").unwrap();
+        let b = parse_query("query Q(\n  $a: Int = 1\n) {\n  a(x: [1.5, {b: null}])\n  ...F\n}")
+            .unwrap();
+        assert_ne!(a, b);
+        assert!(a.semantic_eq(&b));
+        let semantics = Semantics::default();
+        assert_eq!(hash(&a, &semantics), hash(&b, &semantics));
+    }
+
+    #[test]
+    fn compares_content() {
+        let a = parse_query("{ a(x: 1) }").unwrap();
+        for other in &["{ a(x: 2) }", "{ a(y: 1) }", "{ b(x: 1) }", "{ a(x: 1) b }"] {
+            assert!(!a.semantic_eq(&parse_query(other).unwrap()), "{}", other);
+        }
+        assert!(!a.semantic_eq(&parse_query("query { a(x: 1) }").unwrap()));
+    }
+
+    #[test]
+    fn argument_order() {
+        let a = parse_query("{ a(x: 1, y: 2) @d(a: 1, b: 2) }").unwrap();
+        let b = parse_query("{ a(y: 2, x: 1) @d(b: 2, a: 1) }").unwrap();
+        assert!(!a.semantic_eq(&b));
+        let mut semantics = Semantics::default();
+        semantics.ignore_argument_order(true);
+        assert!(a.semantic_eq_with(&b, &semantics));
+        assert_eq!(hash(&a, &semantics), hash(&b, &semantics));
+    }
+
+    #[test]
+    fn floats() {
+        let zero = Value::Float(0.0);
+        assert!(zero.semantic_eq(&Value::Float(-0.0)));
+        let semantics = Semantics::default();
+        assert_eq!(
+            hash(&zero, &semantics),
+            hash(&Value::Float(-0.0), &semantics)
+        );
+        assert!(Value::Float(f64::NAN).semantic_eq(&Value::Float(f64::NAN)));
+        assert!(!zero.semantic_eq(&Value::Int(0.into())));
+    }
+
+    #[test]
+    fn schemas() {
+        let a = parse_schema("\"d\" type A implements B @x { f(a: Int = 1): [A!] }").unwrap();
+        let b = parse_schema("\"d\"\ntype A implements B @x {\n  f(a: Int = 1): [A!]\n}").unwrap();
+        assert!(a.semantic_eq(&b));
+        let c = parse_schema("type A implements B @x { f(a: Int = 1): [A!] }").unwrap();
+        assert!(!a.semantic_eq(&c));
+    }
+
+    #[test]
+    fn dedup() {
+        let docs = [
+            parse_query("{ a }").unwrap(),
+            parse_query("{\n  a\n}").unwrap(),
+            parse_query("{ b }").unwrap(),
+        ];
+        let set = docs.iter().map(SemanticKey::new).collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+}
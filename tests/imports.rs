@@ -0,0 +1,77 @@
+extern crate graphql_parser;
+
+use std::path::Path;
+
+use graphql_parser::query::{Definition, LoadError, Sources};
+
+#[test]
+fn merges_imports() {
+    let sources = Sources::load("tests/imports/query.graphql").unwrap();
+    let paths = sources
+        .files()
+        .iter()
+        .map(|file| file.path())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec![
+            Path::new("tests/imports/query.graphql"),
+            Path::new("tests/imports/fragments/user.graphql"),
+            Path::new("tests/imports/fragments/avatar.graphql"),
+        ]
+    );
+    let doc = sources.parse().unwrap();
+    let names = doc
+        .definitions()
+        .map(|(file, def)| {
+            let name = match def {
+                Definition::Operation(op) => op.name().unwrap(),
+                Definition::Fragment(frag) => frag.name,
+            };
+            (name, file.path().file_name().unwrap().to_str().unwrap())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("User", "query.graphql"),
+            ("UserFields", "user.graphql"),
+            ("Avatar", "avatar.graphql"),
+        ]
+    );
+    assert_eq!(
+        doc.source(2).unwrap().text(),
+        "fragment Avatar on User {\n  avatar(size: 64)\n}\n"
+    );
+    assert!(doc.source(3).is_none());
+    assert!(doc.document().extract_operation(Some("User")).is_ok());
+}
+
+#[test]
+fn import_cycle() {
+    let err = Sources::load("tests/imports/cycle/a.graphql").unwrap_err();
+    match err {
+        LoadError::ImportCycle(ref cycle) => assert_eq!(
+            cycle,
+            &[
+                Path::new("tests/imports/cycle/a.graphql"),
+                Path::new("tests/imports/cycle/b.graphql"),
+                Path::new("tests/imports/cycle/a.graphql"),
+            ]
+        ),
+        ref err => panic!("unexpected error: {}", err),
+    }
+    assert_eq!(
+        err.to_string(),
+        "import cycle: tests/imports/cycle/a.graphql -> tests/imports/cycle/b.graphql \
+         -> tests/imports/cycle/a.graphql"
+    );
+}
+
+#[test]
+fn missing_import() {
+    match Sources::load("tests/imports/missing.graphql") {
+        Err(LoadError::Io(path, _)) => assert_eq!(path, Path::new("tests/imports/missing.graphql")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "persisted")]
+extern crate graphql_parser;
+
+use std::fs;
+
+use graphql_parser::parse_query;
+use graphql_parser::query::persisted::{document_hash, Allowlist, Manifest};
+
+#[test]
+fn manifest_from_dir() {
+    let manifest = Manifest::from_dir("tests/queries").unwrap();
+    assert!(!manifest.is_empty());
+    let source = fs::read_to_string("tests/queries/kitchen-sink.graphql").unwrap();
+    let id = document_hash(&parse_query(&source).unwrap()).unwrap();
+    assert!(manifest.get(&id).is_some());
+    // canonical form of both files is the same
+    let canonical = fs::read_to_string("tests/queries/kitchen-sink_canonical.graphql").unwrap();
+    assert_eq!(
+        document_hash(&parse_query(&canonical).unwrap()).unwrap(),
+        id
+    );
+
+    let allowlist = Allowlist::new(Manifest::from_json(&manifest.to_json()).unwrap());
+    assert!(allowlist.check(&id, Some(&source)).is_ok());
+}
+
+#[test]
+fn manifest_from_missing_dir() {
+    assert!(Manifest::from_dir("tests/no-such-dir").is_err());
+}
@@ -108,6 +108,10 @@ fn mutation_nameless_vars() {
     roundtrip_default("mutation_nameless_vars");
 }
 #[test]
+fn subscription_nameless_vars() {
+    roundtrip_default("subscription_nameless_vars");
+}
+#[test]
 fn subscription_directive() {
     roundtrip_default("subscription_directive");
 }
@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+extern crate graphql_parser;
+#[cfg(test)]
+#[macro_use]
+extern crate pretty_assertions;
+extern crate serde_json;
+
+use std::fs;
+
+use graphql_parser::{parse_query, parse_schema, query, schema};
+
+fn roundtrip_query(filename: &str) {
+    let path = format!("tests/queries/{}.graphql", filename);
+    let buf = fs::read_to_string(&path).unwrap();
+    let ast = parse_query(&buf).unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    let decoded: query::Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, ast);
+}
+
+fn roundtrip_schema(filename: &str) {
+    let path = format!("tests/schemas/{}.graphql", filename);
+    let buf = fs::read_to_string(&path).unwrap();
+    let ast = parse_schema(&buf).unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    let decoded: schema::Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, ast);
+}
+
+#[test]
+fn all_queries() {
+    for entry in fs::read_dir("tests/queries").unwrap() {
+        let path = entry.unwrap().path();
+        roundtrip_query(path.file_stem().unwrap().to_str().unwrap());
+    }
+}
+
+#[test]
+fn all_schemas() {
+    for entry in fs::read_dir("tests/schemas").unwrap() {
+        let path = entry.unwrap().path();
+        roundtrip_schema(path.file_stem().unwrap().to_str().unwrap());
+    }
+}
+
+#[test]
+fn tagged_representation() {
+    let ast = parse_query("{ a(x: 1, y: $v) }").unwrap();
+    let json = serde_json::to_value(&ast).unwrap();
+    let def = &json["definitions"][0];
+    assert_eq!(def["kind"], "Operation");
+    assert_eq!(def["value"]["kind"], "SelectionSet");
+    let field = &def["value"]["value"]["items"][0];
+    assert_eq!(field["kind"], "Field");
+    assert_eq!(field["value"]["name"], "a");
+    assert_eq!(field["value"]["position"]["line"], 1);
+    let args = &field["value"]["arguments"];
+    assert_eq!(args[0][1], serde_json::json!({"kind": "Int", "value": 1}));
+    assert_eq!(
+        args[1][1],
+        serde_json::json!({"kind": "Variable", "value": "v"})
+    );
+}
+
+#[test]
+fn directive_locations() {
+    let ast = parse_schema("directive @x on FIELD_DEFINITION | QUERY").unwrap();
+    let json = serde_json::to_value(&ast).unwrap();
+    let locations = &json["definitions"][0]["value"]["locations"];
+    assert_eq!(locations, &serde_json::json!(["FIELD_DEFINITION", "QUERY"]));
+}